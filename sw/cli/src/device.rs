@@ -5,25 +5,46 @@ pub use self::device_identifier::DeviceInfo;
 use hidapi::{HidApi, HidDevice};
 use kroneum_api::{
     adc::ADCChannel,
+    alarm::{EscalationConfig, SnoozeConfig},
     array::Array,
-    beeper::tone::Tone,
+    beeper::{
+        melody::Melody, morse, note::Note, stored_melody::StoredMelodyConfig, tone::Tone,
+        volume::BeeperConfig,
+    },
+    buttons::ButtonsConfig,
     config::{DEVICE_PID, DEVICE_VID},
-    flash::storage_slot::StorageSlot,
-    system::SystemInfo,
+    flash::{storage_slot::StorageSlot, storage_stats::StorageStats},
+    preset::Preset,
+    shortcut::{Shortcut, ShortcutTrigger},
+    system::{PomodoroConfig, SystemInfo},
     time::Time,
     usb::{
         command_packet::CommandPacket,
         commands::{
             ADCCommand, AlarmCommand, BeeperCommand, FlashCommand, KeyModifiers, KeyboardCommand,
-            MediaKey, RadioCommand, SystemCommand,
+            MediaKey, RadioCommand, SystemCommand, MAX_MELODY_TONES, MAX_MORSE_TEXT_LENGTH,
+            MELODY_QUEUE_FULL,
         },
     },
 };
 use std::convert::TryFrom;
-use std::time::Duration;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
 
 const MAX_ALARM_SECONDS: u64 = 3600 * 24;
 
+/// Max number of tones device can store in flash.
+const MAX_STORED_MELODY_TONES: usize = 64;
+
+/// Max time we wait for device to free up room for the next chunk of the streamed melody, it's
+/// enough to play a full buffer of the longest tones.
+const MELODY_CHUNK_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Interval between attempts to send the next chunk of the streamed melody.
+const MELODY_CHUNK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct Device {
     device: HidDevice,
     manufacturer: String,
@@ -71,6 +92,10 @@ impl Device {
     }
 
     pub fn beeper_melody(&self, tones: &[Tone]) -> Result<(), String> {
+        if tones.len() > MAX_MELODY_TONES {
+            return self.beeper_stream_melody(tones);
+        }
+
         self.send_command(CommandPacket::Beeper(BeeperCommand::Melody(Array::from(
             tones,
         ))))
@@ -78,36 +103,254 @@ impl Device {
         .map_err(|_| "Failed to play melody".to_string())
     }
 
-    pub fn get_alarm(&self) -> Result<Duration, String> {
-        if let Ok(response) = self.send_command(CommandPacket::Alarm(AlarmCommand::Get)) {
-            if response.len() == 3 {
-                return Ok(Duration::from_secs(
-                    u64::from(response[0]) * 3600
-                        + u64::from(response[1]) * 60
-                        + u64::from(response[2]),
-                ));
+    /// Short messages are encoded by the device itself, longer ones are encoded here and streamed
+    /// as a regular melody.
+    pub fn beeper_morse(&self, text: &str, wpm: u8, pitch: Note) -> Result<(), String> {
+        let mut tones = Vec::new();
+        morse::encode_each(text.as_bytes(), wpm, pitch, |tone| tones.push(tone)).map_err(|_| {
+            format!(
+                "Failed to encode Morse message, only ASCII letters, digits, punctuation and \
+                 spaces at {}-{} WPM are supported",
+                morse::MIN_WPM,
+                morse::MAX_WPM
+            )
+        })?;
+
+        if text.len() > MAX_MORSE_TEXT_LENGTH || morse::encode(text.as_bytes(), wpm, pitch).is_err()
+        {
+            return self.beeper_melody(&tones);
+        }
+
+        self.send_command(CommandPacket::Beeper(BeeperCommand::Morse(
+            wpm,
+            pitch,
+            Array::from(text.as_bytes()),
+        )))
+        .map(|_| ())
+        .map_err(|_| "Failed to play Morse message".to_string())
+    }
+
+    /// Melody that doesn't fit into a single packet is sent in chunks that device plays in
+    /// sequence. Device buffers only a few chunks and starts playing them once it's out of room, so
+    /// chunk is re-sent while device reports that its queue is full. Remaining chunks are played
+    /// once all of them are uploaded.
+    fn beeper_stream_melody(&self, tones: &[Tone]) -> Result<(), String> {
+        self.send_command(CommandPacket::Beeper(BeeperCommand::BeginMelody))
+            .map_err(|_| "Failed to play melody".to_string())?;
+
+        for chunk in tones.chunks(MAX_MELODY_TONES) {
+            let started_at = Instant::now();
+            loop {
+                let response = self
+                    .write(CommandPacket::Beeper(BeeperCommand::AppendMelody(
+                        Array::from(chunk),
+                    )))
+                    .and_then(|_| self.read())?;
+                match response.first() {
+                    Some(0x00) => break,
+                    Some(&MELODY_QUEUE_FULL) if started_at.elapsed() <= MELODY_CHUNK_TIMEOUT => {
+                        thread::sleep(MELODY_CHUNK_RETRY_INTERVAL)
+                    }
+                    Some(&MELODY_QUEUE_FULL) => {
+                        return Err("Timed out while streaming melody".to_string())
+                    }
+                    _ => return Err("Failed to play melody".to_string()),
+                }
             }
         }
 
-        Err("Failed to get alarm time".to_string())
+        self.send_command(CommandPacket::Beeper(BeeperCommand::CommitMelody))
+            .map(|_| ())
+            .map_err(|_| "Failed to play melody".to_string())
     }
 
-    pub fn set_alarm(&self, duration: Duration) -> Result<(), String> {
-        let duration_sec = duration.as_secs();
-        if duration_sec >= MAX_ALARM_SECONDS {
-            return Err("Alarm is limited to 23h 59m 59s".to_string());
+    /// Replaces melody stored in the device flash.
+    pub fn store_melody(&self, tones: &[Tone]) -> Result<(), String> {
+        if tones.len() > MAX_STORED_MELODY_TONES {
+            return Err(format!(
+                "Melody is too long to be stored ({} tones, max is {}).",
+                tones.len(),
+                MAX_STORED_MELODY_TONES
+            ));
         }
 
-        if self
-            .send_command(CommandPacket::Alarm(AlarmCommand::Set(Time::from_seconds(
-                duration_sec as u32,
-            ))))
-            .is_ok()
-        {
-            Ok(())
-        } else {
-            Err("Failed to set alarm".to_string())
+        self.clear_stored_melody()?;
+
+        // Melody doesn't fit into a single packet, so it's uploaded in chunks.
+        for chunk in tones.chunks(MAX_MELODY_TONES) {
+            self.send_command(CommandPacket::Beeper(BeeperCommand::AppendStoredMelody(
+                Array::from(chunk),
+            )))
+            .map_err(|_| "Failed to store melody".to_string())?;
         }
+
+        Ok(())
+    }
+
+    pub fn clear_stored_melody(&self) -> Result<(), String> {
+        self.send_command(CommandPacket::Beeper(BeeperCommand::ClearStoredMelody))
+            .map(|_| ())
+            .map_err(|_| "Failed to clear stored melody".to_string())
+    }
+
+    /// Returns stored melody config and number of stored tones.
+    pub fn get_stored_melody_config(&self) -> Result<(StoredMelodyConfig, usize), String> {
+        self.send_command(CommandPacket::Beeper(BeeperCommand::GetStoredMelodyConfig))
+            .map_err(|_| "Failed to get stored melody settings".to_string())
+            .and_then(|response| match response.as_slice() {
+                [flags, n_tones] => StoredMelodyConfig::try_from(*flags)
+                    .map(|config| (config, *n_tones as usize))
+                    .map_err(|_| "Received corrupted stored melody settings".to_string()),
+                _ => Err("Received corrupted stored melody settings".to_string()),
+            })
+    }
+
+    pub fn set_stored_melody_config(&self, config: StoredMelodyConfig) -> Result<(), String> {
+        self.send_command(CommandPacket::Beeper(BeeperCommand::SetStoredMelodyConfig(
+            config,
+        )))
+        .map(|_| ())
+        .map_err(|_| "Failed to set stored melody settings".to_string())
+    }
+
+    pub fn get_beeper_config(&self) -> Result<BeeperConfig, String> {
+        self.send_command(CommandPacket::Beeper(BeeperCommand::GetConfig))
+            .map_err(|_| "Failed to get volume settings".to_string())
+            .and_then(|response| {
+                BeeperConfig::try_from(response.as_slice())
+                    .map_err(|_| "Received corrupted volume settings".to_string())
+            })
+    }
+
+    pub fn set_beeper_config(&self, config: BeeperConfig) -> Result<(), String> {
+        self.send_command(CommandPacket::Beeper(BeeperCommand::SetConfig(config)))
+            .map(|_| ())
+            .map_err(|_| "Failed to set volume settings".to_string())
+    }
+
+    pub fn get_alarm(&self) -> Result<Time, String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::Get))
+            .map_err(|_| "Failed to get alarm time".to_string())
+            .and_then(Self::parse_time)
+    }
+
+    pub fn get_alarm_remaining(&self) -> Result<Duration, String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::GetRemaining))
+            .map_err(|_| "Failed to get remaining alarm time, alarm may not be pending".to_string())
+            .and_then(Self::parse_time)
+            .map(|time| Duration::from_secs(u64::from(time.as_seconds())))
+    }
+
+    pub fn get_pending_alarm(&self) -> Result<Time, String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::GetPending))
+            .map_err(|_| "Failed to get pending alarm, alarm may not be pending".to_string())
+            .and_then(Self::parse_time)
+    }
+
+    pub fn set_alarm(&self, duration: Duration) -> Result<(), String> {
+        let time = Self::alarm_time(duration)?;
+        self.send_command(CommandPacket::Alarm(AlarmCommand::Set(time)))
+            .map(|_| ())
+            .map_err(|_| "Failed to set alarm".to_string())
+    }
+
+    pub fn set_recurring_alarm(&self, duration: Duration) -> Result<(), String> {
+        let time = Self::alarm_time(duration)?;
+        self.send_command(CommandPacket::Alarm(AlarmCommand::SetRecurring(time)))
+            .map(|_| ())
+            .map_err(|_| "Failed to set recurring alarm".to_string())
+    }
+
+    pub fn set_alarm_at(&self, time: Time) -> Result<(), String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::SetAt(time)))
+            .map(|_| ())
+            .map_err(|_| "Failed to set alarm".to_string())
+    }
+
+    pub fn pause_alarm(&self) -> Result<(), String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::Pause))
+            .map(|_| ())
+            .map_err(|_| "Failed to pause alarm, is it active?".to_string())
+    }
+
+    pub fn resume_alarm(&self) -> Result<(), String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::Resume))
+            .map(|_| ())
+            .map_err(|_| "Failed to resume alarm, is it paused?".to_string())
+    }
+
+    pub fn extend_alarm(&self, minutes: u8) -> Result<(), String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::Extend(minutes)))
+            .map(|_| ())
+            .map_err(|_| "Failed to extend alarm, is it active or paused?".to_string())
+    }
+
+    pub fn cancel_alarm(&self) -> Result<(), String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::Cancel))
+            .map(|_| ())
+            .map_err(|_| "Failed to cancel alarm, is it active or paused?".to_string())
+    }
+
+    pub fn get_snooze(&self) -> Result<SnoozeConfig, String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::GetSnooze))
+            .map_err(|_| "Failed to get snooze settings".to_string())
+            .and_then(|response| {
+                SnoozeConfig::try_from(response.as_slice())
+                    .map_err(|_| "Received corrupted snooze settings".to_string())
+            })
+    }
+
+    pub fn set_snooze(&self, config: SnoozeConfig) -> Result<(), String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::SetSnooze(config)))
+            .map(|_| ())
+            .map_err(|_| "Failed to set snooze settings".to_string())
+    }
+
+    pub fn get_escalation(&self) -> Result<EscalationConfig, String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::GetEscalation))
+            .map_err(|_| "Failed to get escalation settings".to_string())
+            .and_then(|response| {
+                EscalationConfig::try_from(response.as_slice())
+                    .map_err(|_| "Received corrupted escalation settings".to_string())
+            })
+    }
+
+    pub fn set_escalation(&self, config: EscalationConfig) -> Result<(), String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::SetEscalation(config)))
+            .map(|_| ())
+            .map_err(|_| "Failed to set escalation settings".to_string())
+    }
+
+    pub fn get_preset(&self, index: u8) -> Result<Option<Preset>, String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::GetPreset(index)))
+            .map_err(|_| format!("Failed to get preset #{}", index))
+            .and_then(|response| match response.as_slice() {
+                [0, 0, 0, 0] => Ok(None),
+                response => Preset::try_from(response)
+                    .map(Some)
+                    .map_err(|_| format!("Received corrupted preset #{}", index)),
+            })
+    }
+
+    pub fn set_preset(
+        &self,
+        index: u8,
+        duration: Duration,
+        melody: Option<Melody>,
+    ) -> Result<(), String> {
+        let preset = Preset {
+            duration: Self::alarm_time(duration)?,
+            melody,
+        };
+        self.send_command(CommandPacket::Alarm(AlarmCommand::SetPreset(index, preset)))
+            .map(|_| ())
+            .map_err(|_| format!("Failed to set preset #{}", index))
+    }
+
+    pub fn delete_preset(&self, index: u8) -> Result<(), String> {
+        self.send_command(CommandPacket::Alarm(AlarmCommand::DeletePreset(index)))
+            .map(|_| ())
+            .map_err(|_| format!("Failed to delete preset #{}", index))
     }
 
     pub fn read_flash(&self, slot: StorageSlot) -> Result<u8, String> {
@@ -138,6 +381,15 @@ impl Device {
             .map_err(|_| "Could not erase flash".to_string())
     }
 
+    pub fn get_flash_stats(&self) -> Result<StorageStats, String> {
+        self.send_command(CommandPacket::Flash(FlashCommand::Stats))
+            .map_err(|_| "Failed to get flash stats".to_string())
+            .and_then(|response| {
+                StorageStats::try_from(Array::from(&response))
+                    .map_err(|_| "Received corrupted flash stats".to_string())
+            })
+    }
+
     pub fn system_reset(&self) -> Result<(), String> {
         self.send_command(CommandPacket::System(SystemCommand::Reset))
             .map(|_| ())
@@ -160,6 +412,52 @@ impl Device {
             })
     }
 
+    pub fn get_time(&self) -> Result<Time, String> {
+        self.send_command(CommandPacket::System(SystemCommand::GetTime))
+            .map_err(|_| "Failed to get device time".to_string())
+            .and_then(Self::parse_time)
+    }
+
+    pub fn set_time(&self, time: Time) -> Result<(), String> {
+        self.send_command(CommandPacket::System(SystemCommand::SetTime(time)))
+            .map(|_| ())
+            .map_err(|_| "Failed to set device time".to_string())
+    }
+
+    pub fn get_buttons_config(&self) -> Result<ButtonsConfig, String> {
+        self.send_command(CommandPacket::System(SystemCommand::GetButtonsConfig))
+            .map_err(|_| "Failed to get buttons settings".to_string())
+            .and_then(|response| {
+                ButtonsConfig::try_from(response.as_slice())
+                    .map_err(|_| "Received corrupted buttons settings".to_string())
+            })
+    }
+
+    pub fn set_buttons_config(&self, config: ButtonsConfig) -> Result<(), String> {
+        self.send_command(CommandPacket::System(SystemCommand::SetButtonsConfig(
+            config,
+        )))
+        .map(|_| ())
+        .map_err(|_| "Failed to set buttons settings".to_string())
+    }
+
+    pub fn get_pomodoro_config(&self) -> Result<PomodoroConfig, String> {
+        self.send_command(CommandPacket::System(SystemCommand::GetPomodoroConfig))
+            .map_err(|_| "Failed to get Pomodoro settings".to_string())
+            .and_then(|response| {
+                PomodoroConfig::try_from(response.as_slice())
+                    .map_err(|_| "Received corrupted Pomodoro settings".to_string())
+            })
+    }
+
+    pub fn set_pomodoro_config(&self, config: PomodoroConfig) -> Result<(), String> {
+        self.send_command(CommandPacket::System(SystemCommand::SetPomodoroConfig(
+            config,
+        )))
+        .map(|_| ())
+        .map_err(|_| "Failed to set Pomodoro settings".to_string())
+    }
+
     pub fn adc_read(&self, channel: ADCChannel) -> Result<u16, String> {
         info!("Reading ADC for {:?}.", channel);
         self.send_command(CommandPacket::ADC(ADCCommand::Read(channel)))
@@ -206,6 +504,56 @@ impl Device {
         .map_err(|_| "Failed to send a keyboard media key".to_string())
     }
 
+    pub fn get_shortcut(&self, trigger: ShortcutTrigger) -> Result<Option<Shortcut>, String> {
+        self.send_command(CommandPacket::Keyboard(KeyboardCommand::GetShortcut(
+            trigger,
+        )))
+        .map_err(|_| format!("Failed to get shortcut for {:?}", trigger))
+        .and_then(|response| match response.as_slice() {
+            [0, 0, 0] => Ok(None),
+            response => Shortcut::try_from(response)
+                .map(Some)
+                .map_err(|_| format!("Received corrupted shortcut for {:?}", trigger)),
+        })
+    }
+
+    pub fn set_shortcut(&self, trigger: ShortcutTrigger, shortcut: Shortcut) -> Result<(), String> {
+        self.send_command(CommandPacket::Keyboard(KeyboardCommand::SetShortcut(
+            trigger, shortcut,
+        )))
+        .map(|_| ())
+        .map_err(|_| format!("Failed to set shortcut for {:?}", trigger))
+    }
+
+    pub fn delete_shortcut(&self, trigger: ShortcutTrigger) -> Result<(), String> {
+        self.send_command(CommandPacket::Keyboard(KeyboardCommand::DeleteShortcut(
+            trigger,
+        )))
+        .map(|_| ())
+        .map_err(|_| format!("Failed to delete shortcut for {:?}", trigger))
+    }
+
+    fn parse_time(response: Vec<u8>) -> Result<Time, String> {
+        if response.len() == 3 {
+            Ok(Time {
+                hours: response[0],
+                minutes: response[1],
+                seconds: response[2],
+            })
+        } else {
+            Err("Received corrupted time".to_string())
+        }
+    }
+
+    fn alarm_time(duration: Duration) -> Result<Time, String> {
+        let duration_sec = duration.as_secs();
+        if duration_sec >= MAX_ALARM_SECONDS {
+            return Err("Alarm is limited to 23h 59m 59s".to_string());
+        }
+
+        Ok(Time::from_seconds(duration_sec as u32))
+    }
+
     fn send_command(&self, packet: CommandPacket) -> Result<Vec<u8>, String> {
         self.write(packet)
             .and_then(|_| self.read())
@@ -4,12 +4,324 @@
 extern crate log;
 
 mod device;
+mod midi;
 mod ui;
+mod wav;
 
+use chrono::{Local, Timelike};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use device::Device;
-use kroneum_api::flash::storage_slot::StorageSlot;
-use std::time::Duration;
+use kroneum_api::{
+    alarm::EscalationConfig,
+    array::Array,
+    beeper::{
+        melody::Melody,
+        morse,
+        note::Note,
+        rtttl,
+        score::{Score, DEFAULT_TEMPO},
+        stored_melody::StoredMelodyConfig,
+        tone::Tone,
+        volume::{Volume, MAX_DUTY_CYCLE},
+    },
+    buttons::ButtonsConfig,
+    flash::storage_slot::StorageSlot,
+    preset::PRESETS_COUNT,
+    shortcut::{Shortcut, ShortcutTrigger},
+    system::PomodoroConfig,
+    time::Time,
+    usb::commands::{KeyModifiers, MediaKey},
+};
+use std::{convert::TryFrom, fs, path::Path, time::Duration};
+
+/// Pomodoro settings: CLI argument, description and accessor of the corresponding config value.
+const POMODORO_SETTINGS: [(&str, &str, fn(&mut PomodoroConfig) -> &mut u8); 4] = [
+    ("work", "Work interval (minutes)", |config| &mut config.work),
+    ("short-break", "Short break (minutes)", |config| {
+        &mut config.short_break
+    }),
+    ("long-break", "Long break (minutes)", |config| {
+        &mut config.long_break
+    }),
+    ("cycles", "Work intervals before long break", |config| {
+        &mut config.cycles
+    }),
+];
+
+/// Buttons timings: CLI argument, description and accessor of the corresponding config value.
+const BUTTONS_SETTINGS: [(&str, &str, fn(&mut ButtonsConfig) -> &mut u32); 5] = [
+    ("poll-interval", "Buttons poll interval (ms)", |config| {
+        &mut config.poll_interval
+    }),
+    ("short-press", "Max short press duration (ms)", |config| {
+        &mut config.short_press
+    }),
+    ("long-press", "Min long press duration (ms)", |config| {
+        &mut config.long_press
+    }),
+    (
+        "very-long-press",
+        "Min very long press duration (ms)",
+        |config| &mut config.very_long_press,
+    ),
+    (
+        "double-click",
+        "Max pause between double click presses (ms)",
+        |config| &mut config.double_click,
+    ),
+];
+
+/// Alarm escalation settings: CLI argument, description and accessor of the corresponding config
+/// value.
+const ESCALATION_SETTINGS: [(&str, &str, fn(&mut EscalationConfig) -> &mut u8); 4] = [
+    (
+        "volume-step",
+        "Duty cycle added on every snooze (%)",
+        |config| &mut config.volume_step,
+    ),
+    (
+        "repeat-step",
+        "Extra melody repeats on every snooze",
+        |config| &mut config.repeat_step,
+    ),
+    (
+        "interval-step",
+        "Snooze interval reduction on every snooze (seconds)",
+        |config| &mut config.interval_step,
+    ),
+    ("limit", "Number of snoozes alarm escalates for", |config| {
+        &mut config.limit
+    }),
+];
+
+/// Sounds that can use the melody stored in flash: CLI argument, description and accessor of the
+/// corresponding config flag.
+const STORED_MELODY_SOUNDS: [(&str, &str, fn(&mut StoredMelodyConfig) -> &mut bool); 3] = [
+    ("alarm", "Alarm melody", |config| &mut config.alarm),
+    ("snooze", "Snooze melody", |config| &mut config.snooze),
+    ("setup", "Setup melody", |config| &mut config.setup),
+];
+
+/// Value of the `Configuration` flash slot that makes device boot in Pomodoro mode.
+const POMODORO_ROLE: u8 = 0x2;
+
+/// Number of erase cycles STM32F042 flash page is rated for.
+const FLASH_ENDURANCE_CYCLES: u16 = 10_000;
+
+/// Share of the flash endurance (in percent) after which user is warned that storage may wear out.
+const FLASH_WEAR_WARNING_PERCENT: u32 = 80;
+
+/// Melodies that can be used for the snooze and presets: CLI argument value and melody.
+const MELODIES: [(&str, Melody); 8] = [
+    ("alarm", Melody::Alarm),
+    ("beep", Melody::Beep),
+    ("reset", Melody::Reset),
+    ("setup", Melody::Setup),
+    ("work", Melody::Work),
+    ("short-break", Melody::ShortBreak),
+    ("long-break", Melody::LongBreak),
+    ("stored", Melody::Stored),
+];
+
+/// Volume variants of the alarm melody: CLI argument value and volume.
+const VOLUMES: [(&str, Volume); 3] = [
+    ("normal", Volume::Normal),
+    ("quiet", Volume::Quiet),
+    ("crescendo", Volume::Crescendo),
+];
+
+/// Buttons presses and alarm shortcuts can be bound to: CLI argument value and trigger.
+const SHORTCUT_TRIGGERS: [(&str, ShortcutTrigger); 5] = [
+    ("1-short", ShortcutTrigger::OneShort),
+    ("1-long", ShortcutTrigger::OneLong),
+    ("10-short", ShortcutTrigger::TenShort),
+    ("10-long", ShortcutTrigger::TenLong),
+    ("alarm", ShortcutTrigger::Alarm),
+];
+
+/// Media keys shortcut can send: CLI argument value and media key.
+const MEDIA_KEYS: [(&str, MediaKey); 7] = [
+    ("volume-up", MediaKey::VolumeUp),
+    ("volume-down", MediaKey::VolumeDown),
+    ("mute", MediaKey::Mute),
+    ("next-track", MediaKey::NextTrack),
+    ("previous-track", MediaKey::PreviousTrack),
+    ("play-pause", MediaKey::PlayPause),
+    ("stop", MediaKey::Stop),
+];
+
+/// Keyboard combo modifiers: CLI argument value and the bit in the modifiers byte.
+const KEY_MODIFIERS: [(&str, u8); 8] = [
+    ("ctrl", 0),
+    ("shift", 1),
+    ("alt", 2),
+    ("gui", 3),
+    ("right-ctrl", 4),
+    ("right-shift", 5),
+    ("right-alt", 6),
+    ("right-gui", 7),
+];
+
+/// Returns human readable representation of the shortcut, e.g. `ctrl+shift+0x10` or `mute`.
+fn format_shortcut(shortcut: Shortcut) -> String {
+    match shortcut {
+        Shortcut::Key(modifiers, key_code) => {
+            let modifiers: u8 = modifiers.into();
+            KEY_MODIFIERS
+                .iter()
+                .filter(|(_, bit)| modifiers & (1 << bit) != 0)
+                .map(|(name, _)| name.to_string())
+                .chain(std::iter::once(format!("0x{:02x}", key_code)))
+                .collect::<Vec<_>>()
+                .join("+")
+        }
+        Shortcut::Media(media_key) => MEDIA_KEYS
+            .iter()
+            .find(|(_, known_key)| *known_key == media_key)
+            .map(|(name, _)| name.to_string())
+            .unwrap_or_else(|| "unknown".to_string()),
+    }
+}
+
+/// Returns CLI argument value of the specified built-in melody.
+fn melody_name(melody: Melody) -> &'static str {
+    MELODIES
+        .iter()
+        .find(|(_, known_melody)| *known_melody == melody)
+        .map(|(name, _)| *name)
+        .unwrap_or_else(|| "unknown")
+}
+
+/// Imports melody from the MIDI file passed with the <midi> argument using the track and channel
+/// passed with the <track> and <channel> arguments, if any.
+fn midi_melody(matches: &ArgMatches) -> Result<midi::MidiMelody, String> {
+    let path = matches
+        .value_of("midi")
+        .ok_or_else(|| "<midi> argument is not provided.".to_string())?;
+    let bytes =
+        fs::read(path).or_else(|err| Err(format!("Failed to read MIDI file: {:?}", err)))?;
+
+    // Tracks and channels are numbered starting from 1, the same way MIDI editors do.
+    let parse_number = |arg_name: &str, max: usize| -> Result<Option<usize>, String> {
+        match matches.value_of(arg_name) {
+            Some(number_str) => match number_str.parse::<usize>() {
+                Ok(number) if number > 0 && number <= max => Ok(Some(number - 1)),
+                _ => Err(format!(
+                    "<{}> argument must be between 1 and {}.",
+                    arg_name, max
+                )),
+            },
+            None => Ok(None),
+        }
+    };
+
+    midi::import(
+        &bytes,
+        parse_number("track", usize::from(u16::max_value()))?,
+        parse_number("channel", 16)?.map(|channel| channel as u8),
+    )
+}
+
+/// Reads melody passed either as RTTTL or as text notes, both can be passed as a string or as a
+/// path to a file with it, as a path to the MIDI file, or as a name of the built-in melody, and
+/// compiles it into tones transposed if needed.
+fn melody_tones(matches: &ArgMatches) -> Result<Vec<Tone>, String> {
+    let (arg_name, melody) = ["rtttl", "notes", "midi", "built-in"]
+        .iter()
+        .find_map(|arg_name| matches.value_of(arg_name).map(|value| (*arg_name, value)))
+        .ok_or_else(|| {
+            "Either <rtttl>, <notes>, <midi> or <built-in> argument should be provided.".to_string()
+        })?;
+
+    let mut tones = Vec::new();
+    if arg_name == "midi" {
+        tones = midi_melody(matches)?.tones();
+    } else if arg_name == "built-in" {
+        // Stored melody lives in the device flash, so it can't be resolved on the host.
+        let built_in_melody = MELODIES
+            .iter()
+            .find(|(name, built_in_melody)| *name == melody && *built_in_melody != Melody::Stored)
+            .map(|(_, built_in_melody)| *built_in_melody)
+            .ok_or_else(|| format!("Unknown built-in melody: {}", melody))?;
+        tones.extend_from_slice(Into::<Array<Tone>>::into(built_in_melody).as_ref());
+    } else {
+        let melody = if Path::new(melody).is_file() {
+            fs::read_to_string(melody)
+                .or_else(|err| Err(format!("Failed to read melody file: {:?}", err)))?
+        } else {
+            melody.to_string()
+        };
+
+        if arg_name == "rtttl" {
+            rtttl::parse_each(&melody, |tone| tones.push(tone))
+                .map_err(|_| "Failed to parse RTTTL melody".to_string())?;
+        } else {
+            let tempo = match matches.value_of("tempo") {
+                Some(tempo_str) => tempo_str
+                    .parse::<u32>()
+                    .or_else(|err| Err(format!("Failed to parse <tempo> argument: {:?}", err)))?,
+                None => DEFAULT_TEMPO,
+            };
+
+            // Score is limited by the number of notes, but text can be longer, so it's compiled in
+            // parts.
+            let notes = melody.split_whitespace().collect::<Vec<_>>();
+            for part in notes.chunks(32) {
+                Score::parse(tempo, &part.join(" "))
+                    .map_err(|_| "Failed to parse melody notes".to_string())?
+                    .for_each_tone(|tone| tones.push(tone));
+            }
+        }
+    }
+
+    if let Some(transpose_str) = matches.value_of("transpose") {
+        let semitones = transpose_str
+            .parse::<i8>()
+            .or_else(|err| Err(format!("Failed to parse <transpose> argument: {:?}", err)))?;
+
+        for tone in tones.iter_mut() {
+            tone.note = Note::try_from(tone.note)
+                .and_then(|note| note.transpose(semitones))
+                .map(|note| note as u8)
+                .map_err(|_| "Transposed melody is out of the note range".to_string())?;
+        }
+    }
+
+    Ok(tones)
+}
+
+/// Parses time of the day in the `hh:mm` or `hh:mm:ss` form.
+fn parse_time_of_day(time_str: &str) -> Result<Time, String> {
+    let parts = time_str
+        .split(':')
+        .map(|part| part.parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .or_else(|err| Err(format!("Failed to parse time: {:?}", err)))?;
+
+    let time = match parts.as_slice() {
+        [hours, minutes] => Time {
+            hours: *hours,
+            minutes: *minutes,
+            seconds: 0,
+        },
+        [hours, minutes, seconds] => Time {
+            hours: *hours,
+            minutes: *minutes,
+            seconds: *seconds,
+        },
+        _ => return Err("Time should be in the hh:mm[:ss] form.".to_string()),
+    };
+
+    if time.hours > 23 || time.minutes > 59 || time.seconds > 59 {
+        return Err(format!("Time {} is out of range.", time_str));
+    }
+
+    Ok(time)
+}
+
+fn format_time(time: Time) -> String {
+    format!("{:02}:{:02}:{:02}", time.hours, time.minutes, time.seconds)
+}
 
 fn process_command(matches: ArgMatches) -> Result<(), String> {
     match matches.subcommand() {
@@ -25,40 +337,274 @@ fn process_command(matches: ArgMatches) -> Result<(), String> {
                     })?,
             )?;
         }
+        ("morse", Some(matches)) => {
+            let wpm = match matches.value_of("wpm") {
+                Some(wpm_str) => wpm_str
+                    .parse::<u8>()
+                    .or_else(|err| Err(format!("Failed to parse <wpm> argument: {:?}", err)))?,
+                None => morse::DEFAULT_WPM,
+            };
+
+            let pitch = match matches.value_of("pitch") {
+                Some(pitch_str) => match pitch_str.parse::<Note>() {
+                    Ok(Note::Silence) | Err(_) => {
+                        return Err(format!("Unknown <pitch> note: {}", pitch_str));
+                    }
+                    Ok(pitch) => pitch,
+                },
+                None => morse::DEFAULT_PITCH,
+            };
+
+            Device::create()?.beeper_morse(matches.value_of("TEXT").unwrap(), wpm, pitch)?;
+        }
+        ("melody", Some(matches)) => match matches.value_of("ACTION").unwrap() {
+            action @ "play" | action @ "store" => {
+                let tones = melody_tones(&matches)?;
+                let device = Device::create()?;
+                if action == "play" {
+                    device.beeper_melody(&tones)?;
+                } else {
+                    device.store_melody(&tones)?;
+                    println!("Melody is stored ({} tones).", tones.len());
+                }
+            }
+            "render" => {
+                let tones = melody_tones(&matches)?;
+                let repeat = matches
+                    .value_of("repeat")
+                    .unwrap()
+                    .parse::<usize>()
+                    .or_else(|err| Err(format!("Failed to parse <repeat> argument: {:?}", err)))?;
+
+                let duty_cycle = match matches.value_of("duty-cycle") {
+                    Some(duty_cycle_str) => duty_cycle_str.parse::<u8>().or_else(|err| {
+                        Err(format!("Failed to parse <duty-cycle> argument: {:?}", err))
+                    })?,
+                    None => MAX_DUTY_CYCLE,
+                };
+                if duty_cycle == 0 || duty_cycle > MAX_DUTY_CYCLE {
+                    return Err(format!(
+                        "<duty-cycle> argument must be between 1 and {}.",
+                        MAX_DUTY_CYCLE
+                    ));
+                }
+
+                let volume = match matches.value_of("volume") {
+                    Some(volume_str) => VOLUMES
+                        .iter()
+                        .find(|(name, _)| *name == volume_str)
+                        .map(|(_, volume)| *volume)
+                        .ok_or_else(|| format!("Unknown volume: {}", volume_str))?,
+                    None => Volume::Normal,
+                };
+
+                let out = Path::new(matches.value_of("out").unwrap());
+                let samples = wav::render(&tones, repeat, duty_cycle, volume);
+                wav::write(out, &samples)?;
+                println!(
+                    "Melody is rendered to {} ({} tones, {} repetition(s)).",
+                    out.display(),
+                    tones.len(),
+                    repeat
+                );
+            }
+            "import" => {
+                let melody = midi_melody(&matches)?;
+                let out = Path::new(matches.value_of("out").unwrap());
+                let name = Path::new(matches.value_of("midi").unwrap())
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or("MIDI");
+                fs::write(out, melody.to_rtttl(name))
+                    .or_else(|err| Err(format!("Failed to write RTTTL file: {:?}", err)))?;
+                println!(
+                    "Melody is imported to {} ({} notes).",
+                    out.display(),
+                    melody.notes.len()
+                );
+            }
+            "clear" => {
+                Device::create()?.clear_stored_melody()?;
+                println!("Stored melody is cleared.");
+            }
+            "get" => {
+                let (mut config, n_tones) = Device::create()?.get_stored_melody_config()?;
+                println!("Stored melody (tones): {}", n_tones);
+                for (_, description, use_stored) in STORED_MELODY_SOUNDS.iter() {
+                    println!(
+                        "{}: {}",
+                        description,
+                        if *use_stored(&mut config) {
+                            "stored"
+                        } else {
+                            "built-in"
+                        }
+                    );
+                }
+            }
+            _ => {
+                let device = Device::create()?;
+                let (mut config, _) = device.get_stored_melody_config()?;
+                for (arg_name, _, use_stored) in STORED_MELODY_SOUNDS.iter() {
+                    if let Some(source) = matches.value_of(arg_name) {
+                        *use_stored(&mut config) = source == "stored";
+                    }
+                }
+
+                device.set_stored_melody_config(config)?;
+                println!("Stored melody settings are updated.");
+            }
+        },
         ("info", _) => {
             let device = Device::create()?;
             println!("Kroneum ({})", device.get_info(),);
         }
         ("alarm", Some(matches)) => match matches.value_of("ACTION").unwrap_or_else(|| "get") {
+            "set" if matches.is_present("at") => {
+                let alarm = matches
+                    .value_of("ALARM")
+                    .ok_or_else(|| "<ALARM> argument is not provided.".to_string())
+                    .and_then(parse_time_of_day)?;
+
+                Device::create()?.set_alarm_at(alarm)?;
+                println!(
+                    "Alarm is armed for {}, device switched to Timer mode.",
+                    format_time(alarm)
+                );
+            }
             "set" => {
-                Device::create()?.set_alarm(
-                    matches
-                        .value_of("ALARM")
-                        .ok_or_else(|| "<ALARM> argument is not provided.".to_string())
-                        .and_then(|alarm_str| {
-                            alarm_str.parse::<humantime::Duration>().or_else(|err| {
-                                Err(format!("Failed to parse <ALARM> argument: {:?}", err))
-                            })
+                let alarm = matches
+                    .value_of("ALARM")
+                    .ok_or_else(|| "<ALARM> argument is not provided.".to_string())
+                    .and_then(|alarm_str| {
+                        alarm_str.parse::<humantime::Duration>().or_else(|err| {
+                            Err(format!("Failed to parse <ALARM> argument: {:?}", err))
                         })
-                        .map(|alarm_human| {
-                            let duration: Duration = alarm_human.into();
-                            duration
-                        })?,
-                )?;
+                    })
+                    .map(|alarm_human| {
+                        let duration: Duration = alarm_human.into();
+                        duration
+                    })?;
+
+                if matches.is_present("repeat") {
+                    Device::create()?.set_recurring_alarm(alarm)?;
+                    println!(
+                        "Alarm is armed to repeat every {}, device switched to Timer mode.",
+                        humantime::Duration::from(alarm)
+                    );
+                } else {
+                    Device::create()?.set_alarm(alarm)?;
+                    println!(
+                        "Alarm is armed for {}, device switched to Timer mode.",
+                        humantime::Duration::from(alarm)
+                    );
+                }
             }
             "get" => {
                 println!(
                     "Current alarm is set to: {}",
-                    humantime::Duration::from(Device::create()?.get_alarm()?)
+                    format_time(Device::create()?.get_alarm()?)
+                );
+            }
+            "pause" => {
+                Device::create()?.pause_alarm()?;
+                println!("Alarm is paused.");
+            }
+            "resume" => {
+                Device::create()?.resume_alarm()?;
+                println!("Alarm is resumed.");
+            }
+            "extend" => {
+                let minutes = matches
+                    .value_of("minutes")
+                    .ok_or_else(|| "<minutes> argument is not provided.".to_string())
+                    .and_then(|minutes_str| {
+                        minutes_str.parse::<u8>().or_else(|err| {
+                            Err(format!("Failed to parse <minutes> argument: {:?}", err))
+                        })
+                    })?;
+
+                if minutes == 0 {
+                    return Err("<minutes> argument must be positive.".to_string());
+                }
+
+                Device::create()?.extend_alarm(minutes)?;
+                println!("Alarm is extended by {} minutes.", minutes);
+            }
+            "cancel" => {
+                Device::create()?.cancel_alarm()?;
+                println!("Alarm is cancelled.");
+            }
+            "remaining" => {
+                println!(
+                    "Time left until alarm: {}",
+                    humantime::Duration::from(Device::create()?.get_alarm_remaining()?)
+                );
+            }
+            "pending" => {
+                println!(
+                    "Pending alarm fires at: {}",
+                    format_time(Device::create()?.get_pending_alarm()?)
                 );
             }
             _ => {}
         },
+        ("time", Some(matches)) => match matches.value_of("ACTION").unwrap() {
+            "sync" => {
+                let now = Local::now();
+                let time = Time {
+                    hours: now.hour() as u8,
+                    minutes: now.minute() as u8,
+                    seconds: now.second() as u8,
+                };
+
+                Device::create()?.set_time(time)?;
+                println!("Device time is set to {}.", format_time(time));
+            }
+            _ => println!(
+                "Device time: {}",
+                format_time(Device::create()?.get_time()?)
+            ),
+        },
         ("flash", Some(matches)) => match matches.value_of("ACTION").unwrap() {
             "erase" => {
                 Device::create()?.erase_flash()?;
                 println!("Flash is erased");
             }
+            "stats" => {
+                let stats = Device::create()?.get_flash_stats()?;
+                for (index, erase_count) in stats.erase_counts.iter().enumerate() {
+                    println!(
+                        "Page {} erase cycles: {} of {}{}",
+                        index + 1,
+                        erase_count,
+                        FLASH_ENDURANCE_CYCLES,
+                        if index == stats.active_page as usize {
+                            " (active)"
+                        } else {
+                            ""
+                        }
+                    );
+                }
+                println!("Rollovers: {}", stats.rollover_count);
+                println!(
+                    "Active page usage: {} of {} bytes ({}%)",
+                    stats.used_size,
+                    stats.page_size,
+                    u32::from(stats.used_size) * 100 / u32::from(stats.page_size).max(1)
+                );
+
+                let max_erase_count = stats.erase_counts.iter().max().cloned().unwrap_or(0);
+                if u32::from(max_erase_count) * 100
+                    >= u32::from(FLASH_ENDURANCE_CYCLES) * FLASH_WEAR_WARNING_PERCENT
+                {
+                    println!(
+                        "Warning: flash has been erased {} times, it's rated for {} erase cycles \
+                         and may soon fail to retain settings.",
+                        max_erase_count, FLASH_ENDURANCE_CYCLES
+                    );
+                }
+            }
             operation => {
                 let device = Device::create()?;
                 let slot = StorageSlot::Custom(
@@ -95,6 +641,326 @@ fn process_command(matches: ArgMatches) -> Result<(), String> {
             }
         },
 
+        ("pomodoro", Some(matches)) => match matches.value_of("ACTION").unwrap() {
+            "get" => {
+                let mut config = Device::create()?.get_pomodoro_config()?;
+                for (_, description, value) in POMODORO_SETTINGS.iter() {
+                    println!("{}: {}", description, value(&mut config));
+                }
+            }
+            "set" => {
+                let device = Device::create()?;
+                let mut config = device.get_pomodoro_config()?;
+                for (arg_name, _, value) in POMODORO_SETTINGS.iter() {
+                    if let Some(value_str) = matches.value_of(arg_name) {
+                        *value(&mut config) = value_str.parse::<u8>().or_else(|err| {
+                            Err(format!(
+                                "Failed to parse <{}> argument: {:?}",
+                                arg_name, err
+                            ))
+                        })?;
+
+                        if *value(&mut config) == 0 {
+                            return Err(format!("<{}> argument must be positive.", arg_name));
+                        }
+                    }
+                }
+
+                device.set_pomodoro_config(config)?;
+                println!("Pomodoro settings are updated.");
+            }
+            action => {
+                let device = Device::create()?;
+                device.write_flash(
+                    StorageSlot::Configuration,
+                    if action == "enable" { POMODORO_ROLE } else { 0 },
+                )?;
+                println!("Device is being reset...");
+                device.system_reset()?;
+            }
+        },
+
+        ("snooze", Some(matches)) => match matches.value_of("ACTION").unwrap() {
+            "get" => {
+                let config = Device::create()?.get_snooze()?;
+                println!("Snooze interval (seconds): {}", config.interval);
+                println!(
+                    "Snooze limit: {}",
+                    if config.limit == 0 {
+                        "unlimited".to_string()
+                    } else {
+                        config.limit.to_string()
+                    }
+                );
+                println!("Snooze melody: {}", melody_name(config.melody));
+            }
+            _ => {
+                let device = Device::create()?;
+                let mut config = device.get_snooze()?;
+                if let Some(interval_str) = matches.value_of("interval") {
+                    config.interval = interval_str.parse::<u8>().or_else(|err| {
+                        Err(format!("Failed to parse <interval> argument: {:?}", err))
+                    })?;
+
+                    if config.interval == 0 {
+                        return Err("<interval> argument must be positive.".to_string());
+                    }
+                }
+
+                if let Some(limit_str) = matches.value_of("limit") {
+                    config.limit = limit_str.parse::<u8>().or_else(|err| {
+                        Err(format!("Failed to parse <limit> argument: {:?}", err))
+                    })?;
+                }
+
+                if let Some(melody_str) = matches.value_of("melody") {
+                    config.melody = MELODIES
+                        .iter()
+                        .find(|(name, _)| *name == melody_str)
+                        .map(|(_, melody)| *melody)
+                        .ok_or_else(|| format!("Unknown melody: {}", melody_str))?;
+                }
+
+                device.set_snooze(config)?;
+                println!("Snooze settings are updated.");
+            }
+        },
+
+        ("escalation", Some(matches)) => match matches.value_of("ACTION").unwrap() {
+            "get" => {
+                let mut config = Device::create()?.get_escalation()?;
+                for (_, description, value) in ESCALATION_SETTINGS.iter() {
+                    println!("{}: {}", description, value(&mut config));
+                }
+            }
+            _ => {
+                let device = Device::create()?;
+                let mut config = device.get_escalation()?;
+                for (arg_name, _, value) in ESCALATION_SETTINGS.iter() {
+                    if let Some(value_str) = matches.value_of(arg_name) {
+                        *value(&mut config) = value_str.parse::<u8>().or_else(|err| {
+                            Err(format!(
+                                "Failed to parse <{}> argument: {:?}",
+                                arg_name, err
+                            ))
+                        })?;
+                    }
+                }
+
+                device.set_escalation(config)?;
+                println!("Escalation settings are updated.");
+            }
+        },
+
+        ("volume", Some(matches)) => match matches.value_of("ACTION").unwrap() {
+            "get" => {
+                let config = Device::create()?.get_beeper_config()?;
+                println!("Duty cycle (%): {}", config.duty_cycle);
+                println!(
+                    "Alarm volume: {}",
+                    VOLUMES
+                        .iter()
+                        .find(|(_, volume)| *volume == config.alarm_volume)
+                        .map(|(name, _)| *name)
+                        .unwrap_or_else(|| "unknown")
+                );
+            }
+            _ => {
+                let device = Device::create()?;
+                let mut config = device.get_beeper_config()?;
+                if let Some(duty_cycle_str) = matches.value_of("duty-cycle") {
+                    config.duty_cycle = duty_cycle_str.parse::<u8>().or_else(|err| {
+                        Err(format!("Failed to parse <duty-cycle> argument: {:?}", err))
+                    })?;
+
+                    if config.duty_cycle == 0 || config.duty_cycle > MAX_DUTY_CYCLE {
+                        return Err(format!(
+                            "<duty-cycle> argument must be between 1 and {}.",
+                            MAX_DUTY_CYCLE
+                        ));
+                    }
+                }
+
+                if let Some(volume_str) = matches.value_of("alarm") {
+                    config.alarm_volume = VOLUMES
+                        .iter()
+                        .find(|(name, _)| *name == volume_str)
+                        .map(|(_, volume)| *volume)
+                        .ok_or_else(|| format!("Unknown volume: {}", volume_str))?;
+                }
+
+                device.set_beeper_config(config)?;
+                println!("Volume settings are updated.");
+            }
+        },
+
+        ("preset", Some(matches)) => match matches.value_of("ACTION").unwrap() {
+            "list" => {
+                let device = Device::create()?;
+                for index in 1..=PRESETS_COUNT {
+                    match device.get_preset(index)? {
+                        Some(preset) => println!(
+                            "Preset #{}: {} ({})",
+                            index,
+                            format_time(preset.duration),
+                            preset.melody.map(melody_name).unwrap_or_else(|| "alarm")
+                        ),
+                        None => println!("Preset #{}: not set", index),
+                    }
+                }
+            }
+            action => {
+                let index = matches
+                    .value_of("INDEX")
+                    .ok_or_else(|| "<INDEX> argument is not provided.".to_string())
+                    .and_then(|index_str| {
+                        index_str.parse::<u8>().or_else(|err| {
+                            Err(format!("Failed to parse <INDEX> argument: {:?}", err))
+                        })
+                    })?;
+
+                if action == "delete" {
+                    Device::create()?.delete_preset(index)?;
+                    println!("Preset #{} is deleted.", index);
+                    return Ok(());
+                }
+
+                let duration = matches
+                    .value_of("DURATION")
+                    .ok_or_else(|| "<DURATION> argument is not provided.".to_string())
+                    .and_then(|duration_str| {
+                        duration_str.parse::<humantime::Duration>().or_else(|err| {
+                            Err(format!("Failed to parse <DURATION> argument: {:?}", err))
+                        })
+                    })
+                    .map(|duration_human| {
+                        let duration: Duration = duration_human.into();
+                        duration
+                    })?;
+
+                let melody = match matches.value_of("melody") {
+                    Some(melody_str) => Some(
+                        MELODIES
+                            .iter()
+                            .find(|(name, _)| *name == melody_str)
+                            .map(|(_, melody)| *melody)
+                            .ok_or_else(|| format!("Unknown melody: {}", melody_str))?,
+                    ),
+                    None => None,
+                };
+
+                Device::create()?.set_preset(index, duration, melody)?;
+                println!(
+                    "Preset #{} is set to {}.",
+                    index,
+                    humantime::Duration::from(duration)
+                );
+            }
+        },
+
+        ("buttons", Some(matches)) => match matches.value_of("ACTION").unwrap() {
+            "get" => {
+                let mut config = Device::create()?.get_buttons_config()?;
+                for (_, description, value) in BUTTONS_SETTINGS.iter() {
+                    println!("{}: {}", description, value(&mut config));
+                }
+            }
+            _ => {
+                let device = Device::create()?;
+                let mut config = device.get_buttons_config()?;
+                for (arg_name, _, value) in BUTTONS_SETTINGS.iter() {
+                    if let Some(value_str) = matches.value_of(arg_name) {
+                        *value(&mut config) = value_str.parse::<u32>().or_else(|err| {
+                            Err(format!(
+                                "Failed to parse <{}> argument: {:?}",
+                                arg_name, err
+                            ))
+                        })?;
+                    }
+                }
+
+                device.set_buttons_config(config)?;
+                println!("Buttons settings are updated (values are rounded down to 50ms).");
+            }
+        },
+
+        ("shortcut", Some(matches)) => match matches.value_of("ACTION").unwrap() {
+            "list" => {
+                let device = Device::create()?;
+                for (name, trigger) in SHORTCUT_TRIGGERS.iter() {
+                    match device.get_shortcut(*trigger)? {
+                        Some(shortcut) => {
+                            println!("Shortcut {}: {}", name, format_shortcut(shortcut))
+                        }
+                        None => println!("Shortcut {}: not set", name),
+                    }
+                }
+            }
+            action => {
+                let (name, trigger) = matches
+                    .value_of("TRIGGER")
+                    .ok_or_else(|| "<TRIGGER> argument is not provided.".to_string())
+                    .and_then(|trigger_str| {
+                        SHORTCUT_TRIGGERS
+                            .iter()
+                            .find(|(name, _)| *name == trigger_str)
+                            .ok_or_else(|| format!("Unknown trigger: {}", trigger_str))
+                    })?;
+
+                if action == "delete" {
+                    Device::create()?.delete_shortcut(*trigger)?;
+                    println!("Shortcut {} is deleted.", name);
+                    return Ok(());
+                }
+
+                let shortcut = if let Some(media_str) = matches.value_of("media") {
+                    MEDIA_KEYS
+                        .iter()
+                        .find(|(name, _)| *name == media_str)
+                        .map(|(_, media_key)| Shortcut::Media(*media_key))
+                        .ok_or_else(|| format!("Unknown media key: {}", media_str))?
+                } else {
+                    let key_code = matches
+                        .value_of("key")
+                        .ok_or_else(|| {
+                            "Either <key> or <media> argument should be provided.".to_string()
+                        })
+                        .and_then(|key_str| {
+                            if key_str.starts_with("0x") {
+                                u8::from_str_radix(&key_str[2..], 16)
+                            } else {
+                                key_str.parse::<u8>()
+                            }
+                            .or_else(|err| {
+                                Err(format!("Failed to parse <key> argument: {:?}", err))
+                            })
+                        })?;
+
+                    if key_code == 0 {
+                        return Err("<key> argument must be positive.".to_string());
+                    }
+
+                    let modifiers = matches
+                        .values_of("modifiers")
+                        .map(|values| {
+                            values.fold(0u8, |modifiers, modifier_str| {
+                                KEY_MODIFIERS
+                                    .iter()
+                                    .find(|(name, _)| *name == modifier_str)
+                                    .map(|(_, bit)| modifiers | (1 << bit))
+                                    .unwrap_or(modifiers)
+                            })
+                        })
+                        .unwrap_or(0);
+
+                    Shortcut::Key(KeyModifiers::from(modifiers), key_code)
+                };
+
+                Device::create()?.set_shortcut(*trigger, shortcut)?;
+                println!("Shortcut {} is set to {}.", name, format_shortcut(shortcut));
+            }
+        },
+
         ("reset", _) => {
             println!("Device is being reset...");
             Device::create()?.system_reset()?
@@ -135,6 +1001,162 @@ fn main() -> Result<(), String> {
                         .help("Defines number of beeps"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("morse")
+                .about("Plays <TEXT> as Morse code")
+                .arg(
+                    Arg::with_name("TEXT")
+                        .index(1)
+                        .required(true)
+                        .help("ASCII letters, digits, punctuation and spaces"),
+                )
+                .arg(
+                    Arg::with_name("wpm")
+                        .long("wpm")
+                        .takes_value(true)
+                        .help("Speed in words per minute (5-40), 20 by default"),
+                )
+                .arg(
+                    Arg::with_name("pitch")
+                        .long("pitch")
+                        .takes_value(true)
+                        .help("Note to play Morse code with (e.g. C6 or F#5), A5 by default"),
+                ),
+        )
+        .subcommand(
+            STORED_MELODY_SOUNDS.iter().fold(
+                SubCommand::with_name("melody")
+                    .about("Plays melodies and manages the melody stored in Kroneum")
+                    .arg(
+                        Arg::with_name("ACTION")
+                            .index(1)
+                            .required(true)
+                            .possible_values(
+                                ["play", "render", "import", "store", "clear", "get", "set"]
+                                    .as_ref(),
+                            )
+                            .help(
+                                "Plays or renders melody to WAV file, imports MIDI file to RTTTL \
+                                 file, stores or clears custom melody, gets or sets sounds that \
+                                 use it",
+                            ),
+                    )
+                    .arg(
+                        Arg::with_name("rtttl")
+                            .long("rtttl")
+                            .takes_value(true)
+                            .conflicts_with_all(&["notes", "midi", "built-in"])
+                            .help("RTTTL (Nokia ringtone) melody or path to a file with it"),
+                    )
+                    .arg(
+                        Arg::with_name("notes")
+                            .long("notes")
+                            .takes_value(true)
+                            .help(
+                                "Melody notes (e.g. \"C5/4 E5/8. R/2 G#5\") or path to a file \
+                                 with them",
+                            ),
+                    )
+                    .arg(
+                        Arg::with_name("midi")
+                            .long("midi")
+                            .takes_value(true)
+                            .conflicts_with_all(&["notes", "built-in"])
+                            .required_if("ACTION", "import")
+                            .help(
+                                "Path to the MIDI file, polyphony is collapsed to the highest \
+                                 note",
+                            ),
+                    )
+                    .arg(
+                        Arg::with_name("track")
+                            .long("track")
+                            .takes_value(true)
+                            .requires("midi")
+                            .help("MIDI track (1-based), the first one with notes by default"),
+                    )
+                    .arg(
+                        Arg::with_name("channel")
+                            .long("channel")
+                            .takes_value(true)
+                            .requires("midi")
+                            .help("MIDI channel (1-16), all but percussion (10) by default"),
+                    )
+                    .arg(
+                        Arg::with_name("built-in")
+                            .long("built-in")
+                            .takes_value(true)
+                            .conflicts_with("notes")
+                            .possible_values(
+                                MELODIES
+                                    .iter()
+                                    .map(|(name, _)| *name)
+                                    .filter(|name| *name != "stored")
+                                    .collect::<Vec<_>>()
+                                    .as_ref(),
+                            )
+                            .help("Built-in melody"),
+                    )
+                    .arg(
+                        Arg::with_name("tempo")
+                            .long("tempo")
+                            .takes_value(true)
+                            .help("Tempo (beats per minute) of the melody notes, 120 by default"),
+                    )
+                    .arg(
+                        Arg::with_name("transpose")
+                            .long("transpose")
+                            .takes_value(true)
+                            .allow_hyphen_values(true)
+                            .help("Number of semitones to shift the melody by, can be negative"),
+                    )
+                    .arg(
+                        Arg::with_name("out")
+                            .long("out")
+                            .takes_value(true)
+                            .required_ifs(&[("ACTION", "render"), ("ACTION", "import")])
+                            .help(
+                                "Path to the WAV file melody is rendered to or to the RTTTL file \
+                                 MIDI file is imported to",
+                            ),
+                    )
+                    .arg(
+                        Arg::with_name("repeat")
+                            .long("repeat")
+                            .takes_value(true)
+                            .default_value("1")
+                            .help("Number of times rendered melody is repeated"),
+                    )
+                    .arg(
+                        Arg::with_name("duty-cycle")
+                            .long("duty-cycle")
+                            .takes_value(true)
+                            .help("PWM duty cycle (%) rendered melody is played with, 50 by default"),
+                    )
+                    .arg(
+                        Arg::with_name("volume")
+                            .long("volume")
+                            .takes_value(true)
+                            .possible_values(
+                                VOLUMES
+                                    .iter()
+                                    .map(|(name, _)| *name)
+                                    .collect::<Vec<_>>()
+                                    .as_ref(),
+                            )
+                            .help("Volume variant rendered melody is played with"),
+                    ),
+                |subcommand, (arg_name, description, _)| {
+                    subcommand.arg(
+                        Arg::with_name(arg_name)
+                            .long(arg_name)
+                            .takes_value(true)
+                            .possible_values(["built-in", "stored"].as_ref())
+                            .help(description),
+                    )
+                },
+            ),
+        )
         .subcommand(SubCommand::with_name("info").about("Prints information about Kroneum device"))
         .subcommand(
             SubCommand::with_name("alarm")
@@ -142,8 +1164,23 @@ fn main() -> Result<(), String> {
                 .arg(
                     Arg::with_name("ACTION")
                         .index(1)
-                        .possible_values(["set", "get"].as_ref())
-                        .help("Sets or gets Kroneum alarm"),
+                        .possible_values(
+                            [
+                                "set",
+                                "get",
+                                "remaining",
+                                "pending",
+                                "pause",
+                                "resume",
+                                "extend",
+                                "cancel",
+                            ]
+                            .as_ref(),
+                        )
+                        .help(
+                            "Sets, gets, pauses, resumes, extends or cancels Kroneum alarm, or \
+                             shows when pending alarm fires",
+                        ),
                 )
                 .arg(
                     Arg::with_name("ALARM")
@@ -151,6 +1188,35 @@ fn main() -> Result<(), String> {
                         .default_value("5s")
                         .required_if("ACTION", "set")
                         .help("Alarm to set in the hh:mm:ss form."),
+                )
+                .arg(
+                    Arg::with_name("minutes")
+                        .long("minutes")
+                        .takes_value(true)
+                        .default_value("10")
+                        .help("Number of minutes (1-255) to extend alarm by"),
+                )
+                .arg(
+                    Arg::with_name("repeat")
+                        .long("repeat")
+                        .help("Re-arms alarm every time it fires until it's cancelled"),
+                )
+                .arg(
+                    Arg::with_name("at")
+                        .long("at")
+                        .conflicts_with("repeat")
+                        .help("Treats <ALARM> as a time of the day in the hh:mm[:ss] form"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("time")
+                .about("Manages Kroneum wall-clock time")
+                .arg(
+                    Arg::with_name("ACTION")
+                        .index(1)
+                        .required(true)
+                        .possible_values(["get", "sync"].as_ref())
+                        .help("Gets device time or sets it to the local time of the host"),
                 ),
         )
         .subcommand(
@@ -160,8 +1226,11 @@ fn main() -> Result<(), String> {
                     Arg::with_name("ACTION")
                         .index(1)
                         .required(true)
-                        .possible_values(["read", "write", "erase"].as_ref())
-                        .help("Reads from, writes to or erases Kroneum flash memory"),
+                        .possible_values(["read", "write", "erase", "stats"].as_ref())
+                        .help(
+                            "Reads from, writes to or erases Kroneum flash memory, or shows its \
+                             wear statistics",
+                        ),
                 )
                 .arg(
                     Arg::with_name("SLOT")
@@ -177,6 +1246,234 @@ fn main() -> Result<(), String> {
                         .help("Value to write to a memory slot. Value must be an unsigned byte."),
                 ),
         )
+        .subcommand(
+            POMODORO_SETTINGS.iter().fold(
+                SubCommand::with_name("pomodoro")
+                    .about("Manages Kroneum Pomodoro mode")
+                    .arg(
+                        Arg::with_name("ACTION")
+                            .index(1)
+                            .required(true)
+                            .possible_values(["get", "set", "enable", "disable"].as_ref())
+                            .help(
+                                "Gets or sets Pomodoro intervals, enables or disables Pomodoro mode",
+                            ),
+                    ),
+                |subcommand, (arg_name, description, _)| {
+                    subcommand.arg(
+                        Arg::with_name(arg_name)
+                            .long(arg_name)
+                            .takes_value(true)
+                            .help(description),
+                    )
+                },
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("snooze")
+                .about("Manages Kroneum alarm snooze")
+                .arg(
+                    Arg::with_name("ACTION")
+                        .index(1)
+                        .required(true)
+                        .possible_values(["get", "set"].as_ref())
+                        .help("Gets or sets snooze interval, limit and melody"),
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .takes_value(true)
+                        .help("Snooze interval (seconds)"),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .takes_value(true)
+                        .help("Number of snoozes before alarm gives up (0 means unlimited)"),
+                )
+                .arg(
+                    Arg::with_name("melody")
+                        .long("melody")
+                        .takes_value(true)
+                        .possible_values(
+                            MELODIES
+                                .iter()
+                                .map(|(name, _)| *name)
+                                .collect::<Vec<_>>()
+                                .as_ref(),
+                        )
+                        .help("Melody played on every snooze"),
+                ),
+        )
+        .subcommand(
+            ESCALATION_SETTINGS.iter().fold(
+                SubCommand::with_name("escalation")
+                    .about("Manages how Kroneum alarm escalates on every snooze")
+                    .arg(
+                        Arg::with_name("ACTION")
+                            .index(1)
+                            .required(true)
+                            .possible_values(["get", "set"].as_ref())
+                            .help("Gets or sets alarm escalation steps and limit"),
+                    ),
+                |subcommand, (arg_name, description, _)| {
+                    subcommand.arg(
+                        Arg::with_name(arg_name)
+                            .long(arg_name)
+                            .takes_value(true)
+                            .help(description),
+                    )
+                },
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("volume")
+                .about("Manages Kroneum beeper volume")
+                .arg(
+                    Arg::with_name("ACTION")
+                        .index(1)
+                        .required(true)
+                        .possible_values(["get", "set"].as_ref())
+                        .help("Gets or sets beeper volume and alarm volume variant"),
+                )
+                .arg(
+                    Arg::with_name("duty-cycle")
+                        .long("duty-cycle")
+                        .takes_value(true)
+                        .help("PWM duty cycle (%) that defines volume, 50 is the loudest"),
+                )
+                .arg(
+                    Arg::with_name("alarm")
+                        .long("alarm")
+                        .takes_value(true)
+                        .possible_values(
+                            VOLUMES
+                                .iter()
+                                .map(|(name, _)| *name)
+                                .collect::<Vec<_>>()
+                                .as_ref(),
+                        )
+                        .help("Volume variant of the alarm and snooze melodies"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("preset")
+                .about("Manages Kroneum alarm presets")
+                .arg(
+                    Arg::with_name("ACTION")
+                        .index(1)
+                        .required(true)
+                        .possible_values(["list", "set", "delete"].as_ref())
+                        .help("Lists, sets or deletes alarm presets"),
+                )
+                .arg(
+                    Arg::with_name("INDEX")
+                        .index(2)
+                        .required_ifs(&[("ACTION", "set"), ("ACTION", "delete")])
+                        .possible_values(&["1", "2", "3"])
+                        .help("Index of the preset."),
+                )
+                .arg(
+                    Arg::with_name("DURATION")
+                        .index(3)
+                        .required_if("ACTION", "set")
+                        .help("Duration of the preset alarm, e.g. 25m or 1h 30m."),
+                )
+                .arg(
+                    Arg::with_name("melody")
+                        .long("melody")
+                        .takes_value(true)
+                        .possible_values(
+                            MELODIES
+                                .iter()
+                                .map(|(name, _)| *name)
+                                .collect::<Vec<_>>()
+                                .as_ref(),
+                        )
+                        .help("Melody played when preset alarm fires"),
+                ),
+        )
+        .subcommand(
+            BUTTONS_SETTINGS.iter().fold(
+                SubCommand::with_name("buttons")
+                    .about("Manages Kroneum buttons timings")
+                    .arg(
+                        Arg::with_name("ACTION")
+                            .index(1)
+                            .required(true)
+                            .possible_values(["get", "set"].as_ref())
+                            .help("Gets or sets buttons poll interval and press thresholds"),
+                    ),
+                |subcommand, (arg_name, description, _)| {
+                    subcommand.arg(
+                        Arg::with_name(arg_name)
+                            .long(arg_name)
+                            .takes_value(true)
+                            .help(description),
+                    )
+                },
+            ),
+        )
+        .subcommand(
+            SubCommand::with_name("shortcut")
+                .about("Manages keyboard shortcuts sent by Kroneum buttons and alarm")
+                .arg(
+                    Arg::with_name("ACTION")
+                        .index(1)
+                        .required(true)
+                        .possible_values(["list", "set", "delete"].as_ref())
+                        .help("Lists, sets or deletes buttons shortcuts"),
+                )
+                .arg(
+                    Arg::with_name("TRIGGER")
+                        .index(2)
+                        .required_ifs(&[("ACTION", "set"), ("ACTION", "delete")])
+                        .possible_values(
+                            SHORTCUT_TRIGGERS
+                                .iter()
+                                .map(|(name, _)| *name)
+                                .collect::<Vec<_>>()
+                                .as_ref(),
+                        )
+                        .help("Button press or alarm that triggers shortcut."),
+                )
+                .arg(
+                    Arg::with_name("key")
+                        .long("key")
+                        .takes_value(true)
+                        .conflicts_with("media")
+                        .help("HID usage ID of the key to send, e.g. 0x10 for `M`"),
+                )
+                .arg(
+                    Arg::with_name("modifiers")
+                        .long("modifiers")
+                        .takes_value(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .requires("key")
+                        .possible_values(
+                            KEY_MODIFIERS
+                                .iter()
+                                .map(|(name, _)| *name)
+                                .collect::<Vec<_>>()
+                                .as_ref(),
+                        )
+                        .help("Comma separated modifiers pressed together with the key"),
+                )
+                .arg(
+                    Arg::with_name("media")
+                        .long("media")
+                        .takes_value(true)
+                        .possible_values(
+                            MEDIA_KEYS
+                                .iter()
+                                .map(|(name, _)| *name)
+                                .collect::<Vec<_>>()
+                                .as_ref(),
+                        )
+                        .help("Media key to send"),
+                ),
+        )
         .subcommand(SubCommand::with_name("reset").about("Resets Kroneum device"))
         .subcommand(
             SubCommand::with_name("ui")
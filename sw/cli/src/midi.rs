@@ -0,0 +1,456 @@
+use kroneum_api::beeper::{
+    note::Note,
+    score::{split_duration, NoteLength, NoteValue},
+    tone::Tone,
+};
+use std::convert::TryFrom;
+
+/// Tempo (microseconds per quarter note) MIDI file is played with until it sets a different one.
+const DEFAULT_MICROS_PER_QUARTER: u32 = 500_000;
+
+/// Channel that General MIDI reserves for percussion, it's skipped unless selected explicitly.
+const PERCUSSION_CHANNEL: u8 = 9;
+
+/// MIDI note numbers of `C0` and `B8`, notes outside of this range are shifted by octaves.
+const MIN_MIDI_NOTE: u8 = 12;
+const MAX_MIDI_NOTE: u8 = 119;
+
+/// RTTTL note lengths that notes are quantized to: length in 1/32 note units, RTTTL duration and
+/// whether the note is dotted.
+const RTTTL_LENGTHS: [(u32, u32, bool); 10] = [
+    (32, 1, false),
+    (24, 2, true),
+    (16, 2, false),
+    (12, 4, true),
+    (8, 4, false),
+    (6, 8, true),
+    (4, 8, false),
+    (3, 16, true),
+    (2, 16, false),
+    (1, 32, false),
+];
+
+/// Monophonic melody imported from the MIDI file.
+pub struct MidiMelody {
+    /// Notes (`Note::Silence` for rests) and their durations in ms.
+    pub notes: Vec<(Note, u32)>,
+    /// Duration of the quarter note (in ms) at the initial tempo of the file.
+    pub quarter_ms: f64,
+}
+
+impl MidiMelody {
+    /// Converts melody into tones, notes that are longer than a single tone can last are split
+    /// into several consecutive tones of the same note.
+    pub fn tones(&self) -> Vec<Tone> {
+        let mut tones = Vec::new();
+        for (note, duration_ms) in self.notes.iter() {
+            for chunk_ms in split_duration(*duration_ms) {
+                tones.push(Tone::new(*note as u8, chunk_ms));
+            }
+        }
+
+        tones
+    }
+
+    /// Formats melody as RTTTL ringtone quantizing note durations to 1/32 notes at the initial
+    /// tempo of the file.
+    pub fn to_rtttl(&self, name: &str) -> String {
+        // Find RTTTL tempo that makes quarter note as long as in the MIDI file.
+        let quarter = NoteLength::new(NoteValue::Quarter, false);
+        let tempo = (f64::from(quarter.duration_ms(1)) / self.quarter_ms)
+            .round()
+            .max(1.0) as u32;
+        let unit_ms = f64::from(quarter.duration_ms(1)) / f64::from(tempo) / 8.0;
+
+        let mut notes = Vec::new();
+        let (mut elapsed_ms, mut elapsed_units) = (0.0, 0);
+        for (note, duration_ms) in self.notes.iter() {
+            // Round absolute time instead of durations, so that rounding errors don't accumulate.
+            elapsed_ms += f64::from(*duration_ms);
+            let end_units = (elapsed_ms / unit_ms).round() as u32;
+            let mut units = end_units.saturating_sub(elapsed_units);
+            elapsed_units = end_units.max(elapsed_units);
+
+            while units > 0 {
+                let (length_units, duration, is_dotted) = RTTTL_LENGTHS
+                    .iter()
+                    .find(|(length_units, _, _)| *length_units <= units)
+                    .unwrap();
+                notes.push(format!(
+                    "{}{}{}",
+                    duration,
+                    rtttl_note(*note),
+                    if *is_dotted { "." } else { "" }
+                ));
+                units -= length_units;
+            }
+        }
+
+        format!(
+            "{}:d=4,o=5,b={}:{}",
+            name.replace(':', " "),
+            tempo,
+            notes.join(",")
+        )
+    }
+}
+
+/// Imports monophonic melody from the Standard MIDI File. Notes are taken from the specified track
+/// (first one with notes by default) and channel (all but percussion by default), if several notes
+/// sound at the same time, the highest one is played.
+pub fn import(
+    bytes: &[u8],
+    track: Option<usize>,
+    channel: Option<u8>,
+) -> Result<MidiMelody, String> {
+    let mut reader = Reader::new(bytes);
+    let (n_tracks, division) = reader.header()?;
+
+    let mut tracks = Vec::new();
+    let mut tempo_changes = Vec::new();
+    for _ in 0..n_tracks {
+        let (events, track_tempo_changes) = reader.track()?;
+        tracks.push(events);
+        tempo_changes.extend(track_tempo_changes);
+    }
+
+    let is_selected_channel = |event_channel: u8| match channel {
+        Some(channel) => event_channel == channel,
+        None => event_channel != PERCUSSION_CHANNEL,
+    };
+    let has_notes = |events: &Vec<NoteEvent>| {
+        events
+            .iter()
+            .any(|event| event.is_on && is_selected_channel(event.channel))
+    };
+    let events = match track {
+        Some(index) => tracks
+            .get(index)
+            .filter(|events| has_notes(events))
+            .ok_or_else(|| format!("MIDI track #{} doesn't have notes", index + 1))?,
+        None => tracks
+            .iter()
+            .find(|events| has_notes(events))
+            .ok_or_else(|| "MIDI file doesn't have notes".to_string())?,
+    };
+
+    let timing = Timing::new(division, tempo_changes)?;
+
+    // Collapse polyphony: at every moment the highest of the sounding notes is played. Notes are
+    // identified by their number and onset, so that re-struck notes aren't merged together.
+    let mut notes = Vec::new();
+    let mut sounding: Vec<(u8, usize)> = Vec::new();
+    let mut current: Option<(u8, usize)> = None;
+    let mut current_start_ms: Option<f64> = None;
+    let mut events = events
+        .iter()
+        .filter(|event| is_selected_channel(event.channel))
+        .enumerate()
+        .collect::<Vec<_>>();
+    // Releases are processed before the onsets that happen at the same tick.
+    events.sort_by_key(|(index, event)| (event.tick, event.is_on, *index));
+
+    let mut index = 0;
+    while index < events.len() {
+        let tick = events[index].1.tick;
+        while index < events.len() && events[index].1.tick == tick {
+            let (onset, event) = events[index];
+            if event.is_on {
+                sounding.push((event.note, onset));
+            } else if let Some(position) = sounding.iter().position(|(note, _)| *note == event.note)
+            {
+                sounding.remove(position);
+            }
+            index += 1;
+        }
+
+        let highest = sounding
+            .iter()
+            .max_by_key(|(note, onset)| (*note, *onset))
+            .copied();
+        if highest == current {
+            continue;
+        }
+
+        let now_ms = timing.ms(tick);
+        if let Some(start_ms) = current_start_ms {
+            let note = match current {
+                Some((note, _)) => to_note(note),
+                None => Note::Silence,
+            };
+            notes.push((note, start_ms, now_ms));
+        }
+
+        current = highest;
+        current_start_ms = Some(now_ms);
+    }
+
+    // Round absolute time instead of durations, so that rounding errors don't accumulate.
+    let mut melody_notes: Vec<(Note, u32)> = Vec::new();
+    for (note, start_ms, end_ms) in notes {
+        let duration_ms = (end_ms.round() - start_ms.round()) as u32;
+        match melody_notes.last_mut() {
+            Some((Note::Silence, last_duration_ms)) if note == Note::Silence => {
+                *last_duration_ms += duration_ms
+            }
+            _ if duration_ms > 0 => melody_notes.push((note, duration_ms)),
+            _ => {}
+        }
+    }
+
+    // Trailing rest isn't audible.
+    if let Some((Note::Silence, _)) = melody_notes.last() {
+        melody_notes.pop();
+    }
+
+    Ok(MidiMelody {
+        notes: melody_notes,
+        quarter_ms: timing.quarter_ms(),
+    })
+}
+
+/// Converts MIDI note number into the Kroneum note shifting it by octaves if it's out of range.
+fn to_note(midi_note: u8) -> Note {
+    let mut midi_note = midi_note;
+    while midi_note < MIN_MIDI_NOTE {
+        midi_note += 12;
+    }
+    while midi_note > MAX_MIDI_NOTE {
+        midi_note -= 12;
+    }
+
+    Note::new(midi_note % 12, midi_note / 12 - 1).unwrap()
+}
+
+/// Formats note in the RTTTL form, e.g. `c#6` or `p`.
+fn rtttl_note(note: Note) -> String {
+    match note {
+        Note::Silence => "p".to_string(),
+        note => note.to_string().to_lowercase(),
+    }
+}
+
+struct NoteEvent {
+    tick: u64,
+    channel: u8,
+    note: u8,
+    is_on: bool,
+}
+
+/// Tick at which tempo changes and new tempo (microseconds per quarter note).
+type TempoChange = (u64, u32);
+
+/// Converts ticks into milliseconds according to the file division and tempo changes.
+struct Timing {
+    ticks_per_quarter: Option<u16>,
+    ms_per_tick: f64,
+    /// Tempo changes sorted by tick.
+    tempo_changes: Vec<TempoChange>,
+}
+
+impl Timing {
+    fn new(division: u16, mut tempo_changes: Vec<TempoChange>) -> Result<Self, String> {
+        tempo_changes.sort_by_key(|(tick, _)| *tick);
+        if division & 0x8000 == 0 {
+            Ok(Timing {
+                ticks_per_quarter: Some(division.max(1)),
+                ms_per_tick: 0.0,
+                tempo_changes,
+            })
+        } else {
+            // SMPTE division: negative frames per second followed by ticks per frame.
+            let frames_per_second = ((division >> 8) as u8 as i8)
+                .checked_neg()
+                .map(f64::from)
+                .ok_or_else(|| "MIDI header has invalid SMPTE division".to_string())?;
+            let ticks_per_frame = f64::from(division & 0xff);
+            Ok(Timing {
+                ticks_per_quarter: None,
+                ms_per_tick: 1000.0 / (frames_per_second * ticks_per_frame).max(1.0),
+                tempo_changes,
+            })
+        }
+    }
+
+    fn ms(&self, tick: u64) -> f64 {
+        let ticks_per_quarter = match self.ticks_per_quarter {
+            Some(ticks_per_quarter) => f64::from(ticks_per_quarter),
+            None => return tick as f64 * self.ms_per_tick,
+        };
+
+        let (mut ms, mut last_tick, mut micros_per_quarter) = (0.0, 0, DEFAULT_MICROS_PER_QUARTER);
+        for (change_tick, change_micros_per_quarter) in self.tempo_changes.iter() {
+            if *change_tick >= tick {
+                break;
+            }
+
+            ms += (change_tick - last_tick) as f64 * f64::from(micros_per_quarter)
+                / ticks_per_quarter
+                / 1000.0;
+            last_tick = *change_tick;
+            micros_per_quarter = *change_micros_per_quarter;
+        }
+
+        ms + (tick - last_tick) as f64 * f64::from(micros_per_quarter) / ticks_per_quarter / 1000.0
+    }
+
+    fn quarter_ms(&self) -> f64 {
+        match self.ticks_per_quarter {
+            Some(_) => {
+                let micros_per_quarter = self
+                    .tempo_changes
+                    .iter()
+                    .find(|(tick, _)| *tick == 0)
+                    .map(|(_, micros_per_quarter)| *micros_per_quarter)
+                    .unwrap_or(DEFAULT_MICROS_PER_QUARTER);
+                f64::from(micros_per_quarter) / 1000.0
+            }
+            None => DEFAULT_MICROS_PER_QUARTER as f64 / 1000.0,
+        }
+    }
+}
+
+/// Reads chunks and events of the Standard MIDI File.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, position: 0 }
+    }
+
+    /// Reads header chunk and returns number of tracks and division.
+    fn header(&mut self) -> Result<(u16, u16), String> {
+        let length = self.chunk(b"MThd")?;
+        if length < 6 {
+            return Err("MIDI header is corrupted".to_string());
+        }
+
+        let _format = self.u16()?;
+        let n_tracks = self.u16()?;
+        let division = self.u16()?;
+        self.skip(length - 6)?;
+
+        Ok((n_tracks, division))
+    }
+
+    /// Reads track chunk and returns its note events and tempo changes.
+    fn track(&mut self) -> Result<(Vec<NoteEvent>, Vec<TempoChange>), String> {
+        let length = self.chunk(b"MTrk")?;
+        let end = self.position + length;
+        if end > self.bytes.len() {
+            return Err("MIDI track is truncated".to_string());
+        }
+
+        let (mut events, mut tempo_changes) = (Vec::new(), Vec::new());
+        let (mut tick, mut running_status) = (0u64, None);
+        while self.position < end {
+            tick += u64::from(self.variable_length()?);
+
+            let mut status = self.u8()?;
+            if status < 0x80 {
+                // Running status: data byte of the event with the previous status.
+                self.position -= 1;
+                status = running_status.ok_or_else(|| "MIDI event is corrupted".to_string())?;
+            }
+
+            match status {
+                0xFF => {
+                    let meta_type = self.u8()?;
+                    let length = self.variable_length()? as usize;
+                    if meta_type == 0x51 && length == 3 {
+                        let data = self.bytes(3)?;
+                        tempo_changes.push((
+                            tick,
+                            u32::from(data[0]) << 16 | u32::from(data[1]) << 8 | u32::from(data[2]),
+                        ));
+                    } else {
+                        self.skip(length)?;
+                    }
+                }
+                0xF0 | 0xF7 => {
+                    let length = self.variable_length()? as usize;
+                    self.skip(length)?;
+                }
+                status => {
+                    running_status = Some(status);
+                    let channel = status & 0x0f;
+                    match status & 0xf0 {
+                        0x80 | 0x90 => {
+                            let note = self.u8()?;
+                            let velocity = self.u8()?;
+                            events.push(NoteEvent {
+                                tick,
+                                channel,
+                                note,
+                                is_on: status & 0xf0 == 0x90 && velocity > 0,
+                            });
+                        }
+                        0xA0 | 0xB0 | 0xE0 => self.skip(2)?,
+                        0xC0 | 0xD0 => self.skip(1)?,
+                        _ => return Err("MIDI event is corrupted".to_string()),
+                    }
+                }
+            }
+        }
+
+        self.position = end;
+
+        Ok((events, tempo_changes))
+    }
+
+    /// Reads chunk type and returns its length, chunks of unknown types are skipped.
+    fn chunk(&mut self, chunk_type: &[u8]) -> Result<usize, String> {
+        loop {
+            let current_type = self.bytes(4)?;
+            let length = self.u32()? as usize;
+            if current_type == chunk_type {
+                return Ok(length);
+            }
+
+            self.skip(length)?;
+        }
+    }
+
+    fn variable_length(&mut self) -> Result<u32, String> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = value << 7 | u32::from(byte & 0x7f);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err("MIDI variable length value is corrupted".to_string())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from(bytes[0]) << 8 | u16::from(bytes[1]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.bytes(4)?;
+        Ok(<[u8; 4]>::try_from(bytes).map(u32::from_be_bytes).unwrap())
+    }
+
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + length)
+            .ok_or_else(|| "MIDI file is truncated".to_string())?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, length: usize) -> Result<(), String> {
+        self.bytes(length).map(|_| ())
+    }
+}
@@ -4,9 +4,8 @@ use actix_web::{web, App, HttpResponse, HttpServer, Responder};
 use core::convert::TryFrom;
 use kroneum_api::{
     adc::ADCChannel,
-    beeper::tone::Tone,
+    beeper::{morse, note::Note, rtttl, tone::Tone},
     flash::storage_slot::StorageSlot,
-    time::Time,
     usb::commands::{KeyModifiers, MediaKey},
 };
 use serde_derive::{Deserialize, Serialize};
@@ -77,6 +76,13 @@ struct WriteFlashParams {
     value: u8,
 }
 
+#[derive(Deserialize)]
+struct MorseParams {
+    text: String,
+    wpm: Option<u8>,
+    pitch: Option<String>,
+}
+
 async fn adc(params: web::Path<ADCParams>) -> impl Responder {
     match ADCChannel::try_from(params.channel) {
         Ok(channel) => {
@@ -88,9 +94,9 @@ async fn adc(params: web::Path<ADCParams>) -> impl Responder {
 
 async fn alarm_get() -> impl Responder {
     let device = Device::create().unwrap();
-    let alarm = Time::from_seconds(device.get_alarm().unwrap().as_secs() as u32);
+    let alarm = device.get_alarm().unwrap();
     HttpResponse::Ok().json(format!(
-        "{:02}h {:02}m {:02}s",
+        "{:02}:{:02}:{:02}",
         alarm.hours, alarm.minutes, alarm.seconds
     ))
 }
@@ -154,6 +160,35 @@ async fn play(tones: web::Json<Vec<(u8, u8)>>) -> impl Responder {
     HttpResponse::NoContent()
 }
 
+async fn play_rtttl(rtttl: web::Json<String>) -> impl Responder {
+    let mut tones = Vec::new();
+    if rtttl::parse_each(&rtttl.0, |tone| tones.push(tone)).is_err() {
+        return HttpResponse::BadRequest().body("Failed to parse RTTTL melody");
+    }
+
+    match Device::create().unwrap().beeper_melody(&tones) {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(message) => HttpResponse::InternalServerError().body(message),
+    }
+}
+
+async fn play_morse(params: web::Json<MorseParams>) -> impl Responder {
+    let pitch = match params.pitch.as_ref().map(|pitch| pitch.parse::<Note>()) {
+        Some(Ok(pitch)) => pitch,
+        Some(Err(_)) => return HttpResponse::BadRequest().body("Unknown pitch note"),
+        None => morse::DEFAULT_PITCH,
+    };
+
+    match Device::create().unwrap().beeper_morse(
+        &params.text,
+        params.wpm.unwrap_or(morse::DEFAULT_WPM),
+        pitch,
+    ) {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(message) => HttpResponse::BadRequest().body(message),
+    }
+}
+
 async fn flash_read() -> impl Responder {
     let device = Device::create().unwrap();
     HttpResponse::Ok().json(vec![
@@ -236,6 +271,8 @@ pub async fn run_server(port: u16) -> Result<(), String> {
             .route("/api/alarm/set", web::post().to(alarm_set))
             .route("/api/beep", web::get().to(beep))
             .route("/api/play", web::post().to(play))
+            .route("/api/play/rtttl", web::post().to(play_rtttl))
+            .route("/api/play/morse", web::post().to(play_morse))
             .route("/api/flash", web::get().to(flash_read))
             .route("/api/flash/write", web::post().to(flash_write))
             .route("/api/flash/erase", web::post().to(flash_erase))
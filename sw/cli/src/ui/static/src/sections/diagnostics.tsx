@@ -34,6 +34,9 @@ export function DiagnosticsSection() {
     response: null,
   });
 
+  const [rtttl, setRtttl] = useState<string>('');
+  const [morse, setMorse] = useState<string>('');
+
   const showError = !echoStatus.isValid && echoStatus.bytesString.length > 0;
   return (
     <>
@@ -57,6 +60,42 @@ export function DiagnosticsSection() {
               Play melody
             </EuiButton>
           </EuiFormRow>
+          <EuiFormRow style={{ alignItems: 'center' }} display="columnCompressed" label="RTTTL melody">
+            <EuiFieldText
+              placeholder="e.g. Beep:d=4,o=5,b=200:c,8p,8e6"
+              value={rtttl}
+              name="rtttl"
+              onChange={(ev) => setRtttl(ev.target.value)}
+            />
+          </EuiFormRow>
+          <EuiFormRow style={{ alignItems: 'center' }} display="columnCompressed">
+            <EuiButton
+              isDisabled={rtttl.trim().length === 0}
+              onClick={() =>
+                axios.post('/api/play/rtttl', JSON.stringify(rtttl), {
+                  headers: { 'Content-Type': 'application/json' },
+                })
+              }
+            >
+              Play RTTTL melody
+            </EuiButton>
+          </EuiFormRow>
+          <EuiFormRow style={{ alignItems: 'center' }} display="columnCompressed" label="Morse message">
+            <EuiFieldText
+              placeholder="e.g. SOS"
+              value={morse}
+              name="morse"
+              onChange={(ev) => setMorse(ev.target.value)}
+            />
+          </EuiFormRow>
+          <EuiFormRow style={{ alignItems: 'center' }} display="columnCompressed">
+            <EuiButton
+              isDisabled={morse.trim().length === 0}
+              onClick={() => axios.post('/api/play/morse', { text: morse })}
+            >
+              Play Morse message
+            </EuiButton>
+          </EuiFormRow>
         </EuiPanel>
       </EuiFlexItem>
       <EuiFlexItem>
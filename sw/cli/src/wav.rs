@@ -0,0 +1,76 @@
+use kroneum_api::beeper::{tone::Tone, volume::Volume, REPEAT_PAUSE};
+use std::{fs, path::Path};
+
+/// Sample rate of the rendered audio (samples per second).
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Amplitude of the square wave, leaves enough headroom to not sound harsh.
+const AMPLITUDE: i16 = i16::max_value() / 4;
+
+/// Synthesizes tones the same way device beeper plays them: every tone is a square wave with the
+/// frequency from `Tone::frequency` and the duty cycle from `Volume::duty_cycle`, repetitions are
+/// separated with `REPEAT_PAUSE`. Returns 16-bit mono samples.
+pub fn render(tones: &[Tone], repeat: usize, duty_cycle: u8, volume: Volume) -> Vec<i16> {
+    let mut samples = Vec::new();
+    for repetition in 0..repeat {
+        if repetition > 0 {
+            render_tone(&mut samples, REPEAT_PAUSE, 0);
+        }
+
+        for (index, tone) in tones.iter().enumerate() {
+            render_tone(
+                &mut samples,
+                *tone,
+                volume.duty_cycle(duty_cycle, index, tones.len()),
+            );
+        }
+    }
+
+    samples
+}
+
+/// Writes samples to the PCM WAV file.
+pub fn write(path: &Path, samples: &[i16]) -> Result<(), String> {
+    let data_size = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+
+    // Format chunk: PCM, mono, 16 bits per sample.
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    fs::write(path, bytes).or_else(|err| Err(format!("Failed to write WAV file: {:?}", err)))
+}
+
+/// Appends samples of a single tone, tones with zero duration are skipped just like the device
+/// does.
+fn render_tone(samples: &mut Vec<i16>, tone: Tone, duty_cycle: u8) {
+    let frequency = u64::from(tone.frequency());
+    let n_samples = u64::from(tone.duration) * u64::from(SAMPLE_RATE) / 1000;
+    let sample_rate = u64::from(SAMPLE_RATE);
+    for index in 0..n_samples {
+        let sample = if frequency == 0 || duty_cycle == 0 {
+            0
+        } else if (index * frequency % sample_rate) * 100 < sample_rate * u64::from(duty_cycle) {
+            AMPLITUDE
+        } else {
+            -AMPLITUDE
+        };
+
+        samples.push(sample);
+    }
+}
@@ -0,0 +1,303 @@
+use array::Array;
+use beeper::{melody::Melody, volume::Volume};
+use core::convert::TryFrom;
+use flash::{storage_key::StorageKey, storage_value::StorageValue, Flash, FlashHardware};
+
+/// Describes how alarm is repeated (snoozed) until it's acknowledged.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SnoozeConfig {
+    /// Interval between snoozes in seconds.
+    pub interval: u8,
+    /// Max number of snoozes, `0` means that alarm is snoozed until it's acknowledged.
+    pub limit: u8,
+    /// Built-in melody that is played on every snooze.
+    pub melody: Melody,
+}
+
+impl SnoozeConfig {
+    /// Reads config from the flash falling back to the default one if it's missing or invalid.
+    pub fn read<T: FlashHardware>(flash: &Flash<T>) -> Self {
+        flash
+            .read_value(StorageKey::SnoozeConfig.into())
+            .unwrap_or_default()
+    }
+
+    /// Persists config in the flash.
+    #[allow(clippy::result_unit_err)]
+    pub fn write<T: FlashHardware>(&self, flash: &Flash<T>) -> Result<(), ()> {
+        if self.interval == 0 || u8::try_from(self.melody).is_err() {
+            return Err(());
+        }
+
+        flash.write_value(StorageKey::SnoozeConfig.into(), *self)
+    }
+}
+
+impl Default for SnoozeConfig {
+    fn default() -> Self {
+        SnoozeConfig {
+            interval: 10,
+            limit: 0,
+            melody: Melody::Beep,
+        }
+    }
+}
+
+/// Custom melodies can't be referenced by identifier and serialized as `0` that is rejected during
+/// deserialization.
+impl From<SnoozeConfig> for Array<u8> {
+    fn from(config: SnoozeConfig) -> Self {
+        [
+            config.interval,
+            config.limit,
+            u8::try_from(config.melody).unwrap_or(0),
+        ]
+        .as_ref()
+        .into()
+    }
+}
+
+impl TryFrom<&[u8]> for SnoozeConfig {
+    type Error = ();
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value {
+            [interval, limit, melody] if *interval > 0 => Ok(SnoozeConfig {
+                interval: *interval,
+                limit: *limit,
+                melody: Melody::try_from(*melody)?,
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+impl StorageValue for SnoozeConfig {
+    fn to_bytes(&self) -> Array<u8> {
+        Array::from(*self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from(bytes).ok()
+    }
+}
+
+/// Number of times alarm melody is played when alarm fires or snoozes without escalation.
+const ALARM_MELODY_REPEAT: usize = 2;
+
+/// Max number of times alarm melody can be repeated on escalation.
+const MAX_ALARM_MELODY_REPEAT: usize = 10;
+
+/// Escalation can't make snooze interval shorter than that (in seconds).
+const MIN_SNOOZE_INTERVAL: u8 = 5;
+
+/// Describes how alarm gets more intense with every snooze until it's acknowledged.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EscalationConfig {
+    /// Duty cycle (in percent) alarm melody gets louder by with every snooze.
+    pub volume_step: u8,
+    /// Number of extra times alarm melody is repeated with every snooze.
+    pub repeat_step: u8,
+    /// Number of seconds snooze interval gets shorter by with every snooze.
+    pub interval_step: u8,
+    /// Number of snoozes after which alarm stops escalating.
+    pub limit: u8,
+}
+
+impl EscalationConfig {
+    /// Reads config from the flash falling back to the default one if it's missing.
+    pub fn read<T: FlashHardware>(flash: &Flash<T>) -> Self {
+        flash
+            .read_value(StorageKey::EscalationConfig.into())
+            .unwrap_or_default()
+    }
+
+    /// Persists config in the flash.
+    #[allow(clippy::result_unit_err)]
+    pub fn write<T: FlashHardware>(&self, flash: &Flash<T>) -> Result<(), ()> {
+        flash.write_value(StorageKey::EscalationConfig.into(), *self)
+    }
+
+    /// Returns the volume alarm melody is played with when alarm fires (`snooze` is `1`) or
+    /// snoozes (`snooze` is the number of the snooze plus one).
+    pub fn volume(&self, snooze: u8, alarm_volume: Volume) -> Volume {
+        match self.level(snooze).saturating_mul(self.volume_step) {
+            0 => alarm_volume,
+            boost => Volume::Louder(boost),
+        }
+    }
+
+    /// Returns the number of times alarm melody is repeated, see `volume` for `snooze` meaning.
+    pub fn repeat(&self, snooze: u8) -> usize {
+        (ALARM_MELODY_REPEAT + usize::from(self.level(snooze)) * usize::from(self.repeat_step))
+            .min(MAX_ALARM_MELODY_REPEAT)
+    }
+
+    /// Returns snooze interval (in seconds) that follows the specified snooze.
+    pub fn interval(&self, interval: u8, snooze: u8) -> u8 {
+        let shortened_interval =
+            interval.saturating_sub(self.level(snooze).saturating_mul(self.interval_step));
+        shortened_interval.max(MIN_SNOOZE_INTERVAL.min(interval))
+    }
+
+    /// Alarm doesn't escalate when it fires, and escalates once for every snooze up to the limit.
+    fn level(&self, snooze: u8) -> u8 {
+        snooze.saturating_sub(1).min(self.limit)
+    }
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        EscalationConfig {
+            volume_step: 0,
+            repeat_step: 0,
+            interval_step: 0,
+            limit: 5,
+        }
+    }
+}
+
+impl From<EscalationConfig> for Array<u8> {
+    fn from(config: EscalationConfig) -> Self {
+        [
+            config.volume_step,
+            config.repeat_step,
+            config.interval_step,
+            config.limit,
+        ]
+        .as_ref()
+        .into()
+    }
+}
+
+impl TryFrom<&[u8]> for EscalationConfig {
+    type Error = ();
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value {
+            [volume_step, repeat_step, interval_step, limit] => Ok(EscalationConfig {
+                volume_step: *volume_step,
+                repeat_step: *repeat_step,
+                interval_step: *interval_step,
+                limit: *limit,
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+impl StorageValue for EscalationConfig {
+    fn to_bytes(&self) -> Array<u8> {
+        Array::from(*self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from(bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correctly_serializes() {
+        let config = SnoozeConfig {
+            interval: 30,
+            limit: 5,
+            melody: Melody::Alarm,
+        };
+
+        assert_eq!(Array::from(config).as_ref(), [30, 5, 1]);
+        assert_eq!(SnoozeConfig::try_from([30, 5, 1].as_ref()), Ok(config));
+
+        assert_eq!(config.to_bytes().as_ref(), [30, 5, 1]);
+        assert_eq!(SnoozeConfig::from_bytes(&[30, 5, 1]), Some(config));
+        assert_eq!(SnoozeConfig::from_bytes(&[0, 5, 1]), None);
+    }
+
+    #[test]
+    fn does_not_serialize_custom_melody() {
+        let config = SnoozeConfig {
+            melody: Melody::Custom(Array::new()),
+            ..Default::default()
+        };
+
+        assert_eq!(Array::from(config).as_ref(), [10, 0, 0]);
+        assert_eq!(
+            SnoozeConfig::try_from(Array::from(config).as_ref()),
+            Err(())
+        );
+    }
+
+    #[test]
+    fn fails_to_deserialize_invalid_config() {
+        assert_eq!(SnoozeConfig::try_from([0, 5, 1].as_ref()), Err(()));
+        assert_eq!(SnoozeConfig::try_from([10, 5, 0].as_ref()), Err(()));
+        assert_eq!(SnoozeConfig::try_from([10, 5].as_ref()), Err(()));
+        assert_eq!(SnoozeConfig::try_from([10, 5, 1, 1].as_ref()), Err(()));
+    }
+
+    #[test]
+    fn correctly_serializes_escalation() {
+        let config = EscalationConfig {
+            volume_step: 5,
+            repeat_step: 1,
+            interval_step: 2,
+            limit: 3,
+        };
+
+        assert_eq!(Array::from(config).as_ref(), [5, 1, 2, 3]);
+        assert_eq!(
+            EscalationConfig::try_from([5, 1, 2, 3].as_ref()),
+            Ok(config)
+        );
+        assert_eq!(EscalationConfig::try_from([5, 1, 2].as_ref()), Err(()));
+
+        assert_eq!(config.to_bytes().as_ref(), [5, 1, 2, 3]);
+        assert_eq!(EscalationConfig::from_bytes(&[5, 1, 2, 3]), Some(config));
+        assert_eq!(EscalationConfig::from_bytes(&[5, 1, 2]), None);
+    }
+
+    #[test]
+    fn escalates_up_to_the_limit() {
+        let config = EscalationConfig {
+            volume_step: 5,
+            repeat_step: 3,
+            interval_step: 4,
+            limit: 3,
+        };
+
+        // Alarm doesn't escalate when it fires.
+        assert_eq!(config.volume(1, Volume::Quiet), Volume::Quiet);
+        assert_eq!(config.repeat(1), 2);
+        assert_eq!(config.interval(20, 1), 20);
+
+        assert_eq!(config.volume(2, Volume::Quiet), Volume::Louder(5));
+        assert_eq!(config.repeat(2), 5);
+        assert_eq!(config.interval(20, 2), 16);
+
+        assert_eq!(config.volume(4, Volume::Quiet), Volume::Louder(15));
+        assert_eq!(config.repeat(4), 10);
+        assert_eq!(config.interval(20, 4), 8);
+
+        // Escalation stops at the limit.
+        assert_eq!(config.volume(10, Volume::Quiet), Volume::Louder(15));
+        assert_eq!(config.repeat(10), 10);
+        assert_eq!(config.interval(20, 10), 8);
+
+        // Escalation never makes snooze interval too short.
+        assert_eq!(config.interval(10, 4), 5);
+        assert_eq!(config.interval(3, 4), 3);
+    }
+
+    #[test]
+    fn does_not_escalate_by_default() {
+        let config = EscalationConfig::default();
+        for snooze in 1..10 {
+            assert_eq!(config.volume(snooze, Volume::Normal), Volume::Normal);
+            assert_eq!(config.repeat(snooze), 2);
+            assert_eq!(config.interval(10, snooze), 10);
+        }
+    }
+}
@@ -64,6 +64,11 @@ impl<T: Default + Copy> Array<T> {
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
+
+    /// Checks whether internal buffer is full and no more values can be added.
+    pub fn is_full(&self) -> bool {
+        self.len == MAX_SIZE
+    }
 }
 
 impl<T: Default + Copy> Default for Array<T> {
@@ -164,17 +169,17 @@ mod tests {
     #[test]
     fn correctly_detects_is_empty() {
         let mut array: Array<u8> = Array::new();
-        assert_eq!(array.is_empty(), true);
+        assert!(array.is_empty());
 
         array.push(1);
-        assert_eq!(array.is_empty(), false);
+        assert!(!array.is_empty());
     }
 
     #[test]
     fn correctly_handles_default() {
         let array: Array<u8> = Default::default();
         assert_eq!(array.len(), 0);
-        assert_eq!(array.is_empty(), true);
+        assert!(array.is_empty());
     }
 
     #[test]
@@ -235,7 +240,7 @@ mod tests {
         }
 
         assert_eq!(array.len(), MAX_SIZE);
-        assert_eq!(array.is_empty(), false);
+        assert!(!array.is_empty());
     }
 
     #[test]
@@ -271,7 +276,7 @@ mod tests {
     #[test]
     fn correctly_handles_push() {
         let mut array: Array<u8> = Array::from(&[0, 1]);
-        assert_eq!(array.is_empty(), false);
+        assert!(!array.is_empty());
         assert_eq!(array.len(), 2);
 
         // Push new values.
@@ -294,7 +299,7 @@ mod tests {
     #[test]
     fn correctly_handles_unshift() {
         let mut array: Array<u8> = Array::from(&[62, 63]);
-        assert_eq!(array.is_empty(), false);
+        assert!(!array.is_empty());
         assert_eq!(array.len(), 2);
 
         // Unshift new values.
@@ -1,11 +1,24 @@
 pub mod melody;
+pub mod morse;
 pub mod note;
+pub mod rtttl;
+pub mod score;
+pub mod stored_melody;
 pub mod tone;
-
-use self::{note::Note, tone::Tone};
+pub mod volume;
+
+use self::{
+    melody::Melody,
+    note::Note,
+    tone::Tone,
+    volume::{BeeperConfig, Volume},
+};
 use array::Array;
 use systick::{SysTick, SysTickHardware};
 
+/// Pause made between repetitions of the melody.
+pub const REPEAT_PAUSE: Tone = Tone::new(Note::Silence as u8, 100);
+
 /// Describes the Beeper hardware management interface.
 pub trait PWMBeeperHardware {
     /// Enables device PWM output.
@@ -14,8 +27,9 @@ pub trait PWMBeeperHardware {
     /// Disables device PWM output.
     fn disable_pwm(&self);
 
-    /// Forces PWM to pulse of the specified frequency.
-    fn pulse(&self, note_frequency: u32);
+    /// Forces PWM to pulse of the specified frequency with the specified duty cycle (in percent)
+    /// that defines the volume.
+    fn pulse(&self, note_frequency: u32, duty_cycle: u8);
 }
 
 #[derive(Copy, Clone, Default)]
@@ -23,11 +37,17 @@ pub struct TonesToPlay {
     tones: Array<Tone>,
     current_index: usize,
     repeat: usize,
+    volume: Volume,
 }
 
 #[derive(Copy, Clone, Default)]
 pub struct BeeperState {
+    pub config: BeeperConfig,
     pub tones_to_play: Option<TonesToPlay>,
+    /// Copy of the melody stored in flash that is played instead of `Melody::Stored`.
+    pub stored_melody: Array<Tone>,
+    /// Tones that are played once the current ones are over, used to stream long melodies.
+    pub queued_tones: Array<Tone>,
 }
 
 pub struct PWMBeeper<'a, T: PWMBeeperHardware, S: SysTickHardware> {
@@ -41,14 +61,30 @@ impl<'a, T: PWMBeeperHardware, S: SysTickHardware> PWMBeeper<'a, T, S> {
         PWMBeeper { hw, systick, state }
     }
 
-    /// Starts playing specified tones in a sequence.
-    pub fn play<TN: Into<Array<Tone>> + Sized>(&mut self, tones: TN) {
-        self.play_and_repeat(tones, 1);
+    /// Starts playing specified melody.
+    pub fn play<M: Into<Melody> + Sized>(&mut self, melody: M) {
+        self.play_and_repeat(melody, 1);
+    }
+
+    /// Starts playing specified `melody` repeating it `repeat` number of times with a
+    /// `REPEAT_PAUSE` delay between repetitions.
+    pub fn play_and_repeat<M: Into<Melody> + Sized>(&mut self, melody: M, repeat: usize) {
+        self.play_with_volume(melody, repeat, Volume::Normal);
     }
 
-    /// Starts playing specified `tones` in a sequence repeating it `repeat` number of times with a
-    /// `100ms` delay between repetitions.
-    pub fn play_and_repeat<TN: Into<Array<Tone>> + Sized>(&mut self, tones: TN, repeat: usize) {
+    /// Starts playing specified `melody` the same way `play_and_repeat` does, but with the
+    /// specified volume variant.
+    pub fn play_with_volume<M: Into<Melody> + Sized>(
+        &mut self,
+        melody: M,
+        repeat: usize,
+        volume: Volume,
+    ) {
+        let tones = match melody.into() {
+            Melody::Stored if !self.state.stored_melody.is_empty() => self.state.stored_melody,
+            melody => melody.into(),
+        };
+
         // Stop current melody if there's any.
         if self.is_playing() {
             self.stop();
@@ -57,17 +93,46 @@ impl<'a, T: PWMBeeperHardware, S: SysTickHardware> PWMBeeper<'a, T, S> {
         self.hw.enable_pwm();
 
         self.state.tones_to_play = Some(TonesToPlay {
-            tones: tones.into(),
+            tones,
             current_index: 0,
             repeat,
+            volume,
         });
 
         self.resume();
     }
 
-    /// Stops current melody.
+    /// Queues tones to be played after the current ones, fails if queue can't fit all of them.
+    #[allow(clippy::result_unit_err)]
+    pub fn enqueue(&mut self, tones: &[Tone]) -> Result<(), ()> {
+        let mut queued_tones = self.state.queued_tones;
+        for tone in tones {
+            if queued_tones.is_full() {
+                return Err(());
+            }
+
+            queued_tones.push(*tone);
+        }
+
+        self.state.queued_tones = queued_tones;
+
+        Ok(())
+    }
+
+    /// Starts playing queued tones, if beeper is already playing they are played as soon as the
+    /// current ones are over.
+    pub fn play_queued(&mut self) {
+        if !self.is_playing() && !self.state.queued_tones.is_empty() {
+            let tones = self.state.queued_tones;
+            self.state.queued_tones = Array::new();
+            self.play(tones);
+        }
+    }
+
+    /// Stops current melody and drops queued tones.
     pub fn stop(&mut self) {
         self.state.tones_to_play = None;
+        self.state.queued_tones = Array::new();
         self.hw.disable_pwm();
     }
 
@@ -77,25 +142,39 @@ impl<'a, T: PWMBeeperHardware, S: SysTickHardware> PWMBeeper<'a, T, S> {
             tones,
             current_index,
             repeat,
+            volume,
         }) = self.state.tones_to_play
         {
             if current_index < tones.len() {
-                self.play_tone(tones[current_index]);
+                let duty_cycle =
+                    volume.duty_cycle(self.state.config.duty_cycle, current_index, tones.len());
+                self.play_tone(tones[current_index], duty_cycle);
 
                 self.state.tones_to_play.replace(TonesToPlay {
                     tones,
                     current_index: current_index + 1,
                     repeat,
+                    volume,
                 });
             } else if repeat > 1 {
-                // Make a 100ms pause between repetitions.
-                self.play_tone(Tone::new(Note::Silence as u8, 100));
+                self.play_tone(REPEAT_PAUSE, 0);
 
                 self.state.tones_to_play.replace(TonesToPlay {
                     tones,
                     current_index: 0,
                     repeat: repeat - 1,
+                    volume,
                 });
+            } else if !self.state.queued_tones.is_empty() {
+                self.state.tones_to_play.replace(TonesToPlay {
+                    tones: self.state.queued_tones,
+                    current_index: 0,
+                    repeat: 1,
+                    volume,
+                });
+                self.state.queued_tones = Array::new();
+
+                self.resume();
             } else {
                 self.stop();
             }
@@ -107,12 +186,12 @@ impl<'a, T: PWMBeeperHardware, S: SysTickHardware> PWMBeeper<'a, T, S> {
         self.state.tones_to_play.is_some()
     }
 
-    fn play_tone(&mut self, tone: Tone) {
+    fn play_tone(&mut self, tone: Tone, duty_cycle: u8) {
         if tone.duration == 0 {
             return;
         }
 
-        self.hw.pulse(tone.frequency());
+        self.hw.pulse(tone.frequency(), duty_cycle);
         self.systick.start(tone.duration as u32);
     }
 }
@@ -128,7 +207,7 @@ mod tests {
     enum Call {
         EnablePWM,
         DisablePWM,
-        Pulse(u32),
+        Pulse(u32, u8),
     }
 
     struct PWMBeeperHardwareMock<'a> {
@@ -144,11 +223,11 @@ mod tests {
             self.data.borrow_mut().calls.log_call(Call::DisablePWM);
         }
 
-        fn pulse(&self, note_frequency: u32) {
+        fn pulse(&self, note_frequency: u32, duty_cycle: u8) {
             self.data
                 .borrow_mut()
                 .calls
-                .log_call(Call::Pulse(note_frequency));
+                .log_call(Call::Pulse(note_frequency, duty_cycle));
         }
     }
 
@@ -175,14 +254,14 @@ mod tests {
 
         let mut beeper = PWMBeeper::new(&beeper_hw_mock, &mut systick, &mut state);
 
-        assert_eq!(beeper.is_playing(), false);
+        assert!(!beeper.is_playing());
         beeper.play(tones);
-        assert_eq!(beeper.is_playing(), true);
+        assert!(beeper.is_playing());
 
         assert_eq!(
             [
                 Some((Call::EnablePWM, 0)),
-                Some((Call::Pulse(tones[0].frequency()), 1))
+                Some((Call::Pulse(tones[0].frequency(), 50), 1))
             ],
             beeper.hw.data.borrow().calls.ordered_logs()
         );
@@ -196,6 +275,165 @@ mod tests {
         );
     }
 
+    #[test]
+    fn play_resolves_stored_melody() {
+        let mut systick_mock = MockData::new(AssociatedData::default());
+
+        let mut systick = create_systick(&mut systick_mock);
+        let tones = create_tones();
+        let mut state = BeeperState {
+            stored_melody: tones,
+            ..Default::default()
+        };
+
+        let beeper_hw_mock = PWMBeeperHardwareMock {
+            data: RefCell::new(MockData::<Call, ()>::without_data()),
+        };
+
+        let mut beeper = PWMBeeper::new(&beeper_hw_mock, &mut systick, &mut state);
+
+        beeper.play(Melody::Stored);
+        assert_eq!(
+            beeper.state.tones_to_play.map(|to_play| to_play.tones),
+            Some(tones)
+        );
+
+        // Built-in alarm melody is played if there is no stored melody.
+        beeper.state.stored_melody = Array::new();
+        beeper.play(Melody::Stored);
+        assert_eq!(
+            beeper.state.tones_to_play.map(|to_play| to_play.tones),
+            Some(Melody::Alarm.into())
+        );
+    }
+
+    #[test]
+    fn resume_plays_queued_tones() {
+        let mut systick_mock = MockData::new(AssociatedData::default());
+
+        let mut systick = create_systick(&mut systick_mock);
+        let mut state = BeeperState::default();
+        let tones = create_tones();
+
+        let beeper_hw_mock = PWMBeeperHardwareMock {
+            data: RefCell::new(MockData::<Call, ()>::without_data()),
+        };
+
+        let mut beeper = PWMBeeper::new(&beeper_hw_mock, &mut systick, &mut state);
+
+        assert_eq!(beeper.enqueue(tones.as_ref()), Ok(()));
+        beeper.play_queued();
+        assert!(beeper.state.queued_tones.is_empty());
+
+        // Tones queued while beeper is playing are played right after the current ones.
+        assert_eq!(beeper.enqueue(&[tones[1]]), Ok(()));
+        beeper.play_queued();
+        beeper.resume();
+        beeper.resume();
+        assert!(beeper.is_playing());
+        beeper.resume();
+        assert!(!beeper.is_playing());
+
+        assert_eq!(
+            beeper.hw.data.borrow().calls.logs(),
+            [
+                Some(Call::EnablePWM),
+                Some(Call::Pulse(tones[0].frequency(), 50)),
+                Some(Call::Pulse(tones[1].frequency(), 50)),
+                Some(Call::Pulse(tones[1].frequency(), 50)),
+                Some(Call::DisablePWM),
+            ]
+        );
+    }
+
+    #[test]
+    fn play_with_volume_applies_duty_cycle() {
+        let mut systick_mock = MockData::new(AssociatedData::default());
+
+        let mut systick = create_systick(&mut systick_mock);
+        let mut state = BeeperState::default();
+        state.config.duty_cycle = 20;
+        let tones = create_tones();
+
+        let beeper_hw_mock = PWMBeeperHardwareMock {
+            data: RefCell::new(MockData::<Call, ()>::without_data()),
+        };
+
+        let mut beeper = PWMBeeper::new(&beeper_hw_mock, &mut systick, &mut state);
+
+        beeper.play(tones);
+        beeper.play_with_volume(tones, 1, Volume::Quiet);
+        beeper.play_with_volume(tones, 1, Volume::Crescendo);
+        beeper.resume();
+
+        assert_eq!(
+            beeper.hw.data.borrow().calls.logs(),
+            [
+                Some(Call::EnablePWM),
+                Some(Call::Pulse(tones[0].frequency(), 20)),
+                Some(Call::DisablePWM),
+                Some(Call::EnablePWM),
+                Some(Call::Pulse(tones[0].frequency(), 5)),
+                Some(Call::DisablePWM),
+                Some(Call::EnablePWM),
+                Some(Call::Pulse(tones[0].frequency(), 5)),
+                Some(Call::Pulse(tones[1].frequency(), 20)),
+            ]
+        );
+    }
+
+    #[test]
+    fn play_with_louder_volume_caps_duty_cycle() {
+        let mut systick_mock = MockData::new(AssociatedData::default());
+
+        let mut systick = create_systick(&mut systick_mock);
+        let mut state = BeeperState::default();
+        state.config.duty_cycle = 20;
+        let tones = create_tones();
+
+        let beeper_hw_mock = PWMBeeperHardwareMock {
+            data: RefCell::new(MockData::<Call, ()>::without_data()),
+        };
+
+        let mut beeper = PWMBeeper::new(&beeper_hw_mock, &mut systick, &mut state);
+
+        beeper.play_with_volume(tones, 1, Volume::Louder(10));
+        beeper.play_with_volume(tones, 1, Volume::Louder(40));
+
+        assert_eq!(
+            beeper.hw.data.borrow().calls.logs(),
+            [
+                Some(Call::EnablePWM),
+                Some(Call::Pulse(tones[0].frequency(), 30)),
+                Some(Call::DisablePWM),
+                Some(Call::EnablePWM),
+                Some(Call::Pulse(tones[0].frequency(), 50)),
+            ]
+        );
+    }
+
+    #[test]
+    fn enqueue_fails_if_queue_is_full() {
+        let mut systick_mock = MockData::new(AssociatedData::default());
+
+        let mut systick = create_systick(&mut systick_mock);
+        let mut state = BeeperState::default();
+        let tones = [Tone::new(Note::A5 as u8, 100); 40];
+
+        let beeper_hw_mock = PWMBeeperHardwareMock {
+            data: RefCell::new(MockData::<Call, ()>::without_data()),
+        };
+
+        let mut beeper = PWMBeeper::new(&beeper_hw_mock, &mut systick, &mut state);
+
+        assert_eq!(beeper.enqueue(&tones), Ok(()));
+        assert_eq!(beeper.enqueue(&tones), Err(()));
+        assert_eq!(beeper.state.queued_tones.len(), 40);
+
+        beeper.stop();
+        assert!(beeper.state.queued_tones.is_empty());
+    }
+
     #[test]
     fn resume_continues_pwm_sound() {
         let order = Order::default();
@@ -215,13 +453,13 @@ mod tests {
         beeper.play(tones);
 
         beeper.resume();
-        assert_eq!(beeper.is_playing(), true);
+        assert!(beeper.is_playing());
 
         assert_eq!(
             [
                 Some((Call::EnablePWM, 0)),
-                Some((Call::Pulse(tones[0].frequency()), 1)),
-                Some((Call::Pulse(tones[1].frequency()), 5))
+                Some((Call::Pulse(tones[0].frequency(), 50), 1)),
+                Some((Call::Pulse(tones[1].frequency(), 50), 5))
             ],
             beeper.hw.data.borrow().calls.ordered_logs()
         );
@@ -258,13 +496,13 @@ mod tests {
 
         beeper.resume();
         beeper.resume();
-        assert_eq!(beeper.is_playing(), false);
+        assert!(!beeper.is_playing());
 
         assert_eq!(
             [
                 Some((Call::EnablePWM, 0)),
-                Some((Call::Pulse(tones[0].frequency()), 1)),
-                Some((Call::Pulse(tones[1].frequency()), 5)),
+                Some((Call::Pulse(tones[0].frequency(), 50), 1)),
+                Some((Call::Pulse(tones[1].frequency(), 50), 5)),
                 Some((Call::DisablePWM, 9)),
             ],
             beeper.hw.data.borrow().calls.ordered_logs()
@@ -301,12 +539,12 @@ mod tests {
         beeper.play(tones);
 
         beeper.stop();
-        assert_eq!(beeper.is_playing(), false);
+        assert!(!beeper.is_playing());
 
         assert_eq!(
             [
                 Some((Call::EnablePWM, 0)),
-                Some((Call::Pulse(tones[0].frequency()), 1)),
+                Some((Call::Pulse(tones[0].frequency(), 50), 1)),
                 Some((Call::DisablePWM, 5)),
             ],
             beeper.hw.data.borrow().calls.ordered_logs()
@@ -342,29 +580,29 @@ mod tests {
         // First repetition.
         beeper.resume();
         beeper.resume();
-        assert_eq!(beeper.is_playing(), true);
+        assert!(beeper.is_playing());
 
         // Second repetition (silence note).
         beeper.resume();
 
         // two consequent notes.
         beeper.resume();
-        assert_eq!(beeper.is_playing(), true);
+        assert!(beeper.is_playing());
 
         beeper.resume();
-        assert_eq!(beeper.is_playing(), false);
+        assert!(!beeper.is_playing());
 
         assert_eq!(
             [
                 // First repetition.
                 Some((Call::EnablePWM, 0)),
-                Some((Call::Pulse(tones[0].frequency()), 1)),
-                Some((Call::Pulse(tones[1].frequency()), 5)),
+                Some((Call::Pulse(tones[0].frequency(), 50), 1)),
+                Some((Call::Pulse(tones[1].frequency(), 50), 5)),
                 // Silence
-                Some((Call::Pulse(0), 9)),
+                Some((Call::Pulse(0, 0), 9)),
                 // Second repetition.
-                Some((Call::Pulse(tones[0].frequency()), 13)),
-                Some((Call::Pulse(tones[1].frequency()), 17)),
+                Some((Call::Pulse(tones[0].frequency(), 50), 13)),
+                Some((Call::Pulse(tones[1].frequency(), 50), 17)),
                 Some((Call::DisablePWM, 21)),
             ],
             beeper.hw.data.borrow().calls.ordered_logs()
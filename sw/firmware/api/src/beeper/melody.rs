@@ -1,29 +1,96 @@
-use super::note::{Note, NOTE_1_2_DURATION, NOTE_1_4_DURATION};
+use super::note::{Note, NOTE_1_2_DURATION, NOTE_1_4_DURATION, NOTE_1_8_DURATION};
 use array::Array;
 use beeper::tone::Tone;
+use core::convert::TryFrom;
 
 /// Defines a predefined melody to play.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Melody {
     Alarm,
     Beep,
     Reset,
     Setup,
+    Work,
+    ShortBreak,
+    LongBreak,
+    Pause,
+    Resume,
+    Extend,
+    /// Melody uploaded by the user and stored in flash, resolved by the beeper when it's played.
+    Stored,
     Custom(Array<Tone>),
 }
 
-impl Into<Array<Tone>> for Melody {
-    fn into(self) -> Array<Tone> {
-        match self {
+impl From<Array<Tone>> for Melody {
+    fn from(tones: Array<Tone>) -> Self {
+        Melody::Custom(tones)
+    }
+}
+
+impl From<Melody> for Array<Tone> {
+    fn from(melody: Melody) -> Self {
+        match melody {
             Melody::Alarm => Array::from(&ALARM_MELODY),
             Melody::Beep => Array::from(&BEEP_MELODY),
             Melody::Reset => Array::from(&RESET_MELODY),
             Melody::Setup => Array::from(&SETUP_MELODY),
+            Melody::Work => Array::from(&WORK_MELODY),
+            Melody::ShortBreak => Array::from(&SHORT_BREAK_MELODY),
+            Melody::LongBreak => Array::from(&LONG_BREAK_MELODY),
+            Melody::Pause => Array::from(&PAUSE_MELODY),
+            Melody::Resume => Array::from(&RESUME_MELODY),
+            Melody::Extend => Array::from(&EXTEND_MELODY),
+            // Built-in alarm melody is used if there is no melody stored in flash.
+            Melody::Stored => Array::from(&ALARM_MELODY),
             Melody::Custom(tones) => tones,
         }
     }
 }
 
+/// Built-in melodies can be referenced by their numeric identifiers (e.g. in flash or USB commands).
+impl TryFrom<u8> for Melody {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Melody::Alarm),
+            2 => Ok(Melody::Beep),
+            3 => Ok(Melody::Reset),
+            4 => Ok(Melody::Setup),
+            5 => Ok(Melody::Work),
+            6 => Ok(Melody::ShortBreak),
+            7 => Ok(Melody::LongBreak),
+            8 => Ok(Melody::Pause),
+            9 => Ok(Melody::Resume),
+            10 => Ok(Melody::Extend),
+            11 => Ok(Melody::Stored),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Custom melodies don't have identifiers and can't be converted.
+impl TryFrom<Melody> for u8 {
+    type Error = ();
+
+    fn try_from(melody: Melody) -> Result<Self, Self::Error> {
+        match melody {
+            Melody::Alarm => Ok(1),
+            Melody::Beep => Ok(2),
+            Melody::Reset => Ok(3),
+            Melody::Setup => Ok(4),
+            Melody::Work => Ok(5),
+            Melody::ShortBreak => Ok(6),
+            Melody::LongBreak => Ok(7),
+            Melody::Pause => Ok(8),
+            Melody::Resume => Ok(9),
+            Melody::Extend => Ok(10),
+            Melody::Stored => Ok(11),
+            Melody::Custom(_) => Err(()),
+        }
+    }
+}
+
 /// Melody that is being played when alarm triggers.
 /// Can be generated at https://onlinesequencer.net/
 const ALARM_MELODY: [Tone; 24] = [
@@ -79,6 +146,50 @@ const SETUP_MELODY: [Tone; 2] = [
     Tone::new(Note::DSharp5 as u8, NOTE_1_4_DURATION),
 ];
 
+/// Melody that is played when Pomodoro work interval starts.
+const WORK_MELODY: [Tone; 4] = [
+    Tone::new(Note::C6 as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::E6 as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::G6 as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::C7 as u8, NOTE_1_2_DURATION),
+];
+
+/// Melody that is played when Pomodoro short break starts.
+const SHORT_BREAK_MELODY: [Tone; 3] = [
+    Tone::new(Note::G6 as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::E6 as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::C6 as u8, NOTE_1_2_DURATION),
+];
+
+/// Melody that is played when Pomodoro long break starts.
+const LONG_BREAK_MELODY: [Tone; 6] = [
+    Tone::new(Note::C7 as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::G6 as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::E6 as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::C6 as u8, NOTE_1_2_DURATION),
+    Tone::new(Note::Silence as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::C6 as u8, NOTE_1_2_DURATION),
+];
+
+/// Melody that is played when timer is paused.
+const PAUSE_MELODY: [Tone; 2] = [
+    Tone::new(Note::G6 as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::C6 as u8, NOTE_1_2_DURATION),
+];
+
+/// Melody that is played when paused timer is resumed.
+const RESUME_MELODY: [Tone; 2] = [
+    Tone::new(Note::C6 as u8, NOTE_1_4_DURATION),
+    Tone::new(Note::G6 as u8, NOTE_1_2_DURATION),
+];
+
+/// Melody that is played when timer is extended.
+const EXTEND_MELODY: [Tone; 3] = [
+    Tone::new(Note::C6 as u8, NOTE_1_8_DURATION),
+    Tone::new(Note::C6 as u8, NOTE_1_8_DURATION),
+    Tone::new(Note::E6 as u8, NOTE_1_4_DURATION),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,6 +208,27 @@ mod tests {
         let melody_tones: Array<Tone> = Melody::Setup.into();
         assert_eq!(melody_tones, Array::from(&SETUP_MELODY));
 
+        let melody_tones: Array<Tone> = Melody::Work.into();
+        assert_eq!(melody_tones, Array::from(&WORK_MELODY));
+
+        let melody_tones: Array<Tone> = Melody::ShortBreak.into();
+        assert_eq!(melody_tones, Array::from(&SHORT_BREAK_MELODY));
+
+        let melody_tones: Array<Tone> = Melody::LongBreak.into();
+        assert_eq!(melody_tones, Array::from(&LONG_BREAK_MELODY));
+
+        let melody_tones: Array<Tone> = Melody::Pause.into();
+        assert_eq!(melody_tones, Array::from(&PAUSE_MELODY));
+
+        let melody_tones: Array<Tone> = Melody::Resume.into();
+        assert_eq!(melody_tones, Array::from(&RESUME_MELODY));
+
+        let melody_tones: Array<Tone> = Melody::Extend.into();
+        assert_eq!(melody_tones, Array::from(&EXTEND_MELODY));
+
+        let melody_tones: Array<Tone> = Melody::Stored.into();
+        assert_eq!(melody_tones, Array::from(&ALARM_MELODY));
+
         let custom_tones = [
             Tone::new(Note::A0 as u8, NOTE_1_4_DURATION),
             Tone::new(Note::DSharp1 as u8, NOTE_1_2_DURATION),
@@ -104,4 +236,21 @@ mod tests {
         let melody_tones: Array<Tone> = Melody::Custom(Array::from(&custom_tones)).into();
         assert_eq!(melody_tones, Array::from(&custom_tones));
     }
+
+    #[test]
+    fn properly_converts_to_and_from_id() {
+        for id in 1..=11 {
+            assert_eq!(u8::try_from(Melody::try_from(id).unwrap()), Ok(id));
+        }
+
+        assert!(Melody::try_from(0).is_err());
+        assert!(Melody::try_from(12).is_err());
+        assert_eq!(
+            u8::try_from(Melody::Custom(Array::from(&[Tone::new(
+                Note::A0 as u8,
+                NOTE_1_4_DURATION
+            )]))),
+            Err(())
+        );
+    }
 }
@@ -0,0 +1,242 @@
+use super::{note::Note, score::split_duration, tone::Tone};
+use array::Array;
+
+/// Speed (words per minute) used if it isn't specified explicitly.
+pub const DEFAULT_WPM: u8 = 20;
+
+/// Pitch used if it isn't specified explicitly.
+pub const DEFAULT_PITCH: Note = Note::A5;
+
+/// Supported speed range (words per minute).
+pub const MIN_WPM: u8 = 5;
+pub const MAX_WPM: u8 = 40;
+
+/// Duration of the dot is `1200 / wpm` ms, based on the standard `PARIS` word of 50 units.
+const UNIT_DURATION_MS: u32 = 1200;
+
+/// Dash and gap durations in units of the dot duration.
+const DASH_UNITS: u32 = 3;
+const LETTER_GAP_UNITS: u32 = 3;
+const WORD_GAP_UNITS: u32 = 7;
+
+/// Encodes ASCII text (letters, digits, common punctuation and spaces) into Morse code tones played
+/// with the specified speed and pitch, see `encode_each`.
+#[allow(clippy::result_unit_err)]
+pub fn encode(text: &[u8], wpm: u8, pitch: Note) -> Result<Array<Tone>, ()> {
+    let mut tones = Array::new();
+    let mut is_truncated = false;
+    encode_each(text, wpm, pitch, |tone| {
+        if tones.is_full() {
+            is_truncated = true;
+        } else {
+            tones.push(tone);
+        }
+    })?;
+
+    if is_truncated {
+        Err(())
+    } else {
+        Ok(tones)
+    }
+}
+
+/// Encodes text the same way `encode` does, but passes every tone to `on_tone` instead of
+/// collecting them, so that messages that don't fit into `Array` can be encoded as well. Fails
+/// without producing any tones if speed is out of range, pitch is silence or text has characters
+/// that can't be encoded.
+#[allow(clippy::result_unit_err)]
+pub fn encode_each<F: FnMut(Tone)>(
+    text: &[u8],
+    wpm: u8,
+    pitch: Note,
+    mut on_tone: F,
+) -> Result<(), ()> {
+    if !is_valid_wpm(wpm) || pitch == Note::Silence {
+        return Err(());
+    }
+
+    if text
+        .iter()
+        .any(|c| *c != b' ' && code(c.to_ascii_uppercase()).is_none())
+    {
+        return Err(());
+    }
+
+    let unit_ms = UNIT_DURATION_MS / u32::from(wpm);
+    let mut emit = |note: Note, units: u32| {
+        for chunk_ms in split_duration(unit_ms * units) {
+            on_tone(Tone::new(note as u8, chunk_ms));
+        }
+    };
+
+    // Gap is emitted only before the next mark, so that consecutive spaces don't accumulate and
+    // message doesn't start or end with silence.
+    let mut pending_gap_units = 0;
+    for c in text {
+        let code = match code(c.to_ascii_uppercase()) {
+            Some(code) => code,
+            None => {
+                if pending_gap_units > 0 {
+                    pending_gap_units = WORD_GAP_UNITS;
+                }
+                continue;
+            }
+        };
+
+        for mark in code.bytes() {
+            if pending_gap_units > 0 {
+                emit(Note::Silence, pending_gap_units);
+            }
+
+            emit(pitch, if mark == b'-' { DASH_UNITS } else { 1 });
+            pending_gap_units = 1;
+        }
+
+        pending_gap_units = pending_gap_units.max(LETTER_GAP_UNITS);
+    }
+
+    Ok(())
+}
+
+pub fn is_valid_wpm(wpm: u8) -> bool {
+    (MIN_WPM..=MAX_WPM).contains(&wpm)
+}
+
+/// Returns International Morse code of the uppercase ASCII character.
+fn code(c: u8) -> Option<&'static str> {
+    let code = match c {
+        b'A' => ".-",
+        b'B' => "-...",
+        b'C' => "-.-.",
+        b'D' => "-..",
+        b'E' => ".",
+        b'F' => "..-.",
+        b'G' => "--.",
+        b'H' => "....",
+        b'I' => "..",
+        b'J' => ".---",
+        b'K' => "-.-",
+        b'L' => ".-..",
+        b'M' => "--",
+        b'N' => "-.",
+        b'O' => "---",
+        b'P' => ".--.",
+        b'Q' => "--.-",
+        b'R' => ".-.",
+        b'S' => "...",
+        b'T' => "-",
+        b'U' => "..-",
+        b'V' => "...-",
+        b'W' => ".--",
+        b'X' => "-..-",
+        b'Y' => "-.--",
+        b'Z' => "--..",
+        b'0' => "-----",
+        b'1' => ".----",
+        b'2' => "..---",
+        b'3' => "...--",
+        b'4' => "....-",
+        b'5' => ".....",
+        b'6' => "-....",
+        b'7' => "--...",
+        b'8' => "---..",
+        b'9' => "----.",
+        b'.' => ".-.-.-",
+        b',' => "--..--",
+        b'?' => "..--..",
+        b'\'' => ".----.",
+        b'!' => "-.-.--",
+        b'/' => "-..-.",
+        b'(' => "-.--.",
+        b')' => "-.--.-",
+        b'&' => ".-...",
+        b':' => "---...",
+        b'=' => "-...-",
+        b'+' => ".-.-.",
+        b'-' => "-....-",
+        b'"' => ".-..-.",
+        b'@' => ".--.-.",
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::prelude::v1::*;
+
+    const DOT: Tone = Tone::new(Note::A5 as u8, 60);
+    const DASH: Tone = Tone::new(Note::A5 as u8, 180);
+    const GAP: Tone = Tone::new(Note::Silence as u8, 60);
+    const LETTER_GAP: Tone = Tone::new(Note::Silence as u8, 180);
+
+    #[test]
+    fn encodes_letters() {
+        assert_eq!(
+            encode(b"sOs", 20, Note::A5).unwrap().as_ref(),
+            [
+                DOT, GAP, DOT, GAP, DOT, LETTER_GAP, DASH, GAP, DASH, GAP, DASH, LETTER_GAP, DOT,
+                GAP, DOT, GAP, DOT,
+            ]
+        );
+    }
+
+    #[test]
+    fn encodes_words() {
+        let word_gap = Tone::new(Note::Silence as u8, 255);
+        let word_gap_rest = Tone::new(Note::Silence as u8, 165);
+
+        // Leading, trailing and consecutive spaces don't add extra gaps.
+        assert_eq!(
+            encode(b"  E  T ", 20, Note::A5).unwrap().as_ref(),
+            [DOT, word_gap, word_gap_rest, DASH]
+        );
+        assert_eq!(encode(b"   ", 20, Note::A5).unwrap().as_ref(), []);
+    }
+
+    #[test]
+    fn applies_speed_and_pitch() {
+        assert_eq!(
+            encode(b"A", 40, Note::C6).unwrap().as_ref(),
+            [
+                Tone::new(Note::C6 as u8, 30),
+                Tone::new(Note::Silence as u8, 30),
+                Tone::new(Note::C6 as u8, 90),
+            ]
+        );
+
+        // Dash at the slowest speed is longer than a single tone can last.
+        assert_eq!(
+            encode(b"T", 5, Note::C6).unwrap().as_ref(),
+            [
+                Tone::new(Note::C6 as u8, 255),
+                Tone::new(Note::C6 as u8, 255),
+                Tone::new(Note::C6 as u8, 210),
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_to_encode_invalid_message() {
+        assert_eq!(encode(b"SOS", 4, Note::A5), Err(()));
+        assert_eq!(encode(b"SOS", 41, Note::A5), Err(()));
+        assert_eq!(encode(b"SOS", 20, Note::Silence), Err(()));
+        assert_eq!(encode(b"SOS #1", 20, Note::A5), Err(()));
+        assert_eq!(encode("Ж".as_bytes(), 20, Note::A5), Err(()));
+    }
+
+    #[test]
+    fn fails_to_encode_too_long_message() {
+        let text = "0".repeat(7);
+        assert_eq!(encode(text.as_bytes(), 20, Note::A5), Err(()));
+
+        let mut n_tones = 0;
+        assert_eq!(
+            encode_each(text.as_bytes(), 20, Note::A5, |_| n_tones += 1),
+            Ok(())
+        );
+        assert_eq!(n_tones, 7 * 10 - 1);
+    }
+}
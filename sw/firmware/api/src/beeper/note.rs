@@ -3,7 +3,16 @@ pub const NOTE_1_8_DURATION: u8 = 50;
 pub const NOTE_1_4_DURATION: u8 = NOTE_1_8_DURATION * 2;
 pub const NOTE_1_2_DURATION: u8 = NOTE_1_4_DURATION * 2;
 
-#[derive(Debug, Copy, Clone)]
+use core::{convert::TryFrom, fmt, mem, str::FromStr};
+
+/// Note names in the order of semitones, starting from `C`.
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Every note is encoded as `(semitone + 1) << 4 | octave`, `0x00` is reserved for silence.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(u8)]
 pub enum Note {
     C0 = 0x10,
     CSharp0 = 0x20,
@@ -125,15 +134,173 @@ pub enum Note {
     Silence = 0x00,
 }
 
+impl Note {
+    /// Max octave the note can belong to.
+    pub const MAX_OCTAVE: u8 = 8;
+
+    /// Creates note from the semitone (`0..=11`, starting from `C`) and octave (`0..=8`).
+    #[allow(clippy::result_unit_err)]
+    pub fn new(semitone: u8, octave: u8) -> Result<Self, ()> {
+        if semitone > 11 || octave > Self::MAX_OCTAVE {
+            return Err(());
+        }
+
+        Self::try_from((semitone + 1) << 4 | octave)
+    }
+
+    /// Returns semitone (`0..=11`, starting from `C`) and octave of the note, `None` for silence.
+    pub fn semitone_and_octave(self) -> Option<(u8, u8)> {
+        match self {
+            Note::Silence => None,
+            note => Some(((note as u8 >> 4) - 1, note as u8 & 0xf)),
+        }
+    }
+
+    /// Shifts note by the specified number of semitones, fails if the resulting note is out of
+    /// range. Silence stays silence.
+    #[allow(clippy::result_unit_err)]
+    pub fn transpose(self, semitones: i8) -> Result<Self, ()> {
+        let (semitone, octave) = match self.semitone_and_octave() {
+            Some(semitone_and_octave) => semitone_and_octave,
+            None => return Ok(self),
+        };
+
+        let position = i16::from(octave) * 12 + i16::from(semitone) + i16::from(semitones);
+        if position < 0 || position >= (i16::from(Self::MAX_OCTAVE) + 1) * 12 {
+            return Err(());
+        }
+
+        Self::new((position % 12) as u8, (position / 12) as u8)
+    }
+}
+
+impl TryFrom<u8> for Note {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        let is_valid = value == Note::Silence as u8
+            || (matches!(value >> 4, 1..=12) && value & 0xf <= Note::MAX_OCTAVE);
+        if is_valid {
+            // Enum is `repr(u8)` and every valid value has the corresponding variant.
+            Ok(unsafe { mem::transmute::<u8, Note>(value) })
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// Formats note in scientific pitch notation, e.g. `C5` or `F#4`, silence is formatted as `R`.
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.semitone_and_octave() {
+            Some((semitone, octave)) => write!(f, "{}{}", NOTE_NAMES[semitone as usize], octave),
+            None => write!(f, "R"),
+        }
+    }
+}
+
+/// Parses note in scientific pitch notation, e.g. `C5`, `f#4` or `Bb3`. Both `R` and `P` mean
+/// silence (rest/pause).
+impl FromStr for Note {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.trim().chars();
+        let semitone: i8 = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('R') | Some('P') if chars.as_str().is_empty() => return Ok(Note::Silence),
+            Some('C') => 0,
+            Some('D') => 2,
+            Some('E') => 4,
+            Some('F') => 5,
+            Some('G') => 7,
+            Some('A') => 9,
+            Some('B') => 11,
+            _ => return Err(()),
+        };
+
+        let rest = chars.as_str();
+        let (semitones, octave) = if let Some(octave) = rest.strip_prefix('#') {
+            (1, octave)
+        } else if let Some(octave) = rest.strip_prefix('b') {
+            (-1, octave)
+        } else {
+            (0, rest)
+        };
+
+        let octave = match octave.as_bytes() {
+            [digit] if digit.is_ascii_digit() => digit - b'0',
+            _ => return Err(()),
+        };
+
+        Self::new(semitone as u8, octave)?.transpose(semitones)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::prelude::v1::*;
 
     #[test]
     fn silence_note_evaluates_to_zero() {
         assert_eq!(Note::Silence as u8, 0);
     }
 
+    #[test]
+    fn converts_from_u8() {
+        assert_eq!(Note::try_from(0x00), Ok(Note::Silence));
+        assert_eq!(Note::try_from(0x15), Ok(Note::C5));
+        assert_eq!(Note::try_from(0xC8), Ok(Note::B8));
+        assert_eq!(Note::try_from(0x05), Err(()));
+        assert_eq!(Note::try_from(0xD0), Err(()));
+        assert_eq!(Note::try_from(0x19), Err(()));
+    }
+
+    #[test]
+    fn transposes_notes() {
+        assert_eq!(Note::C5.transpose(0), Ok(Note::C5));
+        assert_eq!(Note::C5.transpose(4), Ok(Note::E5));
+        assert_eq!(Note::B4.transpose(1), Ok(Note::C5));
+        assert_eq!(Note::C5.transpose(-1), Ok(Note::B4));
+        assert_eq!(Note::A4.transpose(24), Ok(Note::A6));
+        assert_eq!(Note::Silence.transpose(5), Ok(Note::Silence));
+        assert_eq!(Note::C0.transpose(-1), Err(()));
+        assert_eq!(Note::B8.transpose(1), Err(()));
+    }
+
+    #[test]
+    fn parses_and_formats_notes() {
+        assert_eq!("C5".parse::<Note>(), Ok(Note::C5));
+        assert_eq!("f#4".parse::<Note>(), Ok(Note::FSharp4));
+        assert_eq!("Bb3".parse::<Note>(), Ok(Note::ASharp3));
+        assert_eq!("Cb5".parse::<Note>(), Ok(Note::B4));
+        assert_eq!("B#4".parse::<Note>(), Ok(Note::C5));
+        assert_eq!("r".parse::<Note>(), Ok(Note::Silence));
+        assert_eq!("P".parse::<Note>(), Ok(Note::Silence));
+
+        assert_eq!("".parse::<Note>(), Err(()));
+        assert_eq!("H5".parse::<Note>(), Err(()));
+        assert_eq!("C".parse::<Note>(), Err(()));
+        assert_eq!("C9".parse::<Note>(), Err(()));
+        assert_eq!("C55".parse::<Note>(), Err(()));
+        assert_eq!("Cb0".parse::<Note>(), Err(()));
+        assert_eq!("R5".parse::<Note>(), Err(()));
+
+        for note in [
+            Note::C0,
+            Note::FSharp4,
+            Note::ASharp7,
+            Note::B8,
+            Note::Silence,
+        ]
+        .iter()
+        {
+            assert_eq!(note.to_string().parse::<Note>(), Ok(*note));
+        }
+        assert_eq!(Note::DSharp6.to_string(), "D#6");
+        assert_eq!(Note::Silence.to_string(), "R");
+    }
+
     #[test]
     fn durations_are_properly_calculated() {
         assert_eq!(NOTE_1_8_DURATION, 50);
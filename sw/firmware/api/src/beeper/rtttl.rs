@@ -0,0 +1,244 @@
+use super::{
+    note::Note,
+    score::{split_duration, NoteLength, NoteValue},
+    tone::Tone,
+};
+use array::Array;
+use core::convert::TryFrom;
+
+/// Default values used if RTTTL ringtone doesn't specify them, see RTTTL specification.
+const DEFAULT_DURATION: u32 = 4;
+const DEFAULT_OCTAVE: u8 = 6;
+const DEFAULT_TEMPO: u32 = 63;
+
+/// Parses RTTTL (Nokia ringtone) string, e.g. `Beep:d=4,o=5,b=200:c,8p,8e6.`, into the tones that
+/// can be played by the beeper. Notes that are longer than a single tone can last are split into
+/// several consecutive tones of the same note.
+#[allow(clippy::result_unit_err)]
+pub fn parse(rtttl: &str) -> Result<Array<Tone>, ()> {
+    let mut tones = Array::new();
+    let mut is_truncated = false;
+    parse_each(rtttl, |tone| {
+        if tones.is_full() {
+            is_truncated = true;
+        } else {
+            tones.push(tone);
+        }
+    })?;
+
+    if is_truncated {
+        Err(())
+    } else {
+        Ok(tones)
+    }
+}
+
+/// Parses RTTTL string the same way `parse` does, but passes every tone to `on_tone` instead of
+/// collecting them, so that ringtones that don't fit into `Array` can be parsed as well.
+#[allow(clippy::result_unit_err)]
+pub fn parse_each<F: FnMut(Tone)>(rtttl: &str, mut on_tone: F) -> Result<(), ()> {
+    let mut sections = rtttl.trim().splitn(3, ':');
+    let (defaults, notes) = match (sections.next(), sections.next(), sections.next()) {
+        (Some(_), Some(defaults), Some(notes)) => (defaults, notes),
+        _ => return Err(()),
+    };
+
+    let (mut duration, mut octave, mut tempo) = (DEFAULT_DURATION, DEFAULT_OCTAVE, DEFAULT_TEMPO);
+    for default in defaults.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let mut pair = default.splitn(2, '=').map(str::trim);
+        match (pair.next(), pair.next().map(str::parse::<u32>)) {
+            (Some("d"), Some(Ok(value))) if is_valid_duration(value) => duration = value,
+            (Some("o"), Some(Ok(value))) if value <= 8 => octave = value as u8,
+            (Some("b"), Some(Ok(value))) if value > 0 => tempo = value,
+            _ => return Err(()),
+        }
+    }
+
+    for note in notes.split(',').map(str::trim) {
+        let (note, length) = parse_note(note.as_bytes(), duration, octave)?;
+        for chunk_ms in split_duration(length.duration_ms(tempo).max(1)) {
+            on_tone(Tone::new(note, chunk_ms));
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses single RTTTL note in the `[duration]note[#][.][octave][.]` form and returns note
+/// byte and its length.
+fn parse_note(
+    note: &[u8],
+    default_duration: u32,
+    default_octave: u8,
+) -> Result<(u8, NoteLength), ()> {
+    let digits = note.iter().take_while(|c| c.is_ascii_digit()).count();
+    let duration = match digits {
+        0 => default_duration,
+        _ => parse_number(&note[..digits])
+            .filter(|d| is_valid_duration(*d))
+            .ok_or(())?,
+    };
+
+    let mut rest = note[digits..].iter().map(u8::to_ascii_lowercase).peekable();
+    let semitone = match rest.next() {
+        Some(b'c') => 0,
+        Some(b'd') => 2,
+        Some(b'e') => 4,
+        Some(b'f') => 5,
+        Some(b'g') => 7,
+        Some(b'a') => 9,
+        Some(b'b') | Some(b'h') => 11,
+        Some(b'p') => {
+            let is_dotted = rest.peek() == Some(&b'.');
+            if is_dotted {
+                rest.next();
+            }
+
+            return match rest.next() {
+                None => Ok((Note::Silence as u8, note_length(duration, is_dotted)?)),
+                Some(_) => Err(()),
+            };
+        }
+        _ => return Err(()),
+    };
+
+    let is_sharp = rest.peek() == Some(&b'#');
+    if is_sharp {
+        rest.next();
+    }
+
+    // Dot can be specified either before or after the octave.
+    let mut is_dotted = false;
+    if rest.peek() == Some(&b'.') {
+        is_dotted = true;
+        rest.next();
+    }
+
+    let octave = match rest.peek() {
+        Some(c) if c.is_ascii_digit() && *c <= b'8' => {
+            let octave = c - b'0';
+            rest.next();
+            octave
+        }
+        _ => default_octave,
+    };
+
+    if !is_dotted && rest.peek() == Some(&b'.') {
+        is_dotted = true;
+        rest.next();
+    }
+
+    if rest.next().is_some() {
+        return Err(());
+    }
+
+    // `B#` is the `C` of the next octave.
+    let semitone = semitone + if is_sharp { 1 } else { 0 };
+    let (semitone, octave) = if semitone > 11 {
+        (0, octave + 1)
+    } else {
+        (semitone, octave)
+    };
+
+    if octave > 8 {
+        return Err(());
+    }
+
+    Ok((
+        ((semitone + 1) << 4) | octave,
+        note_length(duration, is_dotted)?,
+    ))
+}
+
+fn note_length(duration: u32, is_dotted: bool) -> Result<NoteLength, ()> {
+    Ok(NoteLength::new(NoteValue::try_from(duration)?, is_dotted))
+}
+
+fn parse_number(digits: &[u8]) -> Option<u32> {
+    digits.iter().try_fold(0u32, |number, digit| {
+        number
+            .checked_mul(10)
+            .and_then(|number| number.checked_add(u32::from(digit - b'0')))
+    })
+}
+
+fn is_valid_duration(duration: u32) -> bool {
+    NoteValue::try_from(duration).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::prelude::v1::*;
+
+    #[test]
+    fn parses_notes() {
+        let tones = parse("Test:d=4,o=5,b=200:c,8d#6,16p,2a.,b#4").unwrap();
+        assert_eq!(
+            tones.as_ref(),
+            [
+                Tone::new(Note::C5 as u8, 100),
+                Tone::new(Note::DSharp6 as u8, 50),
+                Tone::new(Note::Silence as u8, 25),
+                Tone::new(Note::A5 as u8, 255),
+                Tone::new(Note::A5 as u8, 45),
+                Tone::new(Note::C5 as u8, 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_defaults_and_tempo() {
+        // Default duration is 4, octave is 6 and tempo is 63 bpm.
+        let tones = parse("::C").unwrap();
+        assert_eq!(
+            tones.as_ref(),
+            [
+                Tone::new(Note::C6 as u8, 255),
+                Tone::new(Note::C6 as u8, 62),
+            ]
+        );
+
+        let tones = parse("Fast: b=400, d=8 :e.,G7,f#.5").unwrap();
+        assert_eq!(
+            tones.as_ref(),
+            [
+                Tone::new(Note::E6 as u8, 37),
+                Tone::new(Note::G7 as u8, 25),
+                Tone::new(Note::FSharp5 as u8, 37),
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_to_parse_invalid_ringtone() {
+        assert_eq!(parse(""), Err(()));
+        assert_eq!(parse("Test:d=4"), Err(()));
+        assert_eq!(parse("Test:d=3:c"), Err(()));
+        assert_eq!(parse("Test:o=9:c"), Err(()));
+        assert_eq!(parse("Test:b=0:c"), Err(()));
+        assert_eq!(parse("Test:x=1:c"), Err(()));
+        assert_eq!(parse("Test::"), Err(()));
+        assert_eq!(parse("Test::x"), Err(()));
+        assert_eq!(parse("Test::c9"), Err(()));
+        assert_eq!(parse("Test::c5x"), Err(()));
+        assert_eq!(parse("Test::3c"), Err(()));
+        assert_eq!(parse("Test::p5"), Err(()));
+    }
+
+    #[test]
+    fn fails_to_parse_too_long_ringtone() {
+        let mut rtttl = String::from("Long:d=16,o=5,b=200:c");
+        for _ in 0..63 {
+            rtttl.push_str(",c");
+        }
+        assert_eq!(parse(&rtttl).map(|tones| tones.len()), Ok(64));
+
+        rtttl.push_str(",c");
+        assert_eq!(parse(&rtttl), Err(()));
+
+        let mut n_tones = 0;
+        assert_eq!(parse_each(&rtttl, |_| n_tones += 1), Ok(()));
+        assert_eq!(n_tones, 65);
+    }
+}
@@ -0,0 +1,319 @@
+use super::{
+    note::{Note, NOTE_1_4_DURATION},
+    tone::Tone,
+};
+use array::Array;
+use core::{convert::TryFrom, fmt, str::FromStr};
+
+/// Tempo (beats per minute) at which note lengths match `NOTE_*_DURATION` constants.
+pub const REFERENCE_TEMPO: u32 = 200;
+
+/// Tempo used if it isn't specified explicitly.
+pub const DEFAULT_TEMPO: u32 = 120;
+
+/// Note value as a fraction of the whole note, e.g. `Quarter` is `1/4`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NoteValue {
+    Whole = 1,
+    Half = 2,
+    Quarter = 4,
+    Eighth = 8,
+    Sixteenth = 16,
+    ThirtySecond = 32,
+}
+
+impl TryFrom<u32> for NoteValue {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(NoteValue::Whole),
+            2 => Ok(NoteValue::Half),
+            4 => Ok(NoteValue::Quarter),
+            8 => Ok(NoteValue::Eighth),
+            16 => Ok(NoteValue::Sixteenth),
+            32 => Ok(NoteValue::ThirtySecond),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Describes how long note lasts, dotted note is one and a half times longer.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NoteLength {
+    pub value: NoteValue,
+    pub is_dotted: bool,
+}
+
+impl NoteLength {
+    pub const fn new(value: NoteValue, is_dotted: bool) -> Self {
+        NoteLength { value, is_dotted }
+    }
+
+    /// Returns note duration in milliseconds at the specified tempo, whole note lasts for four
+    /// beats.
+    pub fn duration_ms(self, tempo: u32) -> u32 {
+        let duration_ms =
+            u32::from(NOTE_1_4_DURATION) * 4 * REFERENCE_TEMPO / (self.value as u32 * tempo.max(1));
+        if self.is_dotted {
+            duration_ms + duration_ms / 2
+        } else {
+            duration_ms
+        }
+    }
+}
+
+/// Formats length as the note value denominator followed by the optional dot, e.g. `4` or `8.`.
+impl fmt::Display for NoteLength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            self.value as u32,
+            if self.is_dotted { "." } else { "" }
+        )
+    }
+}
+
+impl FromStr for NoteLength {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, is_dotted) = match s.strip_suffix('.') {
+            Some(value) => (value, true),
+            None => (s, false),
+        };
+
+        let value = value.parse::<u32>().map_err(|_| ())?;
+        Ok(NoteLength::new(NoteValue::try_from(value)?, is_dotted))
+    }
+}
+
+/// Single note or rest (`Note::Silence`) of the score.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScoreNote {
+    pub note: Note,
+    pub length: NoteLength,
+}
+
+impl ScoreNote {
+    pub const fn new(note: Note, length: NoteLength) -> Self {
+        ScoreNote { note, length }
+    }
+}
+
+impl Default for ScoreNote {
+    fn default() -> Self {
+        ScoreNote::new(Note::Silence, NoteLength::new(NoteValue::Quarter, false))
+    }
+}
+
+/// Formats note as `note/length`, e.g. `C5/4` or `R/2.`.
+impl fmt::Display for ScoreNote {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.note, self.length)
+    }
+}
+
+/// Parses note in the `note[/length]` form, note is a quarter one if length isn't specified.
+impl FromStr for ScoreNote {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+        let note = parts.next().ok_or(())?.parse::<Note>()?;
+        let length = match parts.next() {
+            Some(length) => length.parse::<NoteLength>()?,
+            None => ScoreNote::default().length,
+        };
+
+        Ok(ScoreNote::new(note, length))
+    }
+}
+
+/// Melody written as a sequence of notes with musical lengths played at the specified tempo.
+#[derive(Copy, Clone)]
+pub struct Score {
+    /// Tempo in beats (quarter notes) per minute.
+    pub tempo: u32,
+    pub notes: Array<ScoreNote>,
+}
+
+impl Score {
+    pub fn new(tempo: u32) -> Self {
+        Score {
+            tempo,
+            notes: Array::new(),
+        }
+    }
+
+    /// Parses whitespace separated notes, e.g. `C5/4 E5/8 R/2. G5`, fails if tempo is zero or
+    /// notes don't fit into `Array`.
+    #[allow(clippy::result_unit_err)]
+    pub fn parse(tempo: u32, text: &str) -> Result<Self, ()> {
+        if tempo == 0 {
+            return Err(());
+        }
+
+        let mut score = Score::new(tempo);
+        for note in text.split_whitespace() {
+            if score.notes.is_full() {
+                return Err(());
+            }
+
+            score.notes.push(note.parse()?);
+        }
+
+        Ok(score)
+    }
+
+    /// Shifts all notes by the specified number of semitones, score isn't changed if any of the
+    /// notes goes out of range.
+    #[allow(clippy::result_unit_err)]
+    pub fn transpose(&mut self, semitones: i8) -> Result<(), ()> {
+        let mut notes = self.notes;
+        for note in notes.as_mut() {
+            note.note = note.note.transpose(semitones)?;
+        }
+
+        self.notes = notes;
+        Ok(())
+    }
+
+    /// Compiles score into the tones that can be played by the beeper and passes every tone to
+    /// `on_tone`. Notes that are longer than a single tone can last are split into several
+    /// consecutive tones of the same note.
+    pub fn for_each_tone<F: FnMut(Tone)>(&self, mut on_tone: F) {
+        for note in self.notes.as_ref() {
+            for chunk_ms in split_duration(note.length.duration_ms(self.tempo).max(1)) {
+                on_tone(Tone::new(note.note as u8, chunk_ms));
+            }
+        }
+    }
+
+    /// Compiles score into the tones, fails if they don't fit into `Array`.
+    #[allow(clippy::result_unit_err)]
+    pub fn tones(&self) -> Result<Array<Tone>, ()> {
+        let mut tones = Array::new();
+        let mut is_truncated = false;
+        self.for_each_tone(|tone| {
+            if tones.is_full() {
+                is_truncated = true;
+            } else {
+                tones.push(tone);
+            }
+        });
+
+        if is_truncated {
+            Err(())
+        } else {
+            Ok(tones)
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, note) in self.notes.as_ref().iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+
+            write!(f, "{}", note)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits duration into chunks that fit into a single tone.
+pub fn split_duration(duration_ms: u32) -> impl Iterator<Item = u8> {
+    let max = u32::from(u8::MAX);
+    (0..duration_ms.div_ceil(max)).map(move |index| (duration_ms - index * max).min(max) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::prelude::v1::*;
+
+    #[test]
+    fn calculates_duration() {
+        let quarter = NoteLength::new(NoteValue::Quarter, false);
+        assert_eq!(quarter.duration_ms(REFERENCE_TEMPO), 100);
+        assert_eq!(quarter.duration_ms(100), 200);
+        assert_eq!(NoteLength::new(NoteValue::Half, true).duration_ms(200), 300);
+        assert_eq!(
+            NoteLength::new(NoteValue::Whole, false).duration_ms(50),
+            1600
+        );
+    }
+
+    #[test]
+    fn parses_and_formats_score() {
+        let score = Score::parse(200, " C5/4 e5/8. R/2\tbb4 ").unwrap();
+        assert_eq!(
+            score.notes.as_ref(),
+            [
+                ScoreNote::new(Note::C5, NoteLength::new(NoteValue::Quarter, false)),
+                ScoreNote::new(Note::E5, NoteLength::new(NoteValue::Eighth, true)),
+                ScoreNote::new(Note::Silence, NoteLength::new(NoteValue::Half, false)),
+                ScoreNote::new(Note::ASharp4, NoteLength::new(NoteValue::Quarter, false)),
+            ]
+        );
+        assert_eq!(score.to_string(), "C5/4 E5/8. R/2 A#4/4");
+        assert_eq!(
+            Score::parse(200, &score.to_string()).map(|s| s.to_string()),
+            Ok(score.to_string())
+        );
+    }
+
+    #[test]
+    fn fails_to_parse_invalid_score() {
+        assert!(Score::parse(0, "C5/4").is_err());
+        assert!(Score::parse(120, "C5/3").is_err());
+        assert!(Score::parse(120, "C5/").is_err());
+        assert!(Score::parse(120, "C5/4..").is_err());
+        assert!(Score::parse(120, "X5/4").is_err());
+        assert!(Score::parse(120, &"C5 ".repeat(65)).is_err());
+        assert_eq!(Score::parse(120, "").map(|s| s.notes.len()), Ok(0));
+    }
+
+    #[test]
+    fn compiles_into_tones() {
+        // Whole rest at 50 bpm lasts longer than a single tone.
+        let score = Score::parse(50, "C5/8 R/1 E5/16.").unwrap();
+        assert_eq!(
+            score.tones().unwrap().as_ref(),
+            [
+                Tone::new(Note::C5 as u8, 200),
+                Tone::new(Note::Silence as u8, 255),
+                Tone::new(Note::Silence as u8, 255),
+                Tone::new(Note::Silence as u8, 255),
+                Tone::new(Note::Silence as u8, 255),
+                Tone::new(Note::Silence as u8, 255),
+                Tone::new(Note::Silence as u8, 255),
+                Tone::new(Note::Silence as u8, 70),
+                Tone::new(Note::E5 as u8, 150),
+            ]
+        );
+
+        let score = Score::parse(50, &"R/1 ".repeat(10)).unwrap();
+        assert_eq!(score.tones().map(|tones| tones.len()), Err(()));
+
+        let mut n_tones = 0;
+        score.for_each_tone(|_| n_tones += 1);
+        assert_eq!(n_tones, 70);
+    }
+
+    #[test]
+    fn transposes_score() {
+        let mut score = Score::parse(120, "C5/4 R/4 B4/8").unwrap();
+        assert_eq!(score.transpose(2), Ok(()));
+        assert_eq!(score.to_string(), "D5/4 R/4 C#5/8");
+
+        let mut score = Score::parse(120, "C5/4 B8/4").unwrap();
+        assert_eq!(score.transpose(1), Err(()));
+        assert_eq!(score.to_string(), "C5/4 B8/4");
+    }
+}
@@ -0,0 +1,116 @@
+use super::tone::Tone;
+use array::Array;
+use core::convert::TryFrom;
+use flash::{storage_slot::StorageSlot, Flash, FlashHardware};
+
+/// Describes which sounds are played with the melody stored in flash instead of the built-in ones.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct StoredMelodyConfig {
+    /// Melody played when alarm fires.
+    pub alarm: bool,
+    /// Melody played on every alarm snooze.
+    pub snooze: bool,
+    /// Melody played when alarm is set.
+    pub setup: bool,
+}
+
+impl StoredMelodyConfig {
+    /// Reads config from the flash, built-in melodies are used for all sounds by default.
+    pub fn read<T: FlashHardware>(flash: &Flash<T>) -> Self {
+        flash
+            .read(StorageSlot::StoredMelodyConfig)
+            .and_then(|flags| Self::try_from(flags).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persists config in the flash.
+    #[allow(clippy::result_unit_err)]
+    pub fn write<T: FlashHardware>(&self, flash: &Flash<T>) -> Result<(), ()> {
+        flash.write(StorageSlot::StoredMelodyConfig, (*self).into())
+    }
+}
+
+/// Config is serialized as a set of flags: `0b001` - alarm, `0b010` - snooze, `0b100` - setup.
+impl From<StoredMelodyConfig> for u8 {
+    fn from(config: StoredMelodyConfig) -> Self {
+        (config.alarm as u8) | (config.snooze as u8) << 1 | (config.setup as u8) << 2
+    }
+}
+
+impl TryFrom<u8> for StoredMelodyConfig {
+    type Error = ();
+
+    fn try_from(flags: u8) -> Result<Self, Self::Error> {
+        if flags > 0b111 {
+            return Err(());
+        }
+
+        Ok(StoredMelodyConfig {
+            alarm: flags & 0b001 != 0,
+            snooze: flags & 0b010 != 0,
+            setup: flags & 0b100 != 0,
+        })
+    }
+}
+
+/// Reads melody stored in flash, every tone is stored as a single `note << 8 | duration` value.
+pub fn read<T: FlashHardware>(flash: &Flash<T>) -> Array<Tone> {
+    let mut tones = Array::new();
+    for value in flash.read_blob().as_ref() {
+        tones.push(Tone::new((value >> 8) as u8, *value as u8));
+    }
+
+    tones
+}
+
+/// Appends tones to the melody stored in flash, fails if the resulting melody doesn't fit into
+/// `Array` and returns it otherwise.
+#[allow(clippy::result_unit_err)]
+pub fn append<T: FlashHardware>(flash: &Flash<T>, tones: &[Tone]) -> Result<Array<Tone>, ()> {
+    let mut melody = read(flash);
+    let mut values: Array<u16> = Array::new();
+    for tone in tones {
+        if melody.is_full() {
+            return Err(());
+        }
+
+        melody.push(*tone);
+        values.push(u16::from(tone.note) << 8 | u16::from(tone.duration));
+    }
+
+    flash.append_blob(values.as_ref())?;
+
+    Ok(melody)
+}
+
+/// Erases melody stored in flash.
+pub fn clear<T: FlashHardware>(flash: &Flash<T>) {
+    flash.erase_blob();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correctly_serializes_config() {
+        let config = StoredMelodyConfig {
+            alarm: true,
+            snooze: false,
+            setup: true,
+        };
+
+        assert_eq!(u8::from(config), 0b101);
+        assert_eq!(StoredMelodyConfig::try_from(0b101), Ok(config));
+        assert_eq!(
+            StoredMelodyConfig::try_from(0),
+            Ok(StoredMelodyConfig::default())
+        );
+    }
+
+    #[test]
+    fn fails_to_deserialize_invalid_config() {
+        assert_eq!(StoredMelodyConfig::try_from(0b1000), Err(()));
+        assert_eq!(StoredMelodyConfig::try_from(0xff), Err(()));
+    }
+}
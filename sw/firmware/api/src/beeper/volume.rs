@@ -0,0 +1,188 @@
+use array::Array;
+use core::convert::TryFrom;
+use flash::{storage_key::StorageKey, storage_value::StorageValue, Flash, FlashHardware};
+
+/// Max PWM duty cycle (in percent), square wave with 50% duty cycle sounds the loudest.
+pub const MAX_DUTY_CYCLE: u8 = 50;
+
+/// Quiet variant of the melody is played with a quarter of the configured duty cycle.
+const QUIET_DUTY_CYCLE_DIVIDER: u8 = 4;
+
+/// Defines how loud melody is played relative to the configured volume.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum Volume {
+    /// Melody is played with the configured volume.
+    #[default]
+    Normal,
+    /// Melody is played quieter than configured.
+    Quiet,
+    /// Melody starts quietly and gradually gets louder up to the configured volume.
+    Crescendo,
+    /// Melody is played louder than configured by the specified duty cycle (in percent), but not
+    /// louder than the max volume.
+    Louder(u8),
+}
+
+impl Volume {
+    /// Returns duty cycle for the tone at `index` of the melody with `length` tones.
+    pub fn duty_cycle(self, duty_cycle: u8, index: usize, length: usize) -> u8 {
+        let quiet_duty_cycle = (duty_cycle / QUIET_DUTY_CYCLE_DIVIDER).max(1);
+        match self {
+            Volume::Normal => duty_cycle,
+            Volume::Quiet => quiet_duty_cycle,
+            Volume::Crescendo if length > 1 => {
+                let range = usize::from(duty_cycle.saturating_sub(quiet_duty_cycle));
+                quiet_duty_cycle + (range * index.min(length - 1) / (length - 1)) as u8
+            }
+            Volume::Crescendo => duty_cycle,
+            Volume::Louder(boost) => duty_cycle.saturating_add(boost).min(MAX_DUTY_CYCLE),
+        }
+    }
+}
+
+impl TryFrom<u8> for Volume {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Volume::Normal),
+            2 => Ok(Volume::Quiet),
+            3 => Ok(Volume::Crescendo),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Louder volume is relative to the escalation level and can't be persisted.
+impl TryFrom<Volume> for u8 {
+    type Error = ();
+
+    fn try_from(volume: Volume) -> Result<Self, Self::Error> {
+        match volume {
+            Volume::Normal => Ok(1),
+            Volume::Quiet => Ok(2),
+            Volume::Crescendo => Ok(3),
+            Volume::Louder(_) => Err(()),
+        }
+    }
+}
+
+/// Describes how loud beeper plays melodies.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BeeperConfig {
+    /// PWM duty cycle (in percent) that defines the volume: `1..=50`.
+    pub duty_cycle: u8,
+    /// Volume variant used for the alarm and snooze melodies.
+    pub alarm_volume: Volume,
+}
+
+impl BeeperConfig {
+    /// Reads config from the flash falling back to the default one if it's missing or invalid.
+    pub fn read<T: FlashHardware>(flash: &Flash<T>) -> Self {
+        flash
+            .read_value(StorageKey::BeeperConfig.into())
+            .unwrap_or_default()
+    }
+
+    /// Persists config in the flash.
+    #[allow(clippy::result_unit_err)]
+    pub fn write<T: FlashHardware>(&self, flash: &Flash<T>) -> Result<(), ()> {
+        if !is_valid_duty_cycle(self.duty_cycle) || u8::try_from(self.alarm_volume).is_err() {
+            return Err(());
+        }
+
+        flash.write_value(StorageKey::BeeperConfig.into(), *self)
+    }
+}
+
+impl Default for BeeperConfig {
+    fn default() -> Self {
+        BeeperConfig {
+            duty_cycle: MAX_DUTY_CYCLE,
+            alarm_volume: Volume::Normal,
+        }
+    }
+}
+
+/// Volume that can't be persisted is serialized as `0` that is rejected during deserialization.
+impl From<BeeperConfig> for Array<u8> {
+    fn from(config: BeeperConfig) -> Self {
+        [
+            config.duty_cycle,
+            u8::try_from(config.alarm_volume).unwrap_or(0),
+        ]
+        .as_ref()
+        .into()
+    }
+}
+
+impl TryFrom<&[u8]> for BeeperConfig {
+    type Error = ();
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value {
+            [duty_cycle, alarm_volume] if is_valid_duty_cycle(*duty_cycle) => Ok(BeeperConfig {
+                duty_cycle: *duty_cycle,
+                alarm_volume: Volume::try_from(*alarm_volume)?,
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+impl StorageValue for BeeperConfig {
+    fn to_bytes(&self) -> Array<u8> {
+        Array::from(*self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from(bytes).ok()
+    }
+}
+
+fn is_valid_duty_cycle(duty_cycle: u8) -> bool {
+    duty_cycle > 0 && duty_cycle <= MAX_DUTY_CYCLE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calculates_duty_cycle() {
+        assert_eq!(Volume::Normal.duty_cycle(40, 0, 5), 40);
+        assert_eq!(Volume::Quiet.duty_cycle(40, 4, 5), 10);
+        assert_eq!(Volume::Quiet.duty_cycle(2, 0, 5), 1);
+
+        for (index, duty_cycle) in [10, 17, 25, 32, 40].iter().enumerate() {
+            assert_eq!(Volume::Crescendo.duty_cycle(40, index, 5), *duty_cycle);
+        }
+        assert_eq!(Volume::Crescendo.duty_cycle(40, 0, 1), 40);
+
+        assert_eq!(Volume::Louder(5).duty_cycle(40, 0, 5), 45);
+        assert_eq!(Volume::Louder(15).duty_cycle(40, 0, 5), 50);
+    }
+
+    #[test]
+    fn correctly_serializes_config() {
+        let config = BeeperConfig {
+            duty_cycle: 25,
+            alarm_volume: Volume::Crescendo,
+        };
+
+        assert_eq!(Array::from(config).as_ref(), [25, 3]);
+        assert_eq!(BeeperConfig::try_from([25, 3].as_ref()), Ok(config));
+
+        assert_eq!(config.to_bytes().as_ref(), [25, 3]);
+        assert_eq!(BeeperConfig::from_bytes(&[25, 3]), Some(config));
+        assert_eq!(BeeperConfig::from_bytes(&[25, 4]), None);
+    }
+
+    #[test]
+    fn fails_to_deserialize_invalid_config() {
+        assert_eq!(BeeperConfig::try_from([0, 1].as_ref()), Err(()));
+        assert_eq!(BeeperConfig::try_from([51, 1].as_ref()), Err(()));
+        assert_eq!(BeeperConfig::try_from([25, 4].as_ref()), Err(()));
+        assert_eq!(BeeperConfig::try_from([25].as_ref()), Err(()));
+    }
+}
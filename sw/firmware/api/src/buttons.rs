@@ -1,3 +1,10 @@
+use array::Array;
+use core::convert::TryFrom;
+use flash::{storage_key::StorageKey, storage_value::StorageValue, Flash, FlashHardware};
+
+/// Buttons timings are persisted and transferred in steps of 50ms to fit into a byte.
+const TIMING_STEP: u32 = 50;
+
 /// Defines known button types.
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
 pub enum ButtonType {
@@ -5,26 +12,154 @@ pub enum ButtonType {
     Ten,
 }
 
-/// Defines type of the press (short, long, very long).
+/// Defines type of the press (short, long, very long or double click).
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ButtonPressType {
     /// Button is not pressed.
     None,
     /// Button is keep pressed for less then a second.
     Short,
-    /// Button is pressed for more than a second, but less than 2 seconds.
+    /// Button is pressed for more than a second, but less than 5 seconds.
     Long,
+    /// Button is pressed for 5 seconds or more.
+    VeryLong,
+    /// Button is shortly pressed, released and then pressed again shortly after.
+    DoubleClick,
 }
 
 impl ButtonPressType {
     pub fn is_none(self) -> bool {
-        match self {
-            ButtonPressType::None => true,
-            _ => false,
+        matches!(self, ButtonPressType::None)
+    }
+}
+
+/// Describes buttons timings (in ms), all thresholds are measured since the beginning of the poll.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ButtonsConfig {
+    /// Interval between consecutive buttons polls.
+    pub poll_interval: u32,
+    /// Max time when button press can still be considered as short.
+    pub short_press: u32,
+    /// Time after which button press is considered as long.
+    pub long_press: u32,
+    /// Time after which button press is considered as very long.
+    pub very_long_press: u32,
+    /// Time we wait for the second click after buttons are released.
+    pub double_click: u32,
+}
+
+impl ButtonsConfig {
+    /// Reads config from the flash falling back to the default one if it's missing or invalid.
+    pub fn read<T: FlashHardware>(flash: &Flash<T>) -> Self {
+        flash
+            .read_value(StorageKey::ButtonsConfig.into())
+            .unwrap_or_default()
+    }
+
+    /// Persists config in the flash.
+    #[allow(clippy::result_unit_err)]
+    pub fn write<T: FlashHardware>(&self, flash: &Flash<T>) -> Result<(), ()> {
+        if !self.is_valid() {
+            return Err(());
+        }
+
+        flash.write_value(StorageKey::ButtonsConfig.into(), *self)
+    }
+
+    /// Checks whether all timings are positive, fit into a byte when persisted and press thresholds
+    /// go in the ascending order.
+    fn is_valid(&self) -> bool {
+        let timings = [
+            self.poll_interval,
+            self.short_press,
+            self.long_press,
+            self.very_long_press,
+            self.double_click,
+        ];
+
+        timings
+            .iter()
+            .all(|timing| *timing > 0 && *timing <= u32::from(u8::MAX) * TIMING_STEP)
+            && self.short_press < self.long_press
+            && self.long_press < self.very_long_press
+    }
+}
+
+impl Default for ButtonsConfig {
+    fn default() -> Self {
+        ButtonsConfig {
+            poll_interval: 250,
+            short_press: 500,
+            long_press: 1250,
+            very_long_press: 5000,
+            double_click: 500,
         }
     }
 }
 
+/// Timings are serialized in 50ms steps, so they are rounded down to the closest step.
+impl From<ButtonsConfig> for Array<u8> {
+    fn from(config: ButtonsConfig) -> Self {
+        let to_steps = |timing: u32| (timing / TIMING_STEP) as u8;
+        [
+            to_steps(config.poll_interval),
+            to_steps(config.short_press),
+            to_steps(config.long_press),
+            to_steps(config.very_long_press),
+            to_steps(config.double_click),
+        ]
+        .as_ref()
+        .into()
+    }
+}
+
+impl TryFrom<&[u8]> for ButtonsConfig {
+    type Error = ();
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let from_steps = |steps: u8| u32::from(steps) * TIMING_STEP;
+        match value {
+            [poll_interval, short_press, long_press, very_long_press, double_click] => {
+                let config = ButtonsConfig {
+                    poll_interval: from_steps(*poll_interval),
+                    short_press: from_steps(*short_press),
+                    long_press: from_steps(*long_press),
+                    very_long_press: from_steps(*very_long_press),
+                    double_click: from_steps(*double_click),
+                };
+
+                if config.is_valid() {
+                    Ok(config)
+                } else {
+                    Err(())
+                }
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl StorageValue for ButtonsConfig {
+    fn to_bytes(&self) -> Array<u8> {
+        Array::from(*self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from(bytes).ok()
+    }
+}
+
+/// Describes gestures that are bound in the current context and that buttons poll should wait for.
+/// If gesture isn't bound, poll doesn't wait for it and reports press as soon as possible.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ButtonsGestures {
+    /// Whether poll should wait for the second click once buttons are released after short press.
+    pub double_click: bool,
+    /// Whether poll should keep waiting if both buttons are still pressed once long press
+    /// threshold is reached.
+    pub very_long_press: bool,
+}
+
 pub type ButtonsPollResult = (ButtonPressType, ButtonPressType, u32);
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -35,7 +170,15 @@ pub enum ButtonsPoll {
 
 #[derive(Copy, Clone, Default)]
 pub struct ButtonsState {
+    pub config: ButtonsConfig,
+    /// Gestures bound in the current context, they are set before every new poll.
+    pub gestures: ButtonsGestures,
     pub poll_result: Option<ButtonsPollResult>,
+    /// Poll time when both buttons were released after a short press, until double click
+    /// threshold is reached we wait for the second click.
+    pub released_at: Option<u32>,
+    /// Whether button Ⅰ and button Ⅹ respectively were triggered since the last poll.
+    pub triggered: (bool, bool),
 }
 
 /// Describes the Buttons hardware management interface.
@@ -63,37 +206,108 @@ impl<'a, T: ButtonsHardware> Buttons<'a, T> {
     }
 
     pub fn poll(&mut self) -> ButtonsPoll {
+        let config = self.state.config;
+        let gestures = self.state.gestures;
         let button_one_pressed = self.hw.is_button_pressed(ButtonType::One);
         let button_ten_pressed = self.hw.is_button_pressed(ButtonType::Ten);
-        let get_button_state =
-            |previous_state: ButtonPressType, is_pressed: bool, pending_time: u32| {
-                if is_pressed && pending_time <= 500 {
+
+        // Second click may be too short to be noticed by the poll, so we rely on triggers instead.
+        // Triggers that happen before buttons are released are ignored to not confuse contact
+        // bouncing with the second click.
+        let (button_one_triggered, button_ten_triggered) = if self.state.released_at.is_some() {
+            self.state.triggered
+        } else {
+            (false, false)
+        };
+        self.state.triggered = (false, false);
+
+        let get_button_state = |previous_state: ButtonPressType,
+                                is_pressed: bool,
+                                is_triggered: bool,
+                                pending_time: u32| {
+            match previous_state {
+                ButtonPressType::Short if is_triggered => ButtonPressType::DoubleClick,
+                ButtonPressType::None | ButtonPressType::Short
+                    if is_pressed && pending_time <= config.short_press =>
+                {
                     ButtonPressType::Short
-                } else if is_pressed && pending_time >= 1250 && !previous_state.is_none() {
+                }
+                ButtonPressType::Short if is_pressed && pending_time >= config.long_press => {
                     ButtonPressType::Long
-                } else {
-                    previous_state
                 }
-            };
+                ButtonPressType::Long if is_pressed && pending_time >= config.very_long_press => {
+                    ButtonPressType::VeryLong
+                }
+                _ => previous_state,
+            }
+        };
 
         let (button_one_prev_state, button_ten_prev_state, pending_time) = self
             .state
             .poll_result
-            .unwrap_or_else(|| (ButtonPressType::None, ButtonPressType::None, 0));
-        let button_one_state =
-            get_button_state(button_one_prev_state, button_one_pressed, pending_time);
-        let button_ten_state =
-            get_button_state(button_ten_prev_state, button_ten_pressed, pending_time);
+            .unwrap_or((ButtonPressType::None, ButtonPressType::None, 0));
+        let button_one_state = get_button_state(
+            button_one_prev_state,
+            button_one_pressed,
+            button_one_triggered,
+            pending_time,
+        );
+        let button_ten_state = get_button_state(
+            button_ten_prev_state,
+            button_ten_pressed,
+            button_ten_triggered,
+            pending_time,
+        );
+
+        // Only short presses can turn into double click.
+        let can_be_double_clicked = matches!(
+            (button_one_state, button_ten_state),
+            (ButtonPressType::Short, ButtonPressType::Short)
+                | (ButtonPressType::Short, ButtonPressType::None)
+                | (ButtonPressType::None, ButtonPressType::Short)
+        );
+
+        let is_ready = if pending_time >= config.very_long_press {
+            true
+        } else if button_one_pressed || button_ten_pressed {
+            // Long press is reported while buttons are still pressed, unless both of them are
+            // pressed and may turn into very long press.
+            pending_time >= config.long_press
+                && !(gestures.very_long_press && button_one_pressed && button_ten_pressed)
+        } else {
+            match self.state.released_at {
+                _ if !gestures.double_click || !can_be_double_clicked => true,
+                Some(released_at) => pending_time - released_at >= config.double_click,
+                None => {
+                    self.state.released_at = Some(pending_time);
+                    false
+                }
+            }
+        };
 
-        if (!button_one_pressed && !button_ten_pressed) || pending_time >= 1250 {
+        if is_ready {
             self.state.poll_result = None;
+            self.state.released_at = None;
             ButtonsPoll::Ready((button_one_state, button_ten_state, pending_time))
         } else {
-            self.state.poll_result = Some((button_one_state, button_ten_state, pending_time + 250));
-            ButtonsPoll::Pending(250)
+            self.state.poll_result = Some((
+                button_one_state,
+                button_ten_state,
+                pending_time + config.poll_interval,
+            ));
+            ButtonsPoll::Pending(config.poll_interval)
         }
     }
 
+    /// Remembers which of the control buttons were triggered while buttons are being polled.
+    pub fn track_triggers(&mut self) {
+        let (button_one_triggered, button_ten_triggered) = self.state.triggered;
+        self.state.triggered = (
+            button_one_triggered || self.hw.is_button_triggered(ButtonType::One),
+            button_ten_triggered || self.hw.is_button_triggered(ButtonType::Ten),
+        );
+    }
+
     /// Detects whether buttons are in the middle of the poll.
     pub fn is_polling(&self) -> bool {
         self.state.poll_result.is_some()
@@ -150,6 +364,92 @@ mod tests {
         }
     }
 
+    fn state_with_all_gestures() -> ButtonsState {
+        ButtonsState {
+            gestures: ButtonsGestures {
+                double_click: true,
+                very_long_press: true,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn buttons_config_serialization() {
+        let config = ButtonsConfig::default();
+        assert_eq!(Array::from(config).as_ref(), [5, 10, 25, 100, 10]);
+        assert_eq!(
+            ButtonsConfig::try_from([5, 10, 25, 100, 10].as_ref()),
+            Ok(config)
+        );
+
+        // Thresholds should be positive and go in the ascending order.
+        assert_eq!(
+            ButtonsConfig::try_from([0, 10, 25, 100, 10].as_ref()),
+            Err(())
+        );
+        assert_eq!(
+            ButtonsConfig::try_from([5, 25, 25, 100, 10].as_ref()),
+            Err(())
+        );
+        assert_eq!(
+            ButtonsConfig::try_from([5, 10, 100, 100, 10].as_ref()),
+            Err(())
+        );
+        assert_eq!(ButtonsConfig::try_from([5, 10, 25, 100].as_ref()), Err(()));
+
+        assert_eq!(config.to_bytes().as_ref(), [5, 10, 25, 100, 10]);
+        assert_eq!(
+            ButtonsConfig::from_bytes(&[5, 10, 25, 100, 10]),
+            Some(config)
+        );
+        assert_eq!(ButtonsConfig::from_bytes(&[5, 25, 25, 100, 10]), None);
+    }
+
+    #[test]
+    fn uses_custom_timings() {
+        let pending_time = RefCell::new(0);
+        let mut state = ButtonsState {
+            config: ButtonsConfig {
+                poll_interval: 500,
+                short_press: 1000,
+                long_press: 3000,
+                very_long_press: 10000,
+                double_click: 1000,
+            },
+            gestures: ButtonsGestures {
+                double_click: true,
+                very_long_press: true,
+            },
+            ..Default::default()
+        };
+        let buttons_hw_mock = ButtonsHardwareMock {
+            data: RefCell::new(MockData::new(AssociatedData {
+                is_button_pressed: |bt: ButtonType| match bt {
+                    ButtonType::One => *pending_time.borrow() < 2500,
+                    ButtonType::Ten => false,
+                },
+                is_button_triggered: |_: ButtonType| true,
+            })),
+        };
+
+        // Press that would have been long with default timings is still short.
+        for time in (0..3500).step_by(500) {
+            *pending_time.borrow_mut() = time;
+            assert_eq!(
+                Buttons::new(&buttons_hw_mock, &mut state).poll(),
+                ButtonsPoll::Pending(500)
+            );
+        }
+
+        *pending_time.borrow_mut() = 3500;
+
+        assert_eq!(
+            Buttons::new(&buttons_hw_mock, &mut state).poll(),
+            ButtonsPoll::Ready((ButtonPressType::Short, ButtonPressType::None, 3500))
+        );
+    }
+
     #[test]
     fn reactivates_both() {
         let mut state = ButtonsState::default();
@@ -198,10 +498,7 @@ mod tests {
             })),
         };
 
-        assert_eq!(
-            Buttons::new(&buttons_hw_mock, &mut state).triggered(),
-            false
-        );
+        assert!(!Buttons::new(&buttons_hw_mock, &mut state).triggered());
     }
 
     #[test]
@@ -214,7 +511,7 @@ mod tests {
             })),
         };
 
-        assert_eq!(Buttons::new(&buttons_hw_mock, &mut state).triggered(), true);
+        assert!(Buttons::new(&buttons_hw_mock, &mut state).triggered());
     }
 
     #[test]
@@ -230,7 +527,7 @@ mod tests {
             })),
         };
 
-        assert_eq!(Buttons::new(&buttons_hw_mock, &mut state).triggered(), true);
+        assert!(Buttons::new(&buttons_hw_mock, &mut state).triggered());
     }
 
     #[test]
@@ -246,7 +543,7 @@ mod tests {
             })),
         };
 
-        assert_eq!(Buttons::new(&buttons_hw_mock, &mut state).triggered(), true);
+        assert!(Buttons::new(&buttons_hw_mock, &mut state).triggered());
     }
 
     #[test]
@@ -255,13 +552,7 @@ mod tests {
         let mut state = ButtonsState::default();
         let buttons_hw_mock = ButtonsHardwareMock {
             data: RefCell::new(MockData::new(AssociatedData {
-                is_button_pressed: |_bt: ButtonType| {
-                    if *pending_time.borrow() >= 250 {
-                        false
-                    } else {
-                        true
-                    }
-                },
+                is_button_pressed: |_bt: ButtonType| *pending_time.borrow() < 250,
                 is_button_triggered: |_: ButtonType| true,
             })),
         };
@@ -279,6 +570,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn waits_for_double_click_if_bound() {
+        let pending_time = RefCell::new(0);
+        let mut state = state_with_all_gestures();
+        let buttons_hw_mock = ButtonsHardwareMock {
+            data: RefCell::new(MockData::new(AssociatedData {
+                is_button_pressed: |_bt: ButtonType| *pending_time.borrow() < 250,
+                is_button_triggered: |_: ButtonType| true,
+            })),
+        };
+
+        // Wait for the potential second click once buttons are released.
+        for time in (0..750).step_by(250) {
+            *pending_time.borrow_mut() = time;
+            assert_eq!(
+                Buttons::new(&buttons_hw_mock, &mut state).poll(),
+                ButtonsPoll::Pending(250)
+            );
+        }
+
+        *pending_time.borrow_mut() = 750;
+
+        assert_eq!(
+            Buttons::new(&buttons_hw_mock, &mut state).poll(),
+            ButtonsPoll::Ready((ButtonPressType::Short, ButtonPressType::Short, 750))
+        );
+    }
+
     #[test]
     fn both_short_even_if_one_pressed_later() {
         let pending_time = RefCell::new(0);
@@ -363,13 +682,7 @@ mod tests {
             data: RefCell::new(MockData::new(AssociatedData {
                 is_button_pressed: |bt: ButtonType| match bt {
                     ButtonType::One => false,
-                    ButtonType::Ten => {
-                        if *pending_time.borrow() >= 250 {
-                            false
-                        } else {
-                            true
-                        }
-                    }
+                    ButtonType::Ten => *pending_time.borrow() < 250,
                 },
                 is_button_triggered: |_: ButtonType| true,
             })),
@@ -395,13 +708,7 @@ mod tests {
         let buttons_hw_mock = ButtonsHardwareMock {
             data: RefCell::new(MockData::new(AssociatedData {
                 is_button_pressed: |bt: ButtonType| match bt {
-                    ButtonType::One => {
-                        if *pending_time.borrow() >= 250 {
-                            false
-                        } else {
-                            true
-                        }
-                    }
+                    ButtonType::One => *pending_time.borrow() < 250,
                     ButtonType::Ten => false,
                 },
                 is_button_triggered: |_: ButtonType| true,
@@ -421,19 +728,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn one_double_click() {
+        let pending_time = RefCell::new(0);
+        let triggered = RefCell::new(false);
+        let mut state = state_with_all_gestures();
+        let buttons_hw_mock = ButtonsHardwareMock {
+            data: RefCell::new(MockData::new(AssociatedData {
+                is_button_pressed: |bt: ButtonType| match bt {
+                    ButtonType::One => *pending_time.borrow() < 250,
+                    ButtonType::Ten => false,
+                },
+                is_button_triggered: |bt: ButtonType| match bt {
+                    ButtonType::One => *triggered.borrow(),
+                    ButtonType::Ten => false,
+                },
+            })),
+        };
+
+        for time in (0..500).step_by(250) {
+            *pending_time.borrow_mut() = time;
+            assert_eq!(
+                Buttons::new(&buttons_hw_mock, &mut state).poll(),
+                ButtonsPoll::Pending(250)
+            );
+        }
+
+        // Second click is too short to be noticed by the poll, but it triggers the button.
+        *triggered.borrow_mut() = true;
+        Buttons::new(&buttons_hw_mock, &mut state).track_triggers();
+
+        *pending_time.borrow_mut() = 500;
+
+        assert_eq!(
+            Buttons::new(&buttons_hw_mock, &mut state).poll(),
+            ButtonsPoll::Ready((ButtonPressType::DoubleClick, ButtonPressType::None, 500))
+        );
+    }
+
+    #[test]
+    fn ignores_triggers_before_release() {
+        let pending_time = RefCell::new(0);
+        let triggered = RefCell::new(false);
+        let mut state = state_with_all_gestures();
+        let buttons_hw_mock = ButtonsHardwareMock {
+            data: RefCell::new(MockData::new(AssociatedData {
+                is_button_pressed: |bt: ButtonType| match bt {
+                    ButtonType::One => *pending_time.borrow() < 500,
+                    ButtonType::Ten => false,
+                },
+                is_button_triggered: |_: ButtonType| *triggered.borrow(),
+            })),
+        };
+
+        assert_eq!(
+            Buttons::new(&buttons_hw_mock, &mut state).poll(),
+            ButtonsPoll::Pending(250)
+        );
+
+        // Contact bouncing while button is still pressed.
+        *triggered.borrow_mut() = true;
+        Buttons::new(&buttons_hw_mock, &mut state).track_triggers();
+        *triggered.borrow_mut() = false;
+
+        for time in (250..1000).step_by(250) {
+            *pending_time.borrow_mut() = time;
+            assert_eq!(
+                Buttons::new(&buttons_hw_mock, &mut state).poll(),
+                ButtonsPoll::Pending(250)
+            );
+        }
+
+        *pending_time.borrow_mut() = 1000;
+
+        assert_eq!(
+            Buttons::new(&buttons_hw_mock, &mut state).poll(),
+            ButtonsPoll::Ready((ButtonPressType::Short, ButtonPressType::None, 1000))
+        );
+    }
+
     #[test]
     fn both_long() {
         let pending_time = RefCell::new(0);
         let mut state = ButtonsState::default();
         let buttons_hw_mock = ButtonsHardwareMock {
             data: RefCell::new(MockData::new(AssociatedData {
-                is_button_pressed: |_bt: ButtonType| {
-                    if *pending_time.borrow() > 1250 {
-                        false
-                    } else {
-                        true
-                    }
-                },
+                is_button_pressed: |_bt: ButtonType| *pending_time.borrow() <= 1250,
                 is_button_triggered: |_: ButtonType| true,
             })),
         };
@@ -477,6 +857,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn both_very_long_when_infinitely_pressed() {
+        let mut state = state_with_all_gestures();
+        let buttons_hw_mock = ButtonsHardwareMock {
+            data: RefCell::new(MockData::new(AssociatedData {
+                is_button_pressed: |_bt: ButtonType| true,
+                is_button_triggered: |_: ButtonType| true,
+            })),
+        };
+
+        for _ in (0..5000).step_by(250) {
+            assert_eq!(
+                Buttons::new(&buttons_hw_mock, &mut state).poll(),
+                ButtonsPoll::Pending(250)
+            );
+        }
+
+        assert_eq!(
+            Buttons::new(&buttons_hw_mock, &mut state).poll(),
+            ButtonsPoll::Ready((ButtonPressType::VeryLong, ButtonPressType::VeryLong, 5000))
+        );
+    }
+
     #[test]
     fn both_long_even_if_one_pressed_later() {
         let pending_time = RefCell::new(0);
@@ -586,13 +989,7 @@ mod tests {
         let buttons_hw_mock = ButtonsHardwareMock {
             data: RefCell::new(MockData::new(AssociatedData {
                 is_button_pressed: |bt: ButtonType| match bt {
-                    ButtonType::One => {
-                        if *pending_time.borrow() >= 250 {
-                            false
-                        } else {
-                            true
-                        }
-                    }
+                    ButtonType::One => *pending_time.borrow() < 250,
                     ButtonType::Ten => true,
                 },
                 is_button_triggered: |_: ButtonType| true,
@@ -647,13 +1044,7 @@ mod tests {
             data: RefCell::new(MockData::new(AssociatedData {
                 is_button_pressed: |bt: ButtonType| match bt {
                     ButtonType::One => true,
-                    ButtonType::Ten => {
-                        if *pending_time.borrow() >= 250 {
-                            false
-                        } else {
-                            true
-                        }
-                    }
+                    ButtonType::Ten => *pending_time.borrow() < 250,
                 },
                 is_button_triggered: |_: ButtonType| true,
             })),
@@ -674,4 +1065,61 @@ mod tests {
             ButtonsPoll::Ready((ButtonPressType::Long, ButtonPressType::Short, 1250))
         );
     }
+    #[test]
+    fn one_long_even_if_very_long_press_is_bound() {
+        let mut state = state_with_all_gestures();
+        let buttons_hw_mock = ButtonsHardwareMock {
+            data: RefCell::new(MockData::new(AssociatedData {
+                is_button_pressed: |bt: ButtonType| match bt {
+                    ButtonType::One => true,
+                    ButtonType::Ten => false,
+                },
+                is_button_triggered: |_: ButtonType| true,
+            })),
+        };
+
+        // Only both buttons can be pressed very long, so single long press is reported right away.
+        for _ in (0..1250).step_by(250) {
+            assert_eq!(
+                Buttons::new(&buttons_hw_mock, &mut state).poll(),
+                ButtonsPoll::Pending(250)
+            );
+        }
+
+        assert_eq!(
+            Buttons::new(&buttons_hw_mock, &mut state).poll(),
+            ButtonsPoll::Ready((ButtonPressType::Long, ButtonPressType::None, 1250))
+        );
+    }
+
+    #[test]
+    fn both_long_if_one_released_before_very_long() {
+        let pending_time = RefCell::new(0);
+        let mut state = state_with_all_gestures();
+        let buttons_hw_mock = ButtonsHardwareMock {
+            data: RefCell::new(MockData::new(AssociatedData {
+                is_button_pressed: |bt: ButtonType| match bt {
+                    ButtonType::One => true,
+                    ButtonType::Ten => *pending_time.borrow() <= 1250,
+                },
+                is_button_triggered: |_: ButtonType| true,
+            })),
+        };
+
+        // Poll waits for very long press only while both buttons are pressed.
+        for time in (0..1500).step_by(250) {
+            *pending_time.borrow_mut() = time;
+            assert_eq!(
+                Buttons::new(&buttons_hw_mock, &mut state).poll(),
+                ButtonsPoll::Pending(250)
+            );
+        }
+
+        *pending_time.borrow_mut() = 1500;
+
+        assert_eq!(
+            Buttons::new(&buttons_hw_mock, &mut state).poll(),
+            ButtonsPoll::Ready((ButtonPressType::Long, ButtonPressType::Long, 1500))
+        );
+    }
 }
@@ -0,0 +1,121 @@
+use super::FlashHardware;
+use array::Array;
+use core::mem;
+
+/// Erased half-word, marks the end of the stored values.
+const EMPTY_VALUE: u16 = 0xffff;
+
+/// Describes flash page that stores a sequence of u16 values that doesn't fit into one-byte storage
+/// slots (e.g. melody tones). Values are only appended, so the whole page is erased to rewrite them.
+pub struct BlobPage {
+    pub address: usize,
+    pub size: usize,
+}
+
+impl BlobPage {
+    /// Reads stored values, as many as fit into `Array`.
+    pub fn read(&self) -> Array<u16> {
+        let mut values = Array::new();
+        for value in self
+            .value_addresses()
+            .map(|address| self.u16(address))
+            .take_while(|value| *value != EMPTY_VALUE)
+        {
+            if values.is_full() {
+                break;
+            }
+
+            values.push(value);
+        }
+
+        values
+    }
+
+    /// Writes values right after the last stored one. Flash should be in the `write` mode.
+    pub fn append<T: FlashHardware>(&self, hw: &T, values: &[u16]) -> Result<(), ()> {
+        if values.contains(&EMPTY_VALUE) {
+            return Err(());
+        }
+
+        let start_address = self
+            .value_addresses()
+            .find(|address| self.u16(*address) == EMPTY_VALUE)
+            .ok_or(())?;
+        if start_address + mem::size_of_val(values) > self.address + self.size {
+            return Err(());
+        }
+
+        for (index, value) in values.iter().enumerate() {
+            hw.write_half_word(start_address + index * mem::size_of::<u16>(), *value);
+        }
+
+        Ok(())
+    }
+
+    fn value_addresses(&self) -> impl Iterator<Item = usize> {
+        (self.address..self.address + self.size).step_by(mem::size_of::<u16>())
+    }
+
+    /// Reads u16 from the specified address.
+    fn u16(&self, address: usize) -> u16 {
+        unsafe { core::ptr::read(address as *mut u16) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::storage_page::tests::FlashMemoryMock;
+    use super::*;
+
+    // Size of the page in bytes (u8).
+    const PAGE_SIZE: usize = 16;
+
+    #[test]
+    fn correctly_appends_and_reads() {
+        let memory_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page = BlobPage {
+            address: &memory_sandbox as *const _ as usize,
+            size: PAGE_SIZE,
+        };
+        let hw = FlashMemoryMock::new();
+
+        assert_eq!(page.read().as_ref(), []);
+
+        assert_eq!(page.append(&hw, &[0xa50a, 0x0001]), Ok(()));
+        assert_eq!(page.append(&hw, &[0x1234]), Ok(()));
+        assert_eq!(page.read().as_ref(), [0xa50a, 0x0001, 0x1234]);
+        assert_eq!(
+            memory_sandbox,
+            [0xa50a, 0x0001, 0x1234, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff]
+        );
+    }
+
+    #[test]
+    fn fails_to_append_when_page_is_full() {
+        let memory_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page = BlobPage {
+            address: &memory_sandbox as *const _ as usize,
+            size: PAGE_SIZE,
+        };
+        let hw = FlashMemoryMock::new();
+
+        assert_eq!(page.append(&hw, &[1, 2, 3, 4, 5]), Ok(()));
+        assert_eq!(page.append(&hw, &[6, 7, 8, 9]), Err(()));
+        assert_eq!(page.append(&hw, &[6, 7, 8]), Ok(()));
+        assert_eq!(page.append(&hw, &[9]), Err(()));
+        assert_eq!(page.read().as_ref(), [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn fails_to_append_empty_value() {
+        let memory_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page = BlobPage {
+            address: &memory_sandbox as *const _ as usize,
+            size: PAGE_SIZE,
+        };
+        let hw = FlashMemoryMock::new();
+
+        assert_eq!(page.append(&hw, &[1, 0xffff]), Err(()));
+        assert_eq!(page.read().as_ref(), []);
+    }
+}
@@ -1,15 +1,26 @@
+mod blob_page;
 pub mod storage;
+pub mod storage_key;
 mod storage_page;
 mod storage_page_status;
 pub mod storage_slot;
+pub mod storage_stats;
+pub mod storage_value;
 
-use self::{storage::Storage, storage_page::StoragePage, storage_slot::StorageSlot};
+use self::{
+    blob_page::BlobPage, storage::Storage, storage_page::StoragePage, storage_slot::StorageSlot,
+    storage_stats::StorageStats, storage_value::StorageValue,
+};
+use array::Array;
 
 /// Describes the Flash hardware management interface.
 pub trait FlashHardware {
     /// Returns addresses of the flash memory pages.
     fn page_addresses(&self) -> [usize; 2];
 
+    /// Returns address of the flash memory page used to store blobs (e.g. custom melody).
+    fn blob_page_address(&self) -> usize;
+
     /// Erases page using specified address.
     fn erase_page(&self, page_address: usize);
 
@@ -18,11 +29,17 @@ pub trait FlashHardware {
 
     /// Makes peripheral to exit `write` mode.
     fn disable_write_mode(&self);
+
+    /// Programs half-word at the specified address, peripheral should be in `write` mode.
+    fn write_half_word(&self, address: usize, value: u16) {
+        unsafe { core::ptr::write(address as *mut u16, value) }
+    }
 }
 
 pub struct Flash<'a, T: FlashHardware> {
     hw: &'a T,
     storage: Storage,
+    blob: BlobPage,
 }
 
 impl<'a, T: FlashHardware> Flash<'a, T> {
@@ -42,44 +59,97 @@ impl<'a, T: FlashHardware> Flash<'a, T> {
                     },
                 ],
             },
+            blob: BlobPage {
+                address: hw.blob_page_address(),
+                size: 1024,
+            },
         }
     }
 
+    /// Prepares storage for use: resumes interrupted rollover and converts values written by the
+    /// older firmware. Should be called once when device starts.
+    #[allow(clippy::result_unit_err)]
+    pub fn setup(&self) -> Result<(), ()> {
+        self.storage.setup(self.hw)
+    }
+
     /// Reads a value from a specific memory slot.
     pub fn read(&self, slot: StorageSlot) -> Option<u8> {
         self.storage.read(slot)
     }
 
     /// Writes a value to a specific memory slot.
+    #[allow(clippy::result_unit_err)]
     pub fn write(&self, slot: StorageSlot, value: u8) -> Result<(), ()> {
-        self.hw.enable_write_mode();
+        self.write_bytes(slot.into(), &[value])
+    }
 
-        let result = self.storage.write(slot, value).or_else(|err| {
-            self.hw.disable_write_mode();
+    /// Reads a value of the specified type stored with the specified 8- or 16-bit key. Returns
+    /// `None` if value isn't stored or can't be converted to the specified type.
+    pub fn read_value<V: StorageValue>(&self, key: u16) -> Option<V> {
+        self.read_bytes(key)
+            .and_then(|value| V::from_bytes(value.as_ref()))
+    }
 
-            self.hw.erase_page(err.next_page.address);
-            self.storage.rollover()?;
+    /// Writes a value of the specified type with the specified 8- or 16-bit key.
+    #[allow(clippy::result_unit_err)]
+    pub fn write_value<V: StorageValue>(&self, key: u16, value: V) -> Result<(), ()> {
+        self.write_bytes(key, value.to_bytes().as_ref())
+    }
 
-            self.hw.enable_write_mode();
+    /// Reads raw bytes stored with the specified key.
+    pub fn read_bytes(&self, key: u16) -> Option<Array<u8>> {
+        self.storage.read_bytes(key)
+    }
 
-            self.storage.write(slot, value).map_err(|_| {})
-        });
+    /// Writes raw bytes (up to `MAX_VALUE_SIZE`) with the specified key. If the active storage page
+    /// is full, latest versions of all values are rolled over to the next page first.
+    #[allow(clippy::result_unit_err)]
+    pub fn write_bytes(&self, key: u16, value: &[u8]) -> Result<(), ()> {
+        self.storage.write_bytes(self.hw, key, value)
+    }
 
+    /// Returns wear statistics of the storage pages and fill level of the active one.
+    #[allow(clippy::result_unit_err)]
+    pub fn storage_stats(&self) -> Result<StorageStats, ()> {
+        self.storage.stats()
+    }
+
+    /// Reads values stored in the blob page.
+    pub fn read_blob(&self) -> Array<u16> {
+        self.blob.read()
+    }
+
+    /// Appends values to the ones stored in the blob page.
+    #[allow(clippy::result_unit_err)]
+    pub fn append_blob(&self, values: &[u16]) -> Result<(), ()> {
+        self.hw.enable_write_mode();
+        let result = self.blob.append(self.hw, values);
         self.hw.disable_write_mode();
 
         result
     }
 
-    /// Erases all storage pages.
-    pub fn erase_all(&self) {
+    /// Erases all values stored in the blob page.
+    pub fn erase_blob(&self) {
+        self.hw.erase_page(self.blob.address);
+    }
+
+    /// Erases all storage and blob pages, storage is set up again right away.
+    #[allow(clippy::result_unit_err)]
+    pub fn erase_all(&self) -> Result<(), ()> {
         for page in self.storage.pages.iter() {
             self.hw.erase_page(page.address);
         }
+
+        self.erase_blob();
+        self.setup()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::storage_page::{INVALID_KEY, MAX_VALUE_SIZE};
     use super::*;
     use crate::tests::MockData;
     use core::cell::RefCell;
@@ -97,6 +167,7 @@ mod tests {
     struct FlashHardwareMock<'a> {
         data: RefCell<MockData<'a, Call>>,
         page_addresses: [usize; 2],
+        blob_page_address: usize,
     }
 
     impl<'a> FlashHardware for FlashHardwareMock<'a> {
@@ -104,6 +175,10 @@ mod tests {
             self.page_addresses
         }
 
+        fn blob_page_address(&self) -> usize {
+            self.blob_page_address
+        }
+
         fn erase_page(&self, page_address: usize) {
             self.data
                 .borrow_mut()
@@ -127,44 +202,68 @@ mod tests {
     fn read() {
         let mut page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
         let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let blob_page: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
 
         let flash_hw_mock = FlashHardwareMock {
             data: RefCell::new(MockData::<Call, ()>::without_data()),
             page_addresses: [&page1 as *const _ as usize, &page2 as *const _ as usize],
+            blob_page_address: &blob_page as *const _ as usize,
         };
         let flash = Flash::new(&flash_hw_mock);
 
+        // Storage isn't touched until it's set up.
+        assert_eq!(flash.read(StorageSlot::Configuration), None);
+        assert_eq!(page1[0], 0xffff);
+
+        assert_eq!(flash.setup(), Ok(()));
         assert_eq!(flash.read(StorageSlot::Configuration), None);
         assert_eq!(flash.read(StorageSlot::Custom(2)), None);
+        assert_eq!(page1[0], 0x0fff);
 
-        page1[2] = 0xaf0f;
+        page1[4..7].copy_from_slice(&[0x0137, 0x00af, 0x0fff]);
 
         assert_eq!(flash.read(StorageSlot::Configuration), Some(0x0f));
         assert_eq!(flash.read(StorageSlot::Custom(2)), None);
 
-        page1[3] = 0x2f01;
+        page1[7..10].copy_from_slice(&[0x0116, 0x002f, 0x01ff]);
 
         assert_eq!(flash.read(StorageSlot::Configuration), Some(0x0f));
         assert_eq!(flash.read(StorageSlot::Custom(2)), Some(0x01));
+
+        // Record that doesn't match its CRC is ignored.
+        page1[9..10].copy_from_slice(&[0x00ff]);
+        assert_eq!(flash.read(StorageSlot::Custom(2)), None);
     }
 
     #[test]
     fn write_when_page_has_enough_space() {
         let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
         let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let blob_page: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
 
         let flash_hw_mock = FlashHardwareMock {
             data: RefCell::new(MockData::<Call, ()>::without_data()),
             page_addresses: [&page1 as *const _ as usize, &page2 as *const _ as usize],
+            blob_page_address: &blob_page as *const _ as usize,
         };
 
         let flash = Flash::new(&flash_hw_mock);
-        assert_eq!(flash.write(StorageSlot::Configuration, 10).is_ok(), true);
+        assert_eq!(flash.setup(), Ok(()));
+        assert!(flash.write(StorageSlot::Configuration, 10).is_ok());
 
-        assert_eq!(page1[..4], [0x0fff, 0xffff, 0xaf0a, 0xffff]);
+        assert_eq!(
+            page1[..8],
+            [0x0fff, 0x4b56, 0x0000, 0x0000, 0x012c, 0x00af, 0x0aff, 0xffff]
+        );
         assert_eq!(
             flash.hw.data.borrow().calls.logs(),
-            [Some(Call::EnableWriteMode), Some(Call::DisableWriteMode)]
+            [
+                // Storage is set up for the first time, so the first page is marked as active.
+                Some(Call::EnableWriteMode),
+                Some(Call::DisableWriteMode),
+                Some(Call::EnableWriteMode),
+                Some(Call::DisableWriteMode)
+            ]
         );
     }
 
@@ -172,24 +271,33 @@ mod tests {
     fn write_when_page_is_full() {
         let mut page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
         let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let blob_page: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
 
         let flash_hw_mock = FlashHardwareMock {
             data: RefCell::new(MockData::<Call, ()>::without_data()),
             page_addresses: [&page1 as *const _ as usize, &page2 as *const _ as usize],
+            blob_page_address: &blob_page as *const _ as usize,
         };
 
         let flash = Flash::new(&flash_hw_mock);
 
         // Imitate fully populated page.
-        page1[0] = 0x0fff;
-        page1[1] = 0x0001;
-        for i in 2..(PAGE_SIZE / 2) {
-            page1[i] = 0xaf12;
+        page1[..4].copy_from_slice(&[0x0fff, 0x4b56, 0x0000, 0x0000]);
+        for record in page1[4..511].chunks_mut(3) {
+            record.copy_from_slice(&[0x0164, 0x00af, 0x12ff]);
         }
+        assert_eq!(flash.setup(), Ok(()));
 
-        assert_eq!(flash.write(StorageSlot::Custom(2), 0x17).is_ok(), true);
+        assert!(flash.write(StorageSlot::Custom(2), 0x17).is_ok());
 
-        assert_eq!(page2[..5], [0x0fff, 0xffff, 0xaf12, 0x2f17, 0xffff]);
+        assert_eq!(page1[0], 0x00ff);
+        assert_eq!(
+            page2[..11],
+            [
+                0x0fff, 0x4b56, 0x0001, 0x0001, 0x0164, 0x00af, 0x12ff, 0x0174, 0x002f, 0x17ff,
+                0xffff
+            ]
+        );
         assert_eq!(
             flash.hw.data.borrow().calls.logs(),
             [
@@ -197,30 +305,124 @@ mod tests {
                 Some(Call::DisableWriteMode),
                 Some(Call::ErasePage(&page2 as *const _ as usize)),
                 Some(Call::EnableWriteMode),
+                Some(Call::DisableWriteMode),
+                Some(Call::EnableWriteMode),
                 Some(Call::DisableWriteMode)
             ]
         );
     }
 
+    #[test]
+    fn read_and_write_typed_values() {
+        let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let blob_page: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+
+        let flash_hw_mock = FlashHardwareMock {
+            data: RefCell::new(MockData::<Call, ()>::without_data()),
+            page_addresses: [&page1 as *const _ as usize, &page2 as *const _ as usize],
+            blob_page_address: &blob_page as *const _ as usize,
+        };
+
+        let flash = Flash::new(&flash_hw_mock);
+        assert_eq!(flash.setup(), Ok(()));
+
+        assert_eq!(flash.read_value::<u32>(0x1001), None);
+        assert_eq!(flash.write_value(0x1001, 0x1234_5678u32), Ok(()));
+        assert_eq!(flash.write_value(0x1002, true), Ok(()));
+        assert_eq!(
+            flash.write_value(StorageSlot::Custom(1).into(), 0x0102u16),
+            Ok(())
+        );
+        assert_eq!(flash.write(StorageSlot::Custom(2), 3), Ok(()));
+
+        assert_eq!(flash.read_value::<u32>(0x1001), Some(0x1234_5678));
+        assert_eq!(flash.read_value::<bool>(0x1002), Some(true));
+        assert_eq!(
+            flash.read_value::<u16>(StorageSlot::Custom(1).into()),
+            Some(0x0102)
+        );
+        assert_eq!(
+            flash.read_value::<u8>(StorageSlot::Custom(2).into()),
+            Some(3)
+        );
+        assert_eq!(flash.read(StorageSlot::Custom(2)), Some(3));
+
+        // Value of the different type isn't read.
+        assert_eq!(flash.read_value::<u16>(0x1001), None);
+        assert_eq!(flash.read(StorageSlot::Custom(1)), None);
+
+        assert_eq!(
+            page1[..17],
+            [
+                0x0fff, 0x4b56, 0x0000, 0x0000, 0x047d, 0x1001, 0x7856, 0x3412, 0x01a3, 0x1002,
+                0x01ff, 0x024e, 0x001f, 0x0201, 0x0118, 0x002f, 0x03ff
+            ]
+        );
+
+        // Invalid key and too large value are rejected without touching the flash.
+        assert_eq!(flash.write_value(INVALID_KEY, 1u8), Err(()));
+        assert_eq!(flash.write_bytes(0x1003, &[0; MAX_VALUE_SIZE + 1]), Err(()));
+        assert_eq!(flash.hw.data.borrow().calls.logs().len(), 10);
+    }
+
     #[test]
     fn erase_all() {
         let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
         let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let blob_page: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
 
         let flash_hw_mock = FlashHardwareMock {
             data: RefCell::new(MockData::<Call, ()>::without_data()),
             page_addresses: [&page1 as *const _ as usize, &page2 as *const _ as usize],
+            blob_page_address: &blob_page as *const _ as usize,
         };
 
         let flash = Flash::new(&flash_hw_mock);
 
-        flash.erase_all();
+        assert_eq!(flash.erase_all(), Ok(()));
 
         assert_eq!(
             flash.hw.data.borrow().calls.logs(),
             [
                 Some(Call::ErasePage(&page1 as *const _ as usize)),
-                Some(Call::ErasePage(&page2 as *const _ as usize))
+                Some(Call::ErasePage(&page2 as *const _ as usize)),
+                Some(Call::ErasePage(&blob_page as *const _ as usize)),
+                // Storage is set up again, so the first page is marked as active.
+                Some(Call::EnableWriteMode),
+                Some(Call::DisableWriteMode)
+            ]
+        );
+        assert_eq!(page1[0], 0x0fff);
+    }
+
+    #[test]
+    fn append_and_read_blob() {
+        let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let blob_page: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+
+        let flash_hw_mock = FlashHardwareMock {
+            data: RefCell::new(MockData::<Call, ()>::without_data()),
+            page_addresses: [&page1 as *const _ as usize, &page2 as *const _ as usize],
+            blob_page_address: &blob_page as *const _ as usize,
+        };
+
+        let flash = Flash::new(&flash_hw_mock);
+
+        assert_eq!(flash.read_blob().as_ref(), []);
+        assert!(flash.append_blob(&[0xa564, 0x0032]).is_ok());
+        assert_eq!(flash.read_blob().as_ref(), [0xa564, 0x0032]);
+        assert_eq!(page1[..2], [0xffff, 0xffff]);
+
+        flash.erase_blob();
+
+        assert_eq!(
+            flash.hw.data.borrow().calls.logs(),
+            [
+                Some(Call::EnableWriteMode),
+                Some(Call::DisableWriteMode),
+                Some(Call::ErasePage(&blob_page as *const _ as usize))
             ]
         );
     }
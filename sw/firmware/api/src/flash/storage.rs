@@ -1,15 +1,19 @@
 use super::{
-    storage_page::{StoragePage, StoragePageFullError},
+    storage_page::{StoragePage, INVALID_KEY, MAX_VALUE_SIZE},
     storage_page_status::StoragePageStatus,
     storage_slot::StorageSlot,
+    storage_stats::StorageStats,
+    FlashHardware,
 };
+use array::Array;
 
 /// Max number of pages used by storage.
 const PAGES_COUNT: usize = 2;
 
 /// Describes multi-page storage that simulates EEPROM on top of flash. This is very naive and simple
-/// implementation that allows device to store up to 5 different 8-bit values in so called slots.
-/// That's pretty much enough for the configuration options device may need.
+/// implementation that allows device to store values of up to `MAX_VALUE_SIZE` bytes identified by
+/// 8- or 16-bit keys. Storage survives power loss at any moment: values are protected with CRC and
+/// rollover that is interrupted is resumed by `setup` the next time device starts.
 #[doc = r"Flash EEPROM emulation storage"]
 #[derive(Debug)]
 pub struct Storage {
@@ -17,99 +21,227 @@ pub struct Storage {
 }
 
 impl Storage {
+    /// Prepares storage for use, should be called once when device starts. If it doesn't find an
+    /// active page, it either resumes rollover that was interrupted by power loss or, if storage
+    /// hasn't been used yet, marks the first page as an active one. Active page written by the
+    /// older firmware is rolled over right away to convert its values to the current format.
+    #[allow(clippy::result_unit_err)]
+    pub fn setup<T: FlashHardware>(&self, hw: &T) -> Result<(), ()> {
+        let page_with_status =
+            |status: StoragePageStatus| self.pages.iter().find(|page| page.status() == status);
+
+        if let Some(active_page) = page_with_status(StoragePageStatus::Active) {
+            if !active_page.is_legacy() {
+                return Ok(());
+            }
+
+            return self.transfer(hw, active_page, self.next_page(active_page));
+        }
+
+        // Page that is full or that was being marked as full when power was lost keeps the latest
+        // values, while the page that was receiving them can't be trusted.
+        let source_page = page_with_status(StoragePageStatus::Full).or_else(|| {
+            page_with_status(StoragePageStatus::Receiving).map(|page| self.next_page(page))
+        });
+        if let Some(source_page) = source_page {
+            return self.transfer(hw, source_page, self.next_page(source_page));
+        }
+
+        let first_page = &self.pages[0];
+        let is_erased = first_page.is_erased();
+        if !is_erased {
+            hw.erase_page(first_page.address);
+        }
+
+        hw.enable_write_mode();
+        first_page.set_counters(hw, if is_erased { 0 } else { 1 }, 0);
+        first_page.set_status(hw, StoragePageStatus::Active);
+        hw.disable_write_mode();
+
+        Ok(())
+    }
+
     /// Reads value located in the specified virtual memory slot.
     pub fn read(&self, slot: StorageSlot) -> Option<u8> {
-        self.active_page().read(slot.into())
+        self.read_bytes(slot.into())
+            .filter(|value| value.len() == 1)
+            .map(|value| value[0])
     }
 
-    /// Writes value into specified virtual memory slot. If write fails the error includes references
-    /// to the current page and the one that should be used next to allow consumer to prepare next
-    /// page if needed and switch to rolling over all existing values from the current page.
-    pub fn write(&self, slot: StorageSlot, value: u8) -> Result<(), StoragePageFullError> {
-        let active_page = self.active_page();
-        if active_page.write(slot.into(), value).is_err() {
-            Err(StoragePageFullError {
-                active_page,
-                next_page: self.next_page(),
-            })
-        } else {
-            Ok(())
+    /// Writes value into specified virtual memory slot, see `write_bytes`.
+    #[allow(clippy::result_unit_err)]
+    pub fn write<T: FlashHardware>(&self, hw: &T, slot: StorageSlot, value: u8) -> Result<(), ()> {
+        self.write_bytes(hw, slot.into(), &[value])
+    }
+
+    /// Reads value stored with the specified key.
+    pub fn read_bytes(&self, key: u16) -> Option<Array<u8>> {
+        self.active_page().ok()?.read(key)
+    }
+
+    /// Writes value (up to `MAX_VALUE_SIZE` bytes) with the specified key. If active page doesn't
+    /// have enough space, latest versions of all values are rolled over to the next page first.
+    #[allow(clippy::result_unit_err)]
+    pub fn write_bytes<T: FlashHardware>(&self, hw: &T, key: u16, value: &[u8]) -> Result<(), ()> {
+        if key == INVALID_KEY || value.len() > MAX_VALUE_SIZE {
+            return Err(());
         }
+
+        let write = |page: &StoragePage| {
+            hw.enable_write_mode();
+            let result = page.write(hw, key, value);
+            hw.disable_write_mode();
+
+            result
+        };
+
+        let active_page = self.active_page()?;
+        write(active_page).or_else(|_| write(self.rollover(hw)?))
     }
 
-    /// Marks active page as full and switches to the next page rolling over latest version of all
-    /// existing values to the new page.
-    pub fn rollover(&self) -> Result<(), ()> {
-        let active_page = self.active_page();
-        let next_page = self.next_page();
+    /// Switches to the next page rolling over latest version of all existing values to it, returns
+    /// the page that becomes active.
+    #[allow(clippy::result_unit_err)]
+    pub fn rollover<T: FlashHardware>(&self, hw: &T) -> Result<&StoragePage, ()> {
+        let active_page = self.active_page()?;
+        let next_page = self.next_page(active_page);
+        self.transfer(hw, active_page, next_page)?;
+
+        Ok(next_page)
+    }
 
-        active_page.set_status(StoragePageStatus::Full);
-        next_page.set_status(StoragePageStatus::Active);
+    /// Returns wear statistics of the storage pages and fill level of the active page.
+    #[allow(clippy::result_unit_err)]
+    pub fn stats(&self) -> Result<StorageStats, ()> {
+        let active_page = self.active_page()?;
+        let mut erase_counts = [0; PAGES_COUNT];
+        for (erase_count, page) in erase_counts.iter_mut().zip(self.pages.iter()) {
+            *erase_count = page.erase_count().unwrap_or_default();
+        }
 
-        active_page.flush_to(next_page)
+        Ok(StorageStats {
+            erase_counts,
+            rollover_count: active_page.rollover_count().unwrap_or_default(),
+            active_page: self
+                .pages
+                .iter()
+                .position(|page| page == active_page)
+                .unwrap_or_default() as u8,
+            used_size: active_page.used_size() as u16,
+            page_size: active_page.size as u16,
+        })
     }
 
-    /// Returns currently active page. If it doesn't find an active page it marks first one as an
-    /// active page.
-    fn active_page(&self) -> &StoragePage {
-        let active_page_index = self
-            .pages
+    /// Returns currently active page, fails if storage hasn't been set up.
+    fn active_page(&self) -> Result<&StoragePage, ()> {
+        self.pages
             .iter()
-            .position(|page| page.status() == StoragePageStatus::Active);
-        if let Some(page_index) = active_page_index {
-            &self.pages[page_index]
-        } else {
-            self.pages[0].set_status(StoragePageStatus::Active);
-            &self.pages[0]
-        }
+            .find(|page| page.status() == StoragePageStatus::Active)
+            .ok_or(())
+    }
+
+    /// Copies latest version of all values from the source page to the target page. Page statuses
+    /// are updated in the order that allows to resume transfer if it's interrupted at any moment:
+    /// target page is erased and marked as receiving, then values are copied, then source page is
+    /// marked as full and only then target page is marked as active. Target page inherits rollover
+    /// counter of the source page incremented by one. If erase counter of the target page was lost
+    /// (power loss right after erase), it's estimated using counter of the source page since pages
+    /// are erased in turns.
+    fn transfer<T: FlashHardware>(
+        &self,
+        hw: &T,
+        source_page: &StoragePage,
+        target_page: &StoragePage,
+    ) -> Result<(), ()> {
+        let erase_count = target_page
+            .erase_count()
+            .or_else(|| source_page.erase_count())
+            .unwrap_or_default()
+            .saturating_add(1);
+        let rollover_count = source_page
+            .rollover_count()
+            .unwrap_or_default()
+            .saturating_add(1);
+
+        hw.erase_page(target_page.address);
+
+        hw.enable_write_mode();
+
+        target_page.set_counters(hw, erase_count, rollover_count);
+        target_page.set_status(hw, StoragePageStatus::Receiving);
+        let result = source_page.flush_to(hw, target_page).map(|_| {
+            if source_page.status() != StoragePageStatus::Full {
+                source_page.set_status(hw, StoragePageStatus::Full);
+            }
+
+            target_page.set_status(hw, StoragePageStatus::Active);
+        });
+
+        hw.disable_write_mode();
+
+        result
     }
 
-    /// Returns the page reference that will be used once the current page is full.
-    fn next_page(&self) -> &StoragePage {
-        let active_page = self.active_page();
-        let active_page_index = self
+    /// Returns the page reference that will be used once the specified page is full.
+    fn next_page(&self, page: &StoragePage) -> &StoragePage {
+        let page_index = self
             .pages
             .iter()
-            .position(|page| page == active_page)
+            .position(|p| p == page)
             .unwrap_or_default();
 
-        &self.pages[if active_page_index + 1 == self.pages.len() {
+        &self.pages[if page_index + 1 == self.pages.len() {
             0
         } else {
-            active_page_index + 1
+            page_index + 1
         }]
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::storage_page::tests::FlashMemoryMock;
     use super::*;
 
     // Size of the page in bytes (u8).
     const PAGE_SIZE: usize = 1024;
 
-    #[test]
-    fn correctly_initializes() {
-        let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
-        let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
-
-        let storage = Storage {
+    fn create_storage(page1: &[u16], page2: &[u16]) -> Storage {
+        Storage {
             pages: [
                 StoragePage {
-                    address: &page1 as *const _ as usize,
+                    address: page1.as_ptr() as usize,
                     size: PAGE_SIZE,
                 },
                 StoragePage {
-                    address: &page2 as *const _ as usize,
+                    address: page2.as_ptr() as usize,
                     size: PAGE_SIZE,
                 },
             ],
-        };
+        }
+    }
+
+    #[test]
+    fn correctly_initializes() {
+        let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+
+        let storage = create_storage(&page1, &page2);
+        let hw = FlashMemoryMock::new();
+
+        // Storage isn't usable and isn't touched until it's set up.
+        assert_eq!(storage.read(StorageSlot::Configuration), None);
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 1), Err(()));
+        assert!(storage.stats().is_err());
+        assert_eq!(page1[0], 0xffff);
+
+        assert_eq!(storage.setup(&hw), Ok(()));
+        assert_eq!(storage.read(StorageSlot::Configuration), None);
 
         let page1_slice = &page1[..6];
         assert_eq!(
             page1_slice,
-            [0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff]
+            [0x0fff, 0x4b56, 0x0000, 0x0000, 0xffff, 0xffff]
         );
 
         let page2_slice = &page2[..6];
@@ -117,8 +249,25 @@ mod tests {
             page2_slice,
             [0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff]
         );
+    }
+
+    #[test]
+    fn erases_page_with_corrupted_content_on_first_use() {
+        let page1: [u16; PAGE_SIZE / 2] = [0x1234; PAGE_SIZE / 2];
+        let page2: [u16; PAGE_SIZE / 2] = [0x5678; PAGE_SIZE / 2];
+
+        let storage = create_storage(&page1, &page2);
+        let hw = FlashMemoryMock::new();
+        assert_eq!(storage.setup(&hw), Ok(()));
 
         assert_eq!(storage.read(StorageSlot::Configuration), None);
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 2), Ok(()));
+        assert_eq!(storage.read(StorageSlot::Configuration), Some(2));
+        assert_eq!(
+            page1[..8],
+            [0x0fff, 0x4b56, 0x0001, 0x0000, 0x0114, 0x00af, 0x02ff, 0xffff]
+        );
+        assert_eq!(page2[..2], [0x5678, 0x5678]);
     }
 
     #[test]
@@ -126,211 +275,345 @@ mod tests {
         let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
         let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
 
-        let storage = Storage {
-            pages: [
-                StoragePage {
-                    address: &page1 as *const _ as usize,
-                    size: PAGE_SIZE,
-                },
-                StoragePage {
-                    address: &page2 as *const _ as usize,
-                    size: PAGE_SIZE,
-                },
-            ],
-        };
+        let storage = create_storage(&page1, &page2);
+        let hw = FlashMemoryMock::new();
+        assert_eq!(storage.setup(&hw), Ok(()));
 
-        let page1_slice = &page1[..6];
+        let page1_slice = &page1[..13];
         let page2_slice = &page2[..6];
 
         assert_eq!(storage.read(StorageSlot::Configuration), None);
-        assert_eq!(storage.write(StorageSlot::Configuration, 2).is_ok(), true);
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 2), Ok(()));
         assert_eq!(storage.read(StorageSlot::Configuration), Some(2));
+
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 3), Ok(()));
+        assert_eq!(storage.read(StorageSlot::Configuration), Some(3));
+
+        assert_eq!(storage.write(&hw, StorageSlot::Custom(2), 4), Ok(()));
+        assert_eq!(storage.read(StorageSlot::Configuration), Some(3));
+        assert_eq!(storage.read(StorageSlot::Custom(2)), Some(4));
         assert_eq!(
             page1_slice,
-            [0x0fff, 0xffff, 0xaf02, 0xffff, 0xffff, 0xffff]
+            [
+                0x0fff, 0x4b56, 0x0000, 0x0000, 0x0114, 0x00af, 0x02ff, 0x0113, 0x00af, 0x03ff,
+                0x010d, 0x002f, 0x04ff
+            ]
         );
         assert_eq!(
             page2_slice,
             [0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff]
         );
+    }
 
-        assert_eq!(storage.write(StorageSlot::Configuration, 3).is_ok(), true);
-        assert_eq!(storage.read(StorageSlot::Configuration), Some(3));
+    #[test]
+    fn fails_to_write_invalid_values() {
+        let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+
+        let storage = create_storage(&page1, &page2);
+        let hw = FlashMemoryMock::new();
+        assert_eq!(storage.setup(&hw), Ok(()));
+
+        assert_eq!(storage.write_bytes(&hw, INVALID_KEY, &[1]), Err(()));
         assert_eq!(
-            page1_slice,
-            [0x0fff, 0xffff, 0xaf02, 0xaf03, 0xffff, 0xffff]
+            storage.write_bytes(&hw, 0x1234, &[1; MAX_VALUE_SIZE + 1]),
+            Err(())
         );
         assert_eq!(
-            page2_slice,
-            [0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff]
+            storage.write_bytes(&hw, 0x1234, &[1; MAX_VALUE_SIZE]),
+            Ok(())
         );
 
-        assert_eq!(storage.write(StorageSlot::Custom(2), 4).is_ok(), true);
-        assert_eq!(storage.read(StorageSlot::Configuration), Some(3));
-        assert_eq!(storage.read(StorageSlot::Custom(2)), Some(4));
+        // Slot value can't be read as `u8` if it isn't one byte long.
         assert_eq!(
-            page1_slice,
-            [0x0fff, 0xffff, 0xaf02, 0xaf03, 0x2f04, 0xffff]
-        );
-        assert_eq!(
-            page2_slice,
-            [0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff]
+            storage.write_bytes(&hw, StorageSlot::Custom(1).into(), &[7, 8]),
+            Ok(())
         );
+        assert_eq!(storage.read(StorageSlot::Custom(1)), None);
+
+        // Invalid values don't cause rollover.
+        assert_eq!(page1[0], 0x0fff);
+        assert_eq!(page2[0], 0xffff);
     }
 
     #[test]
-    fn fails_when_page_is_full() {
+    fn successfully_rolls_over_to_next_page() {
         let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
         let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
 
-        let storage = Storage {
-            pages: [
-                StoragePage {
-                    address: &page1 as *const _ as usize,
-                    size: PAGE_SIZE,
-                },
-                StoragePage {
-                    address: &page2 as *const _ as usize,
-                    size: PAGE_SIZE,
-                },
-            ],
-        };
+        let storage = create_storage(&page1, &page2);
+        let hw = FlashMemoryMock::new();
+        assert_eq!(storage.setup(&hw), Ok(()));
 
-        // Fill all memory slots.
-        for _ in 0..510 {
-            assert_eq!(storage.write(StorageSlot::Configuration, 1).is_ok(), true);
+        // Fill all memory.
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 1), Ok(()));
+        assert_eq!(storage.write(&hw, StorageSlot::Custom(2), 2), Ok(()));
+
+        for _ in 0..165 {
+            assert_eq!(storage.write(&hw, StorageSlot::Custom(2), 3), Ok(()));
         }
 
-        // Now we can't write anymore
-        assert_eq!(storage.write(StorageSlot::Configuration, 1).is_err(), true);
-        assert_eq!(&page1[510..], [0xaf01, 0xaf01])
+        assert_eq!(storage.write(&hw, StorageSlot::Custom(3), 4), Ok(()));
+        assert_eq!(storage.write(&hw, StorageSlot::Custom(5), 15), Ok(()));
+
+        // Remaining space is too small for any record with a value.
+        let page1_slice = &page1[505..];
+        assert_eq!(
+            page1_slice,
+            [0x01af, 0x003f, 0x04ff, 0x015b, 0x005f, 0x0fff, 0xffff]
+        );
+
+        let page2_slice = &page2[..20];
+        assert_eq!(page2_slice, [0xffff; 20]);
+
+        // Move to next page.
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 5), Ok(()));
+
+        assert_eq!(page1[0], 0x00ff);
+        assert_eq!(
+            page1_slice,
+            [0x01af, 0x003f, 0x04ff, 0x015b, 0x005f, 0x0fff, 0xffff]
+        );
+        assert_eq!(
+            page2_slice,
+            [
+                0x0fff, 0x4b56, 0x0001, 0x0001, 0x011d, 0x00af, 0x01ff, 0x0118, 0x002f, 0x03ff,
+                0x01af, 0x003f, 0x04ff, 0x015b, 0x005f, 0x0fff, 0x0101, 0x00af, 0x05ff, 0xffff
+            ]
+        );
+
+        assert_eq!(storage.read(StorageSlot::Configuration), Some(5));
+        assert_eq!(storage.read(StorageSlot::Custom(2)), Some(3));
+        assert_eq!(storage.read(StorageSlot::Custom(3)), Some(4));
+        assert_eq!(storage.read(StorageSlot::Custom(5)), Some(15));
     }
 
     #[test]
-    fn successfully_rolls_over_to_next_page() {
+    fn successfully_rolls_over_to_first_page() {
         let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
         let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
 
-        let storage = Storage {
-            pages: [
-                StoragePage {
-                    address: &page1 as *const _ as usize,
-                    size: PAGE_SIZE,
-                },
-                StoragePage {
-                    address: &page2 as *const _ as usize,
-                    size: PAGE_SIZE,
-                },
-            ],
-        };
+        let storage = create_storage(&page1, &page2);
+        let hw = FlashMemoryMock::new();
+        assert_eq!(storage.setup(&hw), Ok(()));
 
-        // Fill all memory slots, but the latest one.
-        assert_eq!(storage.write(StorageSlot::Configuration, 1).is_ok(), true);
-        assert_eq!(storage.write(StorageSlot::Custom(2), 2).is_ok(), true);
+        // Fill first page and move to the next one.
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 1), Ok(()));
+        assert_eq!(storage.write(&hw, StorageSlot::Custom(2), 2), Ok(()));
 
-        for _ in 0..506 {
-            assert_eq!(storage.write(StorageSlot::Custom(2), 3).is_ok(), true);
+        for _ in 0..165 {
+            assert_eq!(storage.write(&hw, StorageSlot::Custom(2), 3), Ok(()));
         }
 
-        // Fill remaining slots.
-        assert_eq!(storage.write(StorageSlot::Custom(3), 4).is_ok(), true);
-        assert_eq!(storage.write(StorageSlot::Custom(5), 15).is_ok(), true);
+        assert_eq!(storage.write(&hw, StorageSlot::Custom(3), 4), Ok(()));
+        assert_eq!(storage.write(&hw, StorageSlot::Custom(5), 15), Ok(()));
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 5), Ok(()));
 
-        // Now we can't write anymore
-        assert_eq!(storage.write(StorageSlot::Configuration, 1).is_err(), true);
+        // Fill next page.
+        for _ in 0..164 {
+            assert_eq!(storage.write(&hw, StorageSlot::Custom(2), 30), Ok(()));
+        }
 
-        let page1_slice = &page1[508..];
-        assert_eq!(page1_slice, [0x2f03, 0x2f03, 0x3f04, 0x5f0f]);
+        assert_eq!(page1[0], 0x00ff);
+        assert_eq!(page2[0], 0x0fff);
 
-        // Move to next page.
-        let page2_slice = &page2[..8];
+        // Move back to the first page.
+        assert_eq!(storage.write(&hw, StorageSlot::Custom(3), 40), Ok(()));
+
+        assert_eq!(storage.read(StorageSlot::Configuration), Some(5));
+        assert_eq!(storage.read(StorageSlot::Custom(2)), Some(30));
+        assert_eq!(storage.read(StorageSlot::Custom(3)), Some(40));
+        assert_eq!(storage.read(StorageSlot::Custom(5)), Some(15));
+        assert_eq!(page2[0], 0x00ff);
         assert_eq!(
-            page2_slice,
-            [0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff]
+            &page1[..20],
+            [
+                0x0fff, 0x4b56, 0x0001, 0x0002, 0x01af, 0x003f, 0x04ff, 0x015b, 0x005f, 0x0fff,
+                0x0101, 0x00af, 0x05ff, 0x014b, 0x002f, 0x1eff, 0x016b, 0x003f, 0x28ff, 0xffff
+            ]
         );
+    }
+
+    #[test]
+    fn rolls_over_latest_version_of_every_key() {
+        let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
 
-        assert_eq!(storage.rollover().is_ok(), true);
+        let storage = create_storage(&page1, &page2);
+        let hw = FlashMemoryMock::new();
+        assert_eq!(storage.setup(&hw), Ok(()));
+
+        assert_eq!(storage.write_bytes(&hw, 0x1234, &[1, 2, 3]), Ok(()));
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 4), Ok(()));
+        assert_eq!(storage.write_bytes(&hw, 0x0100, &[5, 6]), Ok(()));
+
+        // Fill the rest of the page with new versions of the same value.
+        for index in 0..124 {
+            let value = [index as u8, 0xff, 0xff];
+            assert_eq!(storage.write_bytes(&hw, 0x1234, &value), Ok(()));
+        }
+        assert_eq!(page2[0], 0xffff);
 
-        assert_eq!(page1_slice, [0x2f03, 0x2f03, 0x3f04, 0x5f0f]);
         assert_eq!(
-            page2_slice,
-            [0x0fff, 0xffff, 0x5f0f, 0x3f04, 0x2f03, 0xaf01, 0xffff, 0xffff]
+            storage.rollover(&hw).map(|page| page.address),
+            Ok(page2.as_ptr() as usize)
         );
 
-        assert_eq!(storage.write(StorageSlot::Custom(2), 10).is_ok(), true);
+        assert_eq!(storage.read(StorageSlot::Configuration), Some(4));
+        assert_eq!(storage.read_bytes(0x0100), Some([5, 6].as_ref().into()));
         assert_eq!(
-            page2_slice,
-            [0x0fff, 0xffff, 0x5f0f, 0x3f04, 0x2f03, 0xaf01, 0x2f0a, 0xffff]
+            storage.read_bytes(0x1234),
+            Some([123, 0xff, 0xff].as_ref().into())
+        );
+        assert_eq!(
+            &page2[..14],
+            [
+                0x0fff, 0x4b56, 0x0001, 0x0001, 0x0106, 0x00af, 0x04ff, 0x02e7, 0x0100, 0x0506,
+                0x0380, 0x1234, 0x7bff, 0xffff
+            ]
         );
     }
 
     #[test]
-    fn successfully_rolls_over_to_first_page() {
+    fn tracks_wear_statistics() {
         let mut page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
         let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
 
-        let storage = Storage {
-            pages: [
-                StoragePage {
-                    address: &page1 as *const _ as usize,
-                    size: PAGE_SIZE,
-                },
-                StoragePage {
-                    address: &page2 as *const _ as usize,
-                    size: PAGE_SIZE,
-                },
-            ],
+        let storage = create_storage(&page1, &page2);
+        let hw = FlashMemoryMock::new();
+        assert_eq!(storage.setup(&hw), Ok(()));
+
+        let stats = |erase_counts: [u16; 2], rollover_count, active_page, used_size| StorageStats {
+            erase_counts,
+            rollover_count,
+            active_page,
+            used_size,
+            page_size: PAGE_SIZE as u16,
         };
 
-        // Fill all memory slots, but the latest one.
-        assert_eq!(storage.write(StorageSlot::Configuration, 1).is_ok(), true);
-        assert_eq!(storage.write(StorageSlot::Custom(2), 2).is_ok(), true);
+        assert_eq!(storage.stats(), Ok(stats([0, 0], 0, 0, 8)));
 
-        for _ in 0..506 {
-            assert_eq!(storage.write(StorageSlot::Custom(2), 3).is_ok(), true);
-        }
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 1), Ok(()));
+        assert_eq!(storage.write(&hw, StorageSlot::Configuration, 2), Ok(()));
+        assert_eq!(storage.stats(), Ok(stats([0, 0], 0, 0, 20)));
 
-        // Fill remaining slots.
-        assert_eq!(storage.write(StorageSlot::Custom(3), 4).is_ok(), true);
-        assert_eq!(storage.write(StorageSlot::Custom(5), 15).is_ok(), true);
+        assert!(storage.rollover(&hw).is_ok());
+        assert_eq!(storage.stats(), Ok(stats([0, 1], 1, 1, 14)));
 
-        // Now we can't write anymore
-        assert_eq!(storage.write(StorageSlot::Configuration, 1).is_err(), true);
+        assert!(storage.rollover(&hw).is_ok());
+        assert_eq!(storage.stats(), Ok(stats([1, 1], 2, 0, 14)));
 
-        assert_eq!(storage.rollover().is_ok(), true);
+        assert!(storage.rollover(&hw).is_ok());
+        assert_eq!(storage.stats(), Ok(stats([1, 2], 3, 1, 14)));
 
-        // Fill next page.
-        // Fill all memory slots, but the latest one.
-        assert_eq!(storage.write(StorageSlot::Configuration, 10).is_ok(), true);
-        assert_eq!(storage.write(StorageSlot::Custom(2), 20).is_ok(), true);
+        // Lost erase counter is estimated using the counter of the other page.
+        page1[2..3].copy_from_slice(&[0xffff]);
+        assert_eq!(storage.stats(), Ok(stats([0, 2], 3, 1, 14)));
+        assert!(storage.rollover(&hw).is_ok());
+        assert_eq!(storage.stats(), Ok(stats([3, 2], 4, 0, 14)));
+        assert_eq!(storage.read(StorageSlot::Configuration), Some(2));
+    }
 
-        for _ in 0..502 {
-            assert_eq!(storage.write(StorageSlot::Custom(2), 30).is_ok(), true);
-        }
+    #[test]
+    fn upgrades_legacy_page() {
+        let mut page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
 
-        // Fill remaining slots.
-        assert_eq!(storage.write(StorageSlot::Custom(3), 40).is_ok(), true);
-        assert_eq!(storage.write(StorageSlot::Custom(5), 55).is_ok(), true);
+        // Active page written by the older firmware: size hint instead of the marker and compact
+        // records for 8-bit keys with one byte values.
+        page1[..6].copy_from_slice(&[0x0fff, 0x7fff, 0xaf01, 0x1f2a, 0x4d03, 0xaf02]);
 
-        // Now we can't write anymore
-        assert_eq!(storage.write(StorageSlot::Configuration, 1).is_err(), true);
+        let storage = create_storage(&page1, &page2);
+        let hw = FlashMemoryMock::new();
 
-        // Erase first page
-        for i in 0..(PAGE_SIZE / 2) {
-            page1[i] = 0xffff;
-        }
+        // Legacy page can be read, but it's converted only when storage is set up.
+        assert_eq!(storage.read(StorageSlot::Configuration), Some(2));
+        assert_eq!(page1[0], 0x0fff);
+        assert_eq!(page2[0], 0xffff);
 
-        assert_eq!(storage.rollover().is_ok(), true);
+        // Values are converted to the current format on the next page.
+        assert_eq!(storage.setup(&hw), Ok(()));
+        assert_eq!(storage.read(StorageSlot::Configuration), Some(2));
+        assert_eq!(storage.read(StorageSlot::Custom(1)), Some(42));
+        assert_eq!(storage.read(StorageSlot::StoredMelodyConfig), Some(3));
 
-        assert_eq!(storage.write(StorageSlot::Configuration, 1).is_ok(), true);
+        assert_eq!(page1[0], 0x00ff);
+        assert_eq!(page2[..4], [0x0fff, 0x4b56, 0x0001, 0x0001]);
+        assert_eq!(
+            page2[4..14],
+            [0x0126, 0x001f, 0x2aff, 0x010b, 0x004d, 0x03ff, 0x0114, 0x00af, 0x02ff, 0xffff]
+        );
 
-        assert_eq!(storage.read(StorageSlot::Configuration), Some(1));
-        assert_eq!(storage.read(StorageSlot::Custom(2)), Some(30));
-        assert_eq!(storage.read(StorageSlot::Custom(3)), Some(40));
-        assert_eq!(storage.read(StorageSlot::Custom(5)), Some(55));
+        // Legacy page has no counters, so statistics start from scratch.
         assert_eq!(
-            &page1[..7],
-            [0x0fff, 0xffff, 0x5f37, 0x3f28, 0x2f1e, 0xaf0a, 0xaf01]
+            storage.stats(),
+            Ok(StorageStats {
+                erase_counts: [0, 1],
+                rollover_count: 1,
+                active_page: 1,
+                used_size: 26,
+                page_size: PAGE_SIZE as u16,
+            })
         );
+
+        assert_eq!(storage.write(&hw, StorageSlot::Custom(1), 43), Ok(()));
+        assert_eq!(storage.read(StorageSlot::Custom(1)), Some(43));
+        assert_eq!(page1[0], 0x00ff);
+    }
+
+    #[test]
+    fn survives_power_loss_at_any_write() {
+        for torn_write_mask in [0xffff, 0xff00, 0x00ff, 0x0c00].iter() {
+            let mut writes_before_power_loss = 0;
+            loop {
+                let page1: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+                let page2: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+
+                let storage = create_storage(&page1, &page2);
+                let hw = FlashMemoryMock::new();
+                assert_eq!(storage.setup(&hw), Ok(()));
+
+                // Fill the page so that the next write triggers rollover.
+                assert_eq!(storage.write_bytes(&hw, 0x1234, &[1, 2, 3]), Ok(()));
+                assert_eq!(storage.write(&hw, StorageSlot::Configuration, 1), Ok(()));
+                for _ in 0..167 {
+                    assert_eq!(storage.write(&hw, StorageSlot::Custom(1), 2), Ok(()));
+                }
+
+                let hw =
+                    FlashMemoryMock::with_power_loss(writes_before_power_loss, *torn_write_mask);
+                let result = storage.write(&hw, StorageSlot::Configuration, 3);
+                let is_power_lost = hw.is_power_lost();
+
+                // Device restarts and sees either old or new value, but never corrupted one.
+                let hw = FlashMemoryMock::new();
+                assert_eq!(storage.setup(&hw), Ok(()));
+                let configuration = storage.read(StorageSlot::Configuration);
+                if is_power_lost {
+                    assert!(configuration == Some(1) || configuration == Some(3));
+                } else {
+                    assert_eq!(result, Ok(()));
+                    assert_eq!(configuration, Some(3));
+                }
+
+                assert_eq!(storage.read_bytes(0x1234), Some([1, 2, 3].as_ref().into()));
+                assert_eq!(storage.read(StorageSlot::Custom(1)), Some(2));
+
+                // Storage can still be written to.
+                assert_eq!(storage.write(&hw, StorageSlot::Custom(3), 4), Ok(()));
+                assert_eq!(storage.read(StorageSlot::Custom(3)), Some(4));
+                assert_eq!(storage.read(StorageSlot::Configuration), configuration);
+
+                if !is_power_lost {
+                    break;
+                }
+
+                writes_before_power_loss += 1;
+            }
+
+            // Format marker, counters, receiving status, 3 values, full and active statuses and the
+            // value itself.
+            assert_eq!(writes_before_power_loss, 19);
+        }
     }
 }
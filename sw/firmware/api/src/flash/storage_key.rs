@@ -0,0 +1,51 @@
+/// Describes keys of the multi-byte values (configs) stored in flash. Keys are 16-bit, so they
+/// never clash with the 8-bit keys of the single byte `StorageSlot`s.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StorageKey {
+    /// Pomodoro intervals and number of cycles, see `PomodoroConfig`.
+    PomodoroConfig,
+    /// Alarm snooze interval, limit and melody, see `SnoozeConfig`.
+    SnoozeConfig,
+    /// Alarm escalation steps and limit, see `EscalationConfig`.
+    EscalationConfig,
+    /// Beeper duty cycle and alarm volume, see `BeeperConfig`.
+    BeeperConfig,
+    /// Buttons timings, see `ButtonsConfig`.
+    ButtonsConfig,
+    /// Nested value is the index of the preset: 1..=3, see `Preset`.
+    Preset(u8),
+    /// Nested value is the shortcut trigger: 1..=5, see `ShortcutTrigger`.
+    Shortcut(u8),
+}
+
+impl From<StorageKey> for u16 {
+    fn from(key: StorageKey) -> Self {
+        match key {
+            StorageKey::PomodoroConfig => 0x0101,
+            StorageKey::SnoozeConfig => 0x0102,
+            StorageKey::EscalationConfig => 0x0103,
+            StorageKey::BeeperConfig => 0x0104,
+            StorageKey::ButtonsConfig => 0x0105,
+            StorageKey::Preset(index) => 0x0110 | u16::from(index),
+            StorageKey::Shortcut(trigger) => 0x0120 | u16::from(trigger),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correctly_converted_to_u16() {
+        assert_eq!(u16::from(StorageKey::PomodoroConfig), 0x0101);
+        assert_eq!(u16::from(StorageKey::SnoozeConfig), 0x0102);
+        assert_eq!(u16::from(StorageKey::EscalationConfig), 0x0103);
+        assert_eq!(u16::from(StorageKey::BeeperConfig), 0x0104);
+        assert_eq!(u16::from(StorageKey::ButtonsConfig), 0x0105);
+        assert_eq!(u16::from(StorageKey::Preset(1)), 0x0111);
+        assert_eq!(u16::from(StorageKey::Preset(3)), 0x0113);
+        assert_eq!(u16::from(StorageKey::Shortcut(1)), 0x0121);
+        assert_eq!(u16::from(StorageKey::Shortcut(5)), 0x0125);
+    }
+}
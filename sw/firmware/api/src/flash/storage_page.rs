@@ -1,21 +1,65 @@
-use super::storage_page_status::StoragePageStatus;
-use core::{mem, ops::Range};
+use super::{storage_page_status::StoragePageStatus, FlashHardware};
+use array::Array;
+use core::mem;
 
-#[derive(Debug)]
-pub struct StoragePageFullError<'a> {
-    pub active_page: &'a StoragePage,
-    pub next_page: &'a StoragePage,
-}
+/// Max size (in bytes) of the value stored in a single record.
+pub const MAX_VALUE_SIZE: usize = 64;
+
+/// Key that can't be used since it's indistinguishable from the erased flash.
+pub const INVALID_KEY: u16 = 0xffff;
+
+/// Erased half-word.
+const EMPTY_VALUE: u16 = 0xffff;
+
+/// Size of the page header (status, format marker, erase count and rollover count) in bytes.
+const HEADER_SIZE: usize = 4 * mem::size_of::<u16>();
+
+/// Offset of the page format marker within the page header.
+const FORMAT_MARKER_OFFSET: usize = mem::size_of::<u16>();
+
+/// Marks pages that store records in the current format. Pages written by the older firmware have
+/// either erased half-word or size hint (0xffff >> n) at this place.
+const FORMAT_MARKER: u16 = 0x4b56;
+
+/// Offset of the page erase count within the page header.
+const ERASE_COUNT_OFFSET: usize = 2 * mem::size_of::<u16>();
+
+/// Offset of the page rollover count within the page header.
+const ROLLOVER_COUNT_OFFSET: usize = 3 * mem::size_of::<u16>();
+
+/// Size of the legacy page header (status and size hint) in bytes.
+const LEGACY_HEADER_SIZE: usize = 2 * mem::size_of::<u16>();
+
+/// Size of the record header and key in bytes.
+const RECORD_HEADER_SIZE: usize = 2 * mem::size_of::<u16>();
+
+/// Max value of the page counter, erased half-word means that counter isn't known.
+const MAX_COUNTER_VALUE: u16 = 0xfffe;
+
+/// Polynomial of the CRC-8 (CRC-8/SMBUS) that protects records.
+const CRC_POLYNOMIAL: u8 = 0x07;
 
 /// Describes storage page. The page has the following shape:
 /// 0xffff - page header (status)
-/// 0xffff - page header (size hint)
-/// 0x1f01 - page value (slot 0x1f = slot#1 and value 0x01)
-/// 0x2f0a - page value (slot 0x2f = slot#2 and value 0x0a)
+/// 0x4b56 - page header (format marker)
+/// 0x0002 - page header (number of times page has been erased)
+/// 0x0003 - page header (number of rollovers storage has performed by the time page was activated)
+/// 0x038c - record header (value is 3 bytes long and its CRC-8 is 0x8c)
+/// 0x1234 - record key (0x1234)
+/// 0x0a0b - record value (bytes 0x0a and 0x0b)
+/// 0x0cff - record value (byte 0x0c, padded with 0xff)
 /// ......
-/// Due to the way flash memory works we can override value in a particular slot (1s can only change
-/// to 0s), so write new value with the same virtual address. So that when we read value from storage
-/// we are looking for the __latest__ value with the specified virtual address.
+/// Record CRC covers key, value size and value bytes. Record header is written last, so if power
+/// is lost while record is being written, it's either not visible at all or doesn't match its CRC.
+/// Due to the way flash memory works we can't override value of a particular key (1s can only
+/// change to 0s), so we append new record with the same key instead. So that when we read value
+/// from storage we are looking for the __latest__ valid record with the specified key.
+///
+/// Pages written by the older firmware (legacy pages) don't have format marker and counters, and
+/// their records start right after the status and size hint half-words. Legacy pages can only store
+/// one byte values of 8-bit keys as compact records that take a single half-word (0xaf01 - key 0xaf
+/// and value 0x01). Legacy records aren't protected with CRC, so legacy pages are only used to
+/// flush their values to a page in the current format.
 #[doc = r"Flash EEPROM emulation page"]
 #[derive(Debug, PartialOrd, PartialEq)]
 pub struct StoragePage {
@@ -29,146 +73,389 @@ impl StoragePage {
         self.u16(self.address).into()
     }
 
-    /// Sets page status (first two bytes of the page).
-    pub(super) fn set_status(&self, status: StoragePageStatus) {
-        self.set_u16(self.address, status.into())
+    /// Sets page status (first two bytes of the page). Flash should be in the `write` mode.
+    pub(super) fn set_status<T: FlashHardware>(&self, hw: &T, status: StoragePageStatus) {
+        hw.write_half_word(self.address, status.into())
     }
 
-    /// Retrieves page size hint. The hint is 3rd and 4th bytes of the page and suggests the last page
-    /// sector that is not fully empty. Since hint is 16 bit value it allows us to split page into
-    /// 16 equal sectors, when bit is cleared that means sector is used. E.g. when page size is 1024
-    /// bytes, hint can describe 16 sectors where each sector is 64 bytes wide. When page is fully
-    /// erased hint value is 0xffff or 0b1111_1111_1111_1111 meaning that only first sector probably
-    /// contains values, so range is 0 - 63, 0x0111_1111_1111_1111 means that first sector is full and
-    /// second sector is used, so range is 0 - 127 and so on. When we iterate through page to find a
-    /// value we can rely on hint and scan only non-pristine sectors.
-    fn size_hint(&self) -> u16 {
-        self.u16(self.address + mem::size_of::<u16>())
+    /// Retrieves number of times page has been erased. If counter has never been written (e.g. power
+    /// was lost right after the page was erased or page is a legacy one) `None` is returned.
+    pub(super) fn erase_count(&self) -> Option<u16> {
+        self.counter(ERASE_COUNT_OFFSET)
     }
 
-    /// Updates page size hint.
-    fn set_size_hint(&self, size_hint: u16) {
-        self.set_u16(self.address + mem::size_of::<u16>(), size_hint);
+    /// Retrieves number of rollovers storage has performed by the time page was activated. If
+    /// counter has never been written (e.g. page is a legacy one) `None` is returned.
+    pub(super) fn rollover_count(&self) -> Option<u16> {
+        self.counter(ROLLOVER_COUNT_OFFSET)
     }
 
-    /// Read the value using specified virtual address. The value is stored in u16 memory cell (0x1f01),
-    /// where two most significant bits are virtual address (0x1f) and least significant bits are
-    /// the value itself (0x01). So we have u8 virtual address and u8 value. If value isn't found
-    /// `None` is returned.
-    pub(super) fn read(&self, virtual_address: u8) -> Option<u8> {
-        assert_ne!(virtual_address, 0xff);
+    /// Checks whether page has been written by the older firmware and stores records in the legacy
+    /// format.
+    pub(super) fn is_legacy(&self) -> bool {
+        self.status() != StoragePageStatus::Erased
+            && self.u16(self.address + FORMAT_MARKER_OFFSET) != FORMAT_MARKER
+    }
 
-        for offset in self.search_range().rev().step_by(mem::size_of::<u16>()) {
-            let value = self.u16(self.address + offset);
-            // Skip empty values (0xffff) and iterate back to the start of th range.
-            if value != 0xffff && (value >> 8) as u8 == virtual_address {
-                return Some((value & 0xff) as u8);
-            }
-        }
+    /// Sets page format marker, erase and rollover counters, can be done only once after page is
+    /// erased. Flash should be in the `write` mode.
+    pub(super) fn set_counters<T: FlashHardware>(
+        &self,
+        hw: &T,
+        erase_count: u16,
+        rollover_count: u16,
+    ) {
+        hw.write_half_word(self.address + FORMAT_MARKER_OFFSET, FORMAT_MARKER);
+        hw.write_half_word(
+            self.address + ERASE_COUNT_OFFSET,
+            erase_count.min(MAX_COUNTER_VALUE),
+        );
+        hw.write_half_word(
+            self.address + ROLLOVER_COUNT_OFFSET,
+            rollover_count.min(MAX_COUNTER_VALUE),
+        );
+    }
 
-        None
+    /// Returns number of bytes occupied by the page header and valid records.
+    pub(super) fn used_size(&self) -> usize {
+        self.records()
+            .last()
+            .map(|record| record.next_offset())
+            .unwrap_or_else(|| self.header_size())
     }
 
-    /// Flushes latest version of all values from this page to another page.
-    pub(super) fn flush_to(&self, target_page: &StoragePage) -> Result<(), ()> {
-        let mut buffer: [Option<()>; core::u8::MAX as usize] = [None; core::u8::MAX as usize];
+    /// Checks whether every half-word of the page is erased.
+    pub(super) fn is_erased(&self) -> bool {
+        (self.address..self.address + self.size)
+            .step_by(mem::size_of::<u16>())
+            .all(|address| self.u16(address) == EMPTY_VALUE)
+    }
 
-        for offset in self.search_range().rev().step_by(mem::size_of::<u16>()) {
-            let value = self.u16(self.address + offset);
-            // Skip empty values (0xffff).
-            if value != 0xffff {
-                let virtual_address = (value >> 8) as usize;
-                if buffer[virtual_address].is_none() {
-                    buffer[virtual_address] = Some(());
-                    target_page.write(virtual_address as u8, (value & 0xff) as u8)?
-                }
+    /// Reads the value of the latest record with the specified key. If value isn't found `None` is
+    /// returned.
+    pub(super) fn read(&self, key: u16) -> Option<Array<u8>> {
+        self.records()
+            .filter(|record| record.key == key)
+            .last()
+            .map(|record| self.value(&record))
+    }
+
+    /// Flushes latest version of all values from this page to another page, values of the legacy
+    /// page are converted to the current format. Flash should be in the `write` mode.
+    pub(super) fn flush_to<T: FlashHardware>(
+        &self,
+        hw: &T,
+        target_page: &StoragePage,
+    ) -> Result<(), ()> {
+        for record in self.records() {
+            let is_latest = self
+                .records_from(record.next_offset())
+                .all(|next_record| next_record.key != record.key);
+            if is_latest {
+                target_page.write(hw, record.key, self.value(&record).as_ref())?;
             }
         }
 
         Ok(())
     }
 
-    /// Writes value with the specified virtual address. The value is stored in u16 memory cell (0x1f01),
-    /// where two most significant bits are virtual address (0x1f) and least significant bits are
-    /// the value itself (0x01). So we have u8 virtual address and u8 value.
-    /// If page is full error is returned.
-    pub(super) fn write(&self, virtual_address: u8, value: u8) -> Result<(), ()> {
-        assert_ne!(virtual_address, 0xff);
-
-        // Find the first empty (0xffff) cell in the memory to write new value to.
-        let search_range = self.search_range();
-        let offset = self
-            .search_range()
-            .rev()
-            .step_by(mem::size_of::<u16>())
-            .find(|offset| self.u16(self.address + offset) != 0xffff)
-            .map(|offset| offset + mem::size_of::<u16>())
-            .unwrap_or_else(|| {
-                // There are two cases when we don't find the place to write:
-                // 1. the page is fully erased
-                // 2. the last sector is full and we should extend search range.
-                if self.u16(self.address + search_range.start) == 0xffff {
-                    search_range.start
-                } else {
-                    search_range.end + 1
-                }
-            });
+    /// Appends record with the specified key and value right after the last valid one. If key is
+    /// invalid, value is too large or page doesn't have enough erased space (e.g. it's occupied by
+    /// the partially written record) error is returned. Flash should be in the `write` mode.
+    pub(super) fn write<T: FlashHardware>(&self, hw: &T, key: u16, value: &[u8]) -> Result<(), ()> {
+        if key == INVALID_KEY || value.len() > MAX_VALUE_SIZE {
+            return Err(());
+        }
 
-        if offset >= self.size {
+        let offset = self.used_size();
+        let record_size = RECORD_HEADER_SIZE + value.len().div_ceil(2) * mem::size_of::<u16>();
+        if offset + record_size > self.size
+            || (offset..offset + record_size)
+                .step_by(mem::size_of::<u16>())
+                .any(|offset| self.u16(self.address + offset) != EMPTY_VALUE)
+        {
             return Err(());
         }
 
-        // Try to extend search range (only if needed).
-        self.extend_search_range(offset);
+        hw.write_half_word(self.address + offset + mem::size_of::<u16>(), key);
+        for (index, chunk) in value.chunks(2).enumerate() {
+            hw.write_half_word(
+                self.address + offset + (2 + index) * mem::size_of::<u16>(),
+                u16::from(chunk[0]) << 8 | u16::from(*chunk.get(1).unwrap_or(&0xff)),
+            );
+        }
 
-        self.set_u16(
+        hw.write_half_word(
             self.address + offset,
-            (u16::from(virtual_address) << 8) | u16::from(value),
+            (value.len() as u16) << 8 | u16::from(crc(key, value)),
         );
 
         Ok(())
     }
 
+    /// Returns size of the page header that depends on the page format.
+    fn header_size(&self) -> usize {
+        if self.is_legacy() {
+            LEGACY_HEADER_SIZE
+        } else {
+            HEADER_SIZE
+        }
+    }
+
+    /// Iterates over all valid records stored in the page starting from the oldest one.
+    fn records(&self) -> Records<'_> {
+        self.records_from(self.header_size())
+    }
+
+    /// Iterates over valid records starting from the one located at the specified offset.
+    fn records_from(&self, offset: usize) -> Records<'_> {
+        Records {
+            page: self,
+            offset,
+            is_legacy: self.is_legacy(),
+        }
+    }
+
+    /// Reads value bytes of the specified record.
+    fn value(&self, record: &Record) -> Array<u8> {
+        if record.is_compact {
+            return [(self.u16(self.address + record.offset) & 0xff) as u8]
+                .as_ref()
+                .into();
+        }
+
+        let mut value = Array::new();
+        for index in 0..record.size {
+            let half_word =
+                self.u16(self.address + record.offset + (2 + index / 2) * mem::size_of::<u16>());
+            value.push(if index % 2 == 0 {
+                (half_word >> 8) as u8
+            } else {
+                (half_word & 0xff) as u8
+            });
+        }
+
+        value
+    }
+
+    /// Reads page header counter located at the specified offset. Counters are only trusted if page
+    /// has format marker, legacy pages keep records at their place.
+    fn counter(&self, offset: usize) -> Option<u16> {
+        if self.u16(self.address + FORMAT_MARKER_OFFSET) != FORMAT_MARKER {
+            return None;
+        }
+
+        match self.u16(self.address + offset) {
+            EMPTY_VALUE => None,
+            value => Some(value),
+        }
+    }
+
     /// Reads u16 from the specified address.
     fn u16(&self, address: usize) -> u16 {
         unsafe { core::ptr::read(address as *mut u16) }
     }
+}
+
+/// Calculates CRC-8 of the record key, value size and value bytes.
+fn crc(key: u16, value: &[u8]) -> u8 {
+    [(key >> 8) as u8, (key & 0xff) as u8, value.len() as u8]
+        .iter()
+        .chain(value.iter())
+        .fold(0, |crc, byte| {
+            (0..8).fold(crc ^ byte, |crc, _| {
+                if crc & 0x80 != 0 {
+                    crc << 1 ^ CRC_POLYNOMIAL
+                } else {
+                    crc << 1
+                }
+            })
+        })
+}
+
+/// Describes location of the record within the page.
+struct Record {
+    /// Offset of the record header from the start of the page.
+    offset: usize,
+    key: u16,
+    /// Size of the value in bytes.
+    size: usize,
+    /// Indicates whether record is a legacy compact one that takes a single half-word.
+    is_compact: bool,
+}
 
-    /// Writes u16 to the specified address.
-    fn set_u16(&self, address: usize, val: u16) {
-        unsafe { core::ptr::write(address as *mut u16, val) }
+impl Record {
+    /// Returns offset of the record that follows this one.
+    fn next_offset(&self) -> usize {
+        if self.is_compact {
+            self.offset + mem::size_of::<u16>()
+        } else {
+            self.offset + RECORD_HEADER_SIZE + self.size.div_ceil(2) * mem::size_of::<u16>()
+        }
     }
+}
+
+/// Iterates over page records until it reaches erased half-word, record that doesn't match its
+/// CRC or the end of the page.
+struct Records<'a> {
+    page: &'a StoragePage,
+    offset: usize,
+    /// Indicates whether records are stored in the legacy format.
+    is_legacy: bool,
+}
+
+impl<'a> Records<'a> {
+    /// Reads legacy record that isn't protected with CRC, so it's only checked to fit into the page.
+    fn next_legacy(&mut self) -> Option<Record> {
+        if self.offset + mem::size_of::<u16>() > self.page.size {
+            return None;
+        }
 
-    /// Defines a range of page where values are located based on size hint.
-    fn search_range(&self) -> Range<usize> {
-        let sector_size = (self.size / 16) as u32;
-        Range {
-            // Skip 4 header's bytes.
-            start: 2 * mem::size_of::<u16>(),
-            end: ((sector_size - 1) + (self.size_hint().leading_zeros() * sector_size)) as usize,
+        let header = self.page.u16(self.page.address + self.offset);
+        // Compact records can't have 0xff key, so it's either erased or corrupted half-word.
+        let key = (header >> 8) as u8;
+        if key == 0xff {
+            return None;
         }
+
+        let record = Record {
+            offset: self.offset,
+            key: u16::from(key),
+            size: 1,
+            is_compact: true,
+        };
+
+        self.offset = record.next_offset();
+        Some(record)
     }
+}
 
-    /// Extends search range if needed based on the sector where latest value is located.
-    fn extend_search_range(&self, upper_bound_offset: usize) {
-        let current_hint = self.size_hint();
-        let current_bound = current_hint.leading_zeros();
+impl<'a> Iterator for Records<'a> {
+    type Item = Record;
 
-        let sector_size = (self.size / 16) as u32;
-        let required_bound = upper_bound_offset as u32 / sector_size;
-        if required_bound > current_bound {
-            self.set_size_hint(current_hint >> (required_bound - current_bound));
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_legacy {
+            return self.next_legacy();
         }
+
+        if self.offset + RECORD_HEADER_SIZE > self.page.size {
+            return None;
+        }
+
+        let header = self.page.u16(self.page.address + self.offset);
+        let record = Record {
+            offset: self.offset,
+            key: self
+                .page
+                .u16(self.page.address + self.offset + mem::size_of::<u16>()),
+            size: (header >> 8) as usize,
+            is_compact: false,
+        };
+
+        if header == EMPTY_VALUE
+            || record.size > MAX_VALUE_SIZE
+            || record.next_offset() > self.page.size
+            || crc(record.key, self.page.value(&record).as_ref()) != (header & 0xff) as u8
+        {
+            return None;
+        }
+
+        self.offset = record.next_offset();
+        Some(record)
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(super) mod tests {
     use super::*;
+    use core::cell::Cell;
 
     // Size of the page in bytes (u8).
     const PAGE_SIZE: usize = 1024;
 
+    /// Flash hardware that writes and erases memory directly and can imitate power loss.
+    pub(in flash) struct FlashMemoryMock {
+        /// Number of half-word writes that succeed before power is lost.
+        writes_before_power_loss: Cell<Option<usize>>,
+        /// Bits that remain unprogrammed in the half-word that is being written when power is lost.
+        torn_write_mask: u16,
+        is_power_lost: Cell<bool>,
+    }
+
+    impl FlashMemoryMock {
+        pub fn new() -> Self {
+            FlashMemoryMock {
+                writes_before_power_loss: Cell::new(None),
+                torn_write_mask: 0xffff,
+                is_power_lost: Cell::new(false),
+            }
+        }
+
+        pub fn with_power_loss(writes_before_power_loss: usize, torn_write_mask: u16) -> Self {
+            FlashMemoryMock {
+                writes_before_power_loss: Cell::new(Some(writes_before_power_loss)),
+                torn_write_mask,
+                is_power_lost: Cell::new(false),
+            }
+        }
+
+        pub fn is_power_lost(&self) -> bool {
+            self.is_power_lost.get()
+        }
+    }
+
+    impl FlashHardware for FlashMemoryMock {
+        fn page_addresses(&self) -> [usize; 2] {
+            [0, 0]
+        }
+
+        fn blob_page_address(&self) -> usize {
+            0
+        }
+
+        fn erase_page(&self, page_address: usize) {
+            if !self.is_power_lost() {
+                for address in (page_address..page_address + PAGE_SIZE).step_by(2) {
+                    unsafe { core::ptr::write(address as *mut u16, EMPTY_VALUE) }
+                }
+            }
+        }
+
+        fn enable_write_mode(&self) {}
+
+        fn disable_write_mode(&self) {}
+
+        fn write_half_word(&self, address: usize, value: u16) {
+            if self.is_power_lost() {
+                return;
+            }
+
+            let value = match self.writes_before_power_loss.get() {
+                Some(0) => {
+                    self.is_power_lost.set(true);
+                    value | self.torn_write_mask
+                }
+                Some(writes) => {
+                    self.writes_before_power_loss.set(Some(writes - 1));
+                    value
+                }
+                None => value,
+            };
+
+            // Flash programming can only clear bits.
+            unsafe {
+                core::ptr::write(
+                    address as *mut u16,
+                    value & core::ptr::read(address as *mut u16),
+                )
+            }
+        }
+    }
+
+    fn value(bytes: &[u8]) -> Option<Array<u8>> {
+        Some(bytes.into())
+    }
+
     #[test]
     fn correctly_initializes() {
         let memory_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
@@ -183,6 +470,7 @@ mod tests {
             [0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff]
         );
         assert_eq!(page.read(0xaf), None);
+        assert!(page.is_erased());
     }
 
     #[test]
@@ -192,31 +480,71 @@ mod tests {
             address: &memory_sandbox as *const _ as usize,
             size: PAGE_SIZE,
         };
+        let hw = FlashMemoryMock::new();
 
-        let memory_slice = &memory_sandbox[..6];
+        let memory_slice = &memory_sandbox[..13];
 
         assert_eq!(page.read(0xaf), None);
-        assert_eq!(page.write(0xaf, 2).is_ok(), true);
-        assert_eq!(page.read(0xaf), Some(2));
+        assert!(page.write(&hw, 0xaf, &[2]).is_ok());
+        assert_eq!(page.read(0xaf), value(&[2]));
         assert_eq!(
             memory_slice,
-            [0xffff, 0xffff, 0xaf02, 0xffff, 0xffff, 0xffff]
+            [
+                0xffff, 0xffff, 0xffff, 0xffff, 0x0114, 0x00af, 0x02ff, 0xffff, 0xffff, 0xffff,
+                0xffff, 0xffff, 0xffff
+            ]
         );
 
-        assert_eq!(page.write(0xaf, 3).is_ok(), true);
-        assert_eq!(page.read(0xaf), Some(3));
+        assert!(page.write(&hw, 0xaf, &[3]).is_ok());
+        assert_eq!(page.read(0xaf), value(&[3]));
         assert_eq!(
             memory_slice,
-            [0xffff, 0xffff, 0xaf02, 0xaf03, 0xffff, 0xffff]
+            [
+                0xffff, 0xffff, 0xffff, 0xffff, 0x0114, 0x00af, 0x02ff, 0x0113, 0x00af, 0x03ff,
+                0xffff, 0xffff, 0xffff
+            ]
         );
 
-        assert_eq!(page.write(0x2f, 4).is_ok(), true);
-        assert_eq!(page.read(0xaf), Some(3));
-        assert_eq!(page.read(0x2f), Some(4));
+        assert!(page.write(&hw, 0x2f, &[4]).is_ok());
+        assert_eq!(page.read(0xaf), value(&[3]));
+        assert_eq!(page.read(0x2f), value(&[4]));
         assert_eq!(
             memory_slice,
-            [0xffff, 0xffff, 0xaf02, 0xaf03, 0x2f04, 0xffff]
+            [
+                0xffff, 0xffff, 0xffff, 0xffff, 0x0114, 0x00af, 0x02ff, 0x0113, 0x00af, 0x03ff,
+                0x010d, 0x002f, 0x04ff
+            ]
+        );
+        assert!(!page.is_erased());
+    }
+
+    #[test]
+    fn correctly_writes_and_reads_multi_byte_values() {
+        let memory_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page = StoragePage {
+            address: &memory_sandbox as *const _ as usize,
+            size: PAGE_SIZE,
+        };
+        let hw = FlashMemoryMock::new();
+
+        // 16-bit key, odd number of bytes and bytes that look like erased flash.
+        assert!(page.write(&hw, 0x1234, &[0x0a, 0xff, 0xff]).is_ok());
+        // Empty value.
+        assert!(page.write(&hw, 0x2f, &[]).is_ok());
+        assert_eq!(
+            memory_sandbox[..11],
+            [
+                0xffff, 0xffff, 0xffff, 0xffff, 0x038c, 0x1234, 0x0aff, 0xffff, 0x006d, 0x002f,
+                0xffff
+            ]
         );
+
+        assert_eq!(page.read(0x1234), value(&[0x0a, 0xff, 0xff]));
+        assert_eq!(page.read(0x2f), value(&[]));
+        assert_eq!(page.read(0x34), None);
+
+        assert!(page.write(&hw, 0x1234, &[0x01]).is_ok());
+        assert_eq!(page.read(0x1234), value(&[0x01]));
     }
 
     #[test]
@@ -226,29 +554,89 @@ mod tests {
             address: &memory_sandbox as *const _ as usize,
             size: PAGE_SIZE,
         };
+        let hw = FlashMemoryMock::new();
 
-        // Fill all memory slots, but the latest one.
-        assert_eq!(page.write(0xaf, 1).is_ok(), true);
-        assert_eq!(page.write(0x2f, 2).is_ok(), true);
-        for _ in 0..506 {
-            assert_eq!(page.write(0x2f, 3).is_ok(), true);
+        // Fill all memory, but the latest record.
+        assert!(page.write(&hw, 0xaf, &[1]).is_ok());
+        assert!(page.write(&hw, 0x2f, &[2]).is_ok());
+        for _ in 0..165 {
+            assert!(page.write(&hw, 0x2f, &[3]).is_ok());
         }
-        assert_eq!(page.write(0x3f, 4).is_ok(), true);
+        assert!(page.write(&hw, 0x3f, &[4]).is_ok());
 
-        assert_eq!(page.read(0xaf), Some(1));
-        assert_eq!(page.read(0x2f), Some(3));
-        assert_eq!(page.read(0x3f), Some(4));
+        assert_eq!(page.read(0xaf), value(&[1]));
+        assert_eq!(page.read(0x2f), value(&[3]));
+        assert_eq!(page.read(0x3f), value(&[4]));
 
-        let memory_slice = &memory_sandbox[508..];
-        assert_eq!(memory_slice, [0x2f03, 0x2f03, 0x3f04, 0xffff]);
+        let memory_slice = &memory_sandbox[505..];
+        assert_eq!(
+            memory_slice,
+            [0x01af, 0x003f, 0x04ff, 0xffff, 0xffff, 0xffff, 0xffff]
+        );
+        assert_eq!(page.used_size(), PAGE_SIZE - 8);
 
-        // Fill last slot
-        assert_eq!(page.write(0x5f, 15).is_ok(), true);
-        assert_eq!(memory_slice, [0x2f03, 0x2f03, 0x3f04, 0x5f0f]);
+        // Value that doesn't fit into remaining space.
+        assert!(page.write(&hw, 0x5f, &[15, 16, 17, 18, 19]).is_err());
+
+        // Fill last record.
+        assert!(page.write(&hw, 0x5f, &[15, 16, 17]).is_ok());
+        assert_eq!(
+            memory_slice,
+            [0x01af, 0x003f, 0x04ff, 0x0397, 0x005f, 0x0f10, 0x11ff]
+        );
+        assert_eq!(page.used_size(), PAGE_SIZE);
 
         // Now we can't write anymore
-        let write_result = page.write(0xaf, 1);
-        assert_eq!(write_result.is_err(), true);
+        let write_result = page.write(&hw, 0xaf, &[1]);
+        assert!(write_result.is_err());
+    }
+
+    #[test]
+    fn fails_to_write_invalid_records() {
+        let memory_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page = StoragePage {
+            address: &memory_sandbox as *const _ as usize,
+            size: PAGE_SIZE,
+        };
+        let hw = FlashMemoryMock::new();
+
+        assert!(page.write(&hw, INVALID_KEY, &[1]).is_err());
+        assert!(page.write(&hw, 0x1234, &[1; MAX_VALUE_SIZE + 1]).is_err());
+        assert!(page.write(&hw, 0x1234, &[1; MAX_VALUE_SIZE]).is_ok());
+        assert_eq!(page.read(0x1234), value(&[1; MAX_VALUE_SIZE]));
+    }
+
+    #[test]
+    fn ignores_corrupted_records() {
+        let mut memory_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page = StoragePage {
+            address: &memory_sandbox as *const _ as usize,
+            size: PAGE_SIZE,
+        };
+        let hw = FlashMemoryMock::new();
+
+        assert!(page.write(&hw, 0xaf, &[2]).is_ok());
+        assert!(page.write(&hw, 0xaf, &[3]).is_ok());
+        assert!(page.write(&hw, 0x2f, &[4]).is_ok());
+
+        // Corrupt value of the second record.
+        memory_sandbox[9] = 0x01ff;
+        assert_eq!(page.read(0xaf), value(&[2]));
+        assert_eq!(page.read(0x2f), None);
+
+        // Space occupied by corrupted records can't be used.
+        assert!(page.write(&hw, 0xaf, &[5]).is_err());
+
+        // Record which header wasn't written.
+        memory_sandbox[7] = 0xffff;
+        memory_sandbox[8] = 0x00af;
+        memory_sandbox[9] = 0x03ff;
+        assert_eq!(page.read(0xaf), value(&[2]));
+        assert!(page.write(&hw, 0xaf, &[5]).is_err());
+
+        // Record header that can't be valid.
+        memory_sandbox[7..8].copy_from_slice(&[0x4113]);
+        assert_eq!(page.read(0xaf), value(&[2]));
     }
 
     #[test]
@@ -258,19 +646,89 @@ mod tests {
             address: &memory_sandbox as *const _ as usize,
             size: PAGE_SIZE,
         };
+        let hw = FlashMemoryMock::new();
 
         assert_eq!(page.status(), StoragePageStatus::Erased);
 
-        page.set_status(StoragePageStatus::Active);
+        page.set_status(&hw, StoragePageStatus::Receiving);
+        assert_eq!(page.status(), StoragePageStatus::Receiving);
+        assert_eq!(memory_sandbox[..2], [0x7fff, 0xffff]);
+
+        page.set_status(&hw, StoragePageStatus::Active);
         assert_eq!(page.status(), StoragePageStatus::Active);
         assert_eq!(memory_sandbox[..2], [0x0fff, 0xffff]);
 
-        page.set_status(StoragePageStatus::Full);
+        page.set_status(&hw, StoragePageStatus::Full);
         assert_eq!(page.status(), StoragePageStatus::Full);
         assert_eq!(memory_sandbox[..2], [0x00ff, 0xffff]);
 
         memory_sandbox[0] = 0xffff;
         assert_eq!(page.status(), StoragePageStatus::Erased);
+        assert_eq!(memory_sandbox[..2], [0xffff, 0xffff]);
+    }
+
+    #[test]
+    fn correctly_sets_counters() {
+        let memory_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page = StoragePage {
+            address: &memory_sandbox as *const _ as usize,
+            size: PAGE_SIZE,
+        };
+        let hw = FlashMemoryMock::new();
+
+        assert_eq!(page.erase_count(), None);
+        assert_eq!(page.rollover_count(), None);
+        assert_eq!(page.used_size(), 8);
+
+        page.set_counters(&hw, 3, 0);
+        assert_eq!(page.erase_count(), Some(3));
+        assert_eq!(page.rollover_count(), Some(0));
+        assert_eq!(
+            memory_sandbox[..5],
+            [0xffff, 0x4b56, 0x0003, 0x0000, 0xffff]
+        );
+
+        // Records don't affect counters.
+        assert!(page.write(&hw, 0xaf, &[2]).is_ok());
+        assert_eq!(page.erase_count(), Some(3));
+        assert_eq!(page.rollover_count(), Some(0));
+        assert_eq!(page.used_size(), 14);
+    }
+
+    #[test]
+    fn ignores_counters_of_legacy_page() {
+        let mut memory_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page = StoragePage {
+            address: &memory_sandbox as *const _ as usize,
+            size: PAGE_SIZE,
+        };
+
+        // Legacy records take place of the counters.
+        memory_sandbox[..4].copy_from_slice(&[0x0fff, 0xffff, 0xaf01, 0x1f2a]);
+        assert!(page.is_legacy());
+        assert_eq!(page.erase_count(), None);
+        assert_eq!(page.rollover_count(), None);
+        assert_eq!(page.read(0xaf), value(&[0x01]));
+
+        // Size hint doesn't make page look like the current one.
+        memory_sandbox[1..2].copy_from_slice(&[0x3fff]);
+        assert!(page.is_legacy());
+        assert_eq!(page.erase_count(), None);
+        assert_eq!(page.rollover_count(), None);
+    }
+
+    #[test]
+    fn never_sets_counters_to_unknown_value() {
+        let memory_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
+        let page = StoragePage {
+            address: &memory_sandbox as *const _ as usize,
+            size: PAGE_SIZE,
+        };
+        let hw = FlashMemoryMock::new();
+
+        page.set_counters(&hw, 0xffff, 0xffff);
+        assert_eq!(page.erase_count(), Some(0xfffe));
+        assert_eq!(page.rollover_count(), Some(0xfffe));
     }
 
     #[test]
@@ -280,36 +738,33 @@ mod tests {
             address: &page1_sandbox as *const _ as usize,
             size: PAGE_SIZE,
         };
+        let hw = FlashMemoryMock::new();
 
-        assert_eq!(page1.write(0xaf, 1).is_ok(), true);
-        assert_eq!(page1.write(0x2f, 2).is_ok(), true);
-        assert_eq!(page1.write(0x2f, 3).is_ok(), true);
-        assert_eq!(page1.write(0x3f, 4).is_ok(), true);
-        assert_eq!(page1.write(0x5f, 15).is_ok(), true);
-
-        let page_1_slice = &page1_sandbox[..8];
-        assert_eq!(
-            page_1_slice,
-            [0xffff, 0xffff, 0xaf01, 0x2f02, 0x2f03, 0x3f04, 0x5f0f, 0xffff]
-        );
+        assert!(page1.write(&hw, 0xaf, &[1]).is_ok());
+        assert!(page1.write(&hw, 0x2f, &[2]).is_ok());
+        assert!(page1.write(&hw, 0x2f, &[3]).is_ok());
+        assert!(page1.write(&hw, 0x3f, &[4]).is_ok());
+        assert!(page1.write(&hw, 0x5f, &[15]).is_ok());
 
         let page2_sandbox: [u16; PAGE_SIZE / 2] = [0xffff; PAGE_SIZE / 2];
         let page2 = StoragePage {
             address: &page2_sandbox as *const _ as usize,
             size: PAGE_SIZE,
         };
-        assert_eq!(page1.flush_to(&page2).is_ok(), true);
+        assert!(page1.flush_to(&hw, &page2).is_ok());
 
-        let page_2_slice = &page2_sandbox[..8];
+        let page_2_slice = &page2_sandbox[..19];
         assert_eq!(
             page_2_slice,
-            [0xffff, 0xffff, 0x5f0f, 0x3f04, 0x2f03, 0xaf01, 0xffff, 0xffff]
+            [
+                0xffff, 0xffff, 0xffff, 0xffff, 0x011d, 0x00af, 0x01ff, 0x0118, 0x002f, 0x03ff,
+                0x01af, 0x003f, 0x04ff, 0x015b, 0x005f, 0x0fff, 0xffff, 0xffff, 0xffff
+            ]
         );
 
-        assert_eq!(page2.write(0x2f, 10).is_ok(), true);
-        assert_eq!(
-            page_2_slice,
-            [0xffff, 0xffff, 0x5f0f, 0x3f04, 0x2f03, 0xaf01, 0x2f0a, 0xffff]
-        );
+        assert!(page2.write(&hw, 0x2f, &[10]).is_ok());
+        assert_eq!(page_2_slice[16..], [0x0127, 0x002f, 0x0aff]);
+        assert_eq!(page2.read(0x2f), value(&[10]));
+        assert_eq!(page2.read(0xaf), value(&[1]));
     }
 }
@@ -2,17 +2,22 @@
 /// bytes of the page.
 #[derive(Debug, PartialOrd, PartialEq)]
 pub(crate) enum StoragePageStatus {
+    /// Page is receiving latest values from the page that is full, values it has can't be trusted
+    /// until it becomes active.
+    Receiving,
     /// Page is active and can accept new values.
     Active,
     /// Page is full and should be erased before it can be used.
     Full,
-    /// Page is erased and ready to accept new values.
+    /// Page is erased and ready to accept new values. Pages with partially programmed status are
+    /// considered erased as well, so they are erased again before they are used.
     Erased,
 }
 
 impl From<u16> for StoragePageStatus {
     fn from(value: u16) -> Self {
         match value {
+            0x7fff => StoragePageStatus::Receiving,
             0x0fff => StoragePageStatus::Active,
             0x00ff => StoragePageStatus::Full,
             _ => StoragePageStatus::Erased,
@@ -20,9 +25,10 @@ impl From<u16> for StoragePageStatus {
     }
 }
 
-impl Into<u16> for StoragePageStatus {
-    fn into(self) -> u16 {
-        match self {
+impl From<StoragePageStatus> for u16 {
+    fn from(status: StoragePageStatus) -> Self {
+        match status {
+            StoragePageStatus::Receiving => 0x7fff,
             StoragePageStatus::Active => 0x0fff,
             StoragePageStatus::Full => 0x00ff,
             StoragePageStatus::Erased => 0xffff,
@@ -36,13 +42,20 @@ mod tests {
 
     #[test]
     fn correctly_created_from_u16() {
+        assert_eq!(
+            StoragePageStatus::from(0x7fff),
+            StoragePageStatus::Receiving
+        );
         assert_eq!(StoragePageStatus::from(0x0fff), StoragePageStatus::Active);
         assert_eq!(StoragePageStatus::from(0x00ff), StoragePageStatus::Full);
         assert_eq!(StoragePageStatus::from(0xffff), StoragePageStatus::Erased);
+        // Partially programmed status.
+        assert_eq!(StoragePageStatus::from(0x0cff), StoragePageStatus::Erased);
     }
 
     #[test]
     fn correctly_converted_to_u16() {
+        assert_eq!(Into::<u16>::into(StoragePageStatus::Receiving), 0x7fff);
         assert_eq!(Into::<u16>::into(StoragePageStatus::Active), 0x0fff);
         assert_eq!(Into::<u16>::into(StoragePageStatus::Full), 0x00ff);
         assert_eq!(Into::<u16>::into(StoragePageStatus::Erased), 0xffff);
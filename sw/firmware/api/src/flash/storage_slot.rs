@@ -6,6 +6,8 @@ pub enum StorageSlot {
     Configuration,
     /// Nested value is the index of custom slot: 1..=4.
     Custom(u8),
+    /// Flags that define which sounds use melody stored in flash, see `StoredMelodyConfig`.
+    StoredMelodyConfig,
 }
 
 impl TryFrom<u8> for StorageSlot {
@@ -15,20 +17,29 @@ impl TryFrom<u8> for StorageSlot {
         match value {
             0xaf => Ok(StorageSlot::Configuration),
             slot if matches!(slot, 0x1f | 0x2f | 0x3f | 0x4f) => Ok(StorageSlot::Custom(slot >> 4)),
+            0x4d => Ok(StorageSlot::StoredMelodyConfig),
             _ => Err(()),
         }
     }
 }
 
-impl Into<u8> for StorageSlot {
-    fn into(self) -> u8 {
-        match self {
+impl From<StorageSlot> for u8 {
+    fn from(slot: StorageSlot) -> Self {
+        match slot {
             StorageSlot::Configuration => 0xaf,
             StorageSlot::Custom(slot) => slot << 4 | 0xf,
+            StorageSlot::StoredMelodyConfig => 0x4d,
         }
     }
 }
 
+/// Slots are stored with 8-bit keys.
+impl From<StorageSlot> for u16 {
+    fn from(slot: StorageSlot) -> Self {
+        u16::from(Into::<u8>::into(slot))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,6 +51,10 @@ mod tests {
         assert_eq!(StorageSlot::try_from(0x2f), Ok(StorageSlot::Custom(2)));
         assert_eq!(StorageSlot::try_from(0x3f), Ok(StorageSlot::Custom(3)));
         assert_eq!(StorageSlot::try_from(0x4f), Ok(StorageSlot::Custom(4)));
+        assert_eq!(
+            StorageSlot::try_from(0x4d),
+            Ok(StorageSlot::StoredMelodyConfig)
+        );
     }
 
     #[test]
@@ -49,11 +64,18 @@ mod tests {
         assert_eq!(Into::<u8>::into(StorageSlot::Custom(2)), 0x2f);
         assert_eq!(Into::<u8>::into(StorageSlot::Custom(3)), 0x3f);
         assert_eq!(Into::<u8>::into(StorageSlot::Custom(4)), 0x4f);
+        assert_eq!(Into::<u8>::into(StorageSlot::StoredMelodyConfig), 0x4d);
+        assert_eq!(u16::from(StorageSlot::Configuration), 0x00af);
+        assert_eq!(u16::from(StorageSlot::Custom(4)), 0x004f);
     }
 
     #[test]
     fn invalid_slot() {
-        for slot_id in &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x6f, 0xff] {
+        for slot_id in &[
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x6f, 0x68, 0x4c, 0x67, 0x14, 0x73, 0xff,
+            // Configs, presets and shortcuts are stored as multi-byte values with `StorageKey`s.
+            0x1e, 0x1d, 0x13, 0x33, 0x18, 0x1c, 0x2b, 0x3a, 0x19, 0x17, 0x26, 0x45,
+        ] {
             assert_eq!(StorageSlot::try_from(*slot_id), Err(()));
         }
     }
@@ -0,0 +1,112 @@
+use array::Array;
+use core::convert::TryFrom;
+
+/// Size of the serialized stats in bytes.
+const SERIALIZED_SIZE: usize = 11;
+
+/// Describes wear of the storage pages and fill level of the currently active one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StorageStats {
+    /// Number of times every storage page has been erased.
+    pub erase_counts: [u16; 2],
+
+    /// Number of times storage moved values to the next page because active one was full.
+    pub rollover_count: u16,
+
+    /// Index of the currently active storage page.
+    pub active_page: u8,
+
+    /// Number of bytes occupied by the header and values in the active page.
+    pub used_size: u16,
+
+    /// Size of the storage page in bytes.
+    pub page_size: u16,
+}
+
+impl From<StorageStats> for Array<u8> {
+    fn from(stats: StorageStats) -> Self {
+        let mut array = Array::new();
+        for value in [
+            stats.erase_counts[0],
+            stats.erase_counts[1],
+            stats.rollover_count,
+        ]
+        .iter()
+        {
+            array.push((value & 0x00ff) as u8);
+            array.push(((value & 0xff00) >> 8) as u8);
+        }
+
+        array.push(stats.active_page);
+
+        for value in [stats.used_size, stats.page_size].iter() {
+            array.push((value & 0x00ff) as u8);
+            array.push(((value & 0xff00) >> 8) as u8);
+        }
+
+        array
+    }
+}
+
+impl TryFrom<Array<u8>> for StorageStats {
+    type Error = ();
+
+    fn try_from(value: Array<u8>) -> Result<Self, Self::Error> {
+        if value.len() != SERIALIZED_SIZE {
+            return Err(());
+        }
+
+        let u16_at = |index: usize| value[index] as u16 | (value[index + 1] as u16) << 8;
+        Ok(StorageStats {
+            erase_counts: [u16_at(0), u16_at(2)],
+            rollover_count: u16_at(4),
+            active_page: value[6],
+            used_size: u16_at(7),
+            page_size: u16_at(9),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn properly_serialized() {
+        assert_eq!(
+            Array::from(StorageStats {
+                erase_counts: [0x0102, 0x0304],
+                rollover_count: 0x0506,
+                active_page: 1,
+                used_size: 0x0078,
+                page_size: 0x0400,
+            })
+            .as_ref(),
+            [0x02, 0x01, 0x04, 0x03, 0x06, 0x05, 0x01, 0x78, 0x00, 0x00, 0x04]
+        );
+    }
+
+    #[test]
+    fn properly_deserialized() {
+        assert_eq!(
+            StorageStats::try_from(Array::from(&[
+                0x02, 0x01, 0x04, 0x03, 0x06, 0x05, 0x01, 0x78, 0x00, 0x00, 0x04
+            ])),
+            Ok(StorageStats {
+                erase_counts: [0x0102, 0x0304],
+                rollover_count: 0x0506,
+                active_page: 1,
+                used_size: 0x0078,
+                page_size: 0x0400,
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_serialized_data() {
+        assert_eq!(
+            StorageStats::try_from(Array::from(&[0x02, 0x01, 0x04, 0x03, 0x06, 0x05, 0x01])),
+            Err(())
+        );
+    }
+}
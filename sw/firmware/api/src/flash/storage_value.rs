@@ -0,0 +1,113 @@
+use array::Array;
+
+/// Describes value that can be stored in flash storage as a sequence of bytes. Numbers are stored
+/// in little-endian byte order.
+pub trait StorageValue: Sized {
+    /// Serializes value into bytes.
+    fn to_bytes(&self) -> Array<u8>;
+
+    /// Deserializes value from bytes, returns `None` if bytes don't represent a valid value.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl StorageValue for u8 {
+    fn to_bytes(&self) -> Array<u8> {
+        [*self].as_ref().into()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [value] => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl StorageValue for bool {
+    fn to_bytes(&self) -> Array<u8> {
+        (*self as u8).to_bytes()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match u8::from_bytes(bytes) {
+            Some(0) => Some(false),
+            Some(1) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl StorageValue for u16 {
+    fn to_bytes(&self) -> Array<u8> {
+        self.to_le_bytes().as_ref().into()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [b0, b1] => Some(u16::from_le_bytes([*b0, *b1])),
+            _ => None,
+        }
+    }
+}
+
+impl StorageValue for u32 {
+    fn to_bytes(&self) -> Array<u8> {
+        self.to_le_bytes().as_ref().into()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [b0, b1, b2, b3] => Some(u32::from_le_bytes([*b0, *b1, *b2, *b3])),
+            _ => None,
+        }
+    }
+}
+
+/// Raw bytes are stored as is.
+impl StorageValue for Array<u8> {
+    fn to_bytes(&self) -> Array<u8> {
+        *self
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_and_from_bytes() {
+        assert_eq!(0x12u8.to_bytes().as_ref(), [0x12]);
+        assert_eq!(u8::from_bytes(&[0x12]), Some(0x12));
+
+        assert_eq!(true.to_bytes().as_ref(), [1]);
+        assert_eq!(false.to_bytes().as_ref(), [0]);
+        assert_eq!(bool::from_bytes(&[1]), Some(true));
+        assert_eq!(bool::from_bytes(&[0]), Some(false));
+
+        assert_eq!(0x1234u16.to_bytes().as_ref(), [0x34, 0x12]);
+        assert_eq!(u16::from_bytes(&[0x34, 0x12]), Some(0x1234));
+
+        assert_eq!(0x1234_5678u32.to_bytes().as_ref(), [0x78, 0x56, 0x34, 0x12]);
+        assert_eq!(
+            u32::from_bytes(&[0x78, 0x56, 0x34, 0x12]),
+            Some(0x1234_5678)
+        );
+
+        let bytes: Array<u8> = [1, 2, 3].as_ref().into();
+        assert_eq!(bytes.to_bytes(), bytes);
+        assert_eq!(Array::<u8>::from_bytes(&[1, 2, 3]), Some(bytes));
+    }
+
+    #[test]
+    fn fails_to_convert_from_invalid_bytes() {
+        assert_eq!(u8::from_bytes(&[]), None);
+        assert_eq!(u8::from_bytes(&[1, 2]), None);
+        assert_eq!(bool::from_bytes(&[2]), None);
+        assert_eq!(u16::from_bytes(&[1]), None);
+        assert_eq!(u32::from_bytes(&[1, 2, 3]), None);
+    }
+}
@@ -11,13 +11,16 @@ extern crate bit_field;
 extern crate libm;
 
 pub mod adc;
+pub mod alarm;
 pub mod array;
 pub mod beeper;
 pub mod buttons;
 pub mod config;
 pub mod flash;
+pub mod preset;
 pub mod radio;
 pub mod rtc;
+pub mod shortcut;
 pub mod system;
 pub mod systick;
 pub mod time;
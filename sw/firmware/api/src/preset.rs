@@ -0,0 +1,164 @@
+use array::Array;
+use beeper::melody::Melody;
+use core::convert::TryFrom;
+use flash::{storage_key::StorageKey, storage_value::StorageValue, Flash, FlashHardware};
+use time::Time;
+
+/// Max number of presets that can be stored in the flash, presets are indexed starting from 1.
+pub const PRESETS_COUNT: u8 = 3;
+
+/// Describes favorite countdown alarm that can be recalled with a button gesture.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Preset {
+    /// Duration of the countdown alarm.
+    pub duration: Time,
+    /// Built-in melody that is played when alarm fires, default alarm melody is used if not set.
+    pub melody: Option<Melody>,
+}
+
+impl Preset {
+    /// Checks whether preset with the specified index can exist.
+    pub fn is_valid_index(index: u8) -> bool {
+        index > 0 && index <= PRESETS_COUNT
+    }
+
+    /// Reads preset with the specified index from the flash, returns `None` if preset isn't set or
+    /// has been deleted.
+    pub fn read<T: FlashHardware>(flash: &Flash<T>, index: u8) -> Option<Self> {
+        if !Self::is_valid_index(index) {
+            return None;
+        }
+
+        flash.read_value(StorageKey::Preset(index).into())
+    }
+
+    /// Persists preset with the specified index in the flash.
+    #[allow(clippy::result_unit_err)]
+    pub fn write<T: FlashHardware>(&self, flash: &Flash<T>, index: u8) -> Result<(), ()> {
+        if !Self::is_valid_index(index) || self.duration.as_seconds() == 0 {
+            return Err(());
+        }
+
+        if let Some(melody) = self.melody {
+            u8::try_from(melody)?;
+        }
+
+        flash.write_value(StorageKey::Preset(index).into(), *self)
+    }
+
+    /// Deletes preset with the specified index from the flash. Storage doesn't support removal so
+    /// preset is replaced with an empty value.
+    #[allow(clippy::result_unit_err)]
+    pub fn delete<T: FlashHardware>(flash: &Flash<T>, index: u8) -> Result<(), ()> {
+        if !Self::is_valid_index(index) {
+            return Err(());
+        }
+
+        flash.write_bytes(StorageKey::Preset(index).into(), &[])
+    }
+}
+
+/// Preset without melody has its melody serialized as `0`. Custom melodies can't be referenced by
+/// identifier and are serialized as `0` as well.
+impl From<Preset> for Array<u8> {
+    fn from(preset: Preset) -> Self {
+        [
+            preset.duration.hours,
+            preset.duration.minutes,
+            preset.duration.seconds,
+            preset
+                .melody
+                .and_then(|melody| u8::try_from(melody).ok())
+                .unwrap_or(0),
+        ]
+        .as_ref()
+        .into()
+    }
+}
+
+impl TryFrom<&[u8]> for Preset {
+    type Error = ();
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value {
+            [hours, minutes, seconds, melody] => {
+                let duration = Time {
+                    hours: *hours,
+                    minutes: *minutes,
+                    seconds: *seconds,
+                };
+
+                if duration.as_seconds() == 0 {
+                    return Err(());
+                }
+
+                Ok(Preset {
+                    duration,
+                    melody: match melody {
+                        0 => None,
+                        melody => Some(Melody::try_from(*melody)?),
+                    },
+                })
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl StorageValue for Preset {
+    fn to_bytes(&self) -> Array<u8> {
+        Array::from(*self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from(bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correctly_serializes() {
+        let preset = Preset {
+            duration: Time {
+                hours: 0,
+                minutes: 25,
+                seconds: 30,
+            },
+            melody: Some(Melody::Beep),
+        };
+
+        assert_eq!(Array::from(preset).as_ref(), [0, 25, 30, 2]);
+        assert_eq!(Preset::try_from([0, 25, 30, 2].as_ref()), Ok(preset));
+
+        let preset = Preset {
+            melody: None,
+            ..preset
+        };
+
+        assert_eq!(Array::from(preset).as_ref(), [0, 25, 30, 0]);
+        assert_eq!(Preset::try_from([0, 25, 30, 0].as_ref()), Ok(preset));
+
+        assert_eq!(preset.to_bytes().as_ref(), [0, 25, 30, 0]);
+        assert_eq!(Preset::from_bytes(&[0, 25, 30, 0]), Some(preset));
+        assert_eq!(Preset::from_bytes(&[]), None);
+    }
+
+    #[test]
+    fn fails_to_deserialize_invalid_preset() {
+        assert_eq!(Preset::try_from([0, 0, 0, 1].as_ref()), Err(()));
+        assert_eq!(Preset::try_from([0, 5, 0, 0xff].as_ref()), Err(()));
+        assert_eq!(Preset::try_from([0, 5, 0].as_ref()), Err(()));
+        assert_eq!(Preset::try_from([0, 5, 0, 1, 1].as_ref()), Err(()));
+    }
+
+    #[test]
+    fn validates_index() {
+        assert!(!Preset::is_valid_index(0));
+        assert!(Preset::is_valid_index(1));
+        assert!(Preset::is_valid_index(PRESETS_COUNT));
+        assert!(!Preset::is_valid_index(PRESETS_COUNT + 1));
+    }
+}
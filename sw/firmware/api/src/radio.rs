@@ -33,6 +33,7 @@ use radio::{
 use systick::{SysTick, SysTickHardware};
 
 /// Describes the Radio hardware management interface.
+#[allow(clippy::result_unit_err)]
 pub trait RadioHardware {
     /// Initializes hardware if needed.
     fn setup(&mut self, cs: &CriticalSection);
@@ -56,6 +57,7 @@ pub struct Radio<'a, T: RadioHardware, S: SysTickHardware> {
     config: ConfigRegister,
 }
 
+#[allow(clippy::result_unit_err)]
 impl<'a, T: RadioHardware, S: SysTickHardware> Radio<'a, T, S> {
     pub fn new(hw: &'a mut T, systick: &'a mut SysTick<S>) -> Self {
         Radio {
@@ -88,10 +90,7 @@ impl<'a, T: RadioHardware, S: SysTickHardware> Radio<'a, T, S> {
                 self.hw.teardown(cs);
                 Ok(payload)
             })
-            .or_else(|_| {
-                self.hw.teardown(cs);
-                Err(())
-            })
+            .map_err(|_| self.hw.teardown(cs))
     }
 
     pub fn transmit(&mut self, cs: &CriticalSection, data: Array<u8>) -> Result<(), ()> {
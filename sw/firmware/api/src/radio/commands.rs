@@ -26,9 +26,9 @@ pub enum Command {
     FlushRx,
 }
 
-impl Into<u8> for Command {
-    fn into(self) -> u8 {
-        match self {
+impl From<Command> for u8 {
+    fn from(command: Command) -> Self {
+        match command {
             Command::ReadRegister(address) => address,
             Command::WriteRegister(address) => address | 0b001_00000,
             Command::ReadRxPayload => 0b0110_0001,
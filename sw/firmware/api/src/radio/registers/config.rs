@@ -7,9 +7,9 @@ pub enum ConfigRegisterPrim {
     Receiver,
 }
 
-impl Into<bool> for ConfigRegisterPrim {
-    fn into(self) -> bool {
-        match self {
+impl From<ConfigRegisterPrim> for bool {
+    fn from(prim: ConfigRegisterPrim) -> Self {
+        match prim {
             ConfigRegisterPrim::Transmitter => false,
             ConfigRegisterPrim::Receiver => true,
         }
@@ -31,9 +31,9 @@ pub enum ConfigRegisterCRCO {
     OneByte,
 }
 
-impl Into<bool> for ConfigRegisterCRCO {
-    fn into(self) -> bool {
-        match self {
+impl From<ConfigRegisterCRCO> for bool {
+    fn from(crco: ConfigRegisterCRCO) -> Self {
+        match crco {
             ConfigRegisterCRCO::TwoBytes => true,
             ConfigRegisterCRCO::OneByte => false,
         }
@@ -81,7 +81,7 @@ impl ConfigRegister {
 
 impl Default for ConfigRegister {
     fn default() -> Self {
-        ConfigRegister { 0: [0x08] }
+        ConfigRegister([0x08])
     }
 }
 
@@ -97,6 +97,6 @@ impl Register for ConfigRegister {
     }
 
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self { 0: buffer }
+        Self(buffer)
     }
 }
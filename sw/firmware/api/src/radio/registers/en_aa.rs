@@ -25,7 +25,7 @@ impl AutoAcknowledgmentRegister {
 
 impl Default for AutoAcknowledgmentRegister {
     fn default() -> Self {
-        AutoAcknowledgmentRegister { 0: [0x3F] }
+        AutoAcknowledgmentRegister([0x3F])
     }
 }
 
@@ -41,6 +41,6 @@ impl Register for AutoAcknowledgmentRegister {
     }
 
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self { 0: buffer }
+        Self(buffer)
     }
 }
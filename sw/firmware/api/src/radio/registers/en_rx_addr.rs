@@ -25,7 +25,7 @@ impl RxPipesStatusesRegister {
 
 impl Default for RxPipesStatusesRegister {
     fn default() -> Self {
-        RxPipesStatusesRegister { 0: [0x03] }
+        RxPipesStatusesRegister([0x03])
     }
 }
 
@@ -41,6 +41,6 @@ impl Register for RxPipesStatusesRegister {
     }
 
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self { 0: buffer }
+        Self(buffer)
     }
 }
@@ -38,6 +38,6 @@ impl Register for FIFOStatusRegister {
     }
 
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self { 0: buffer }
+        Self(buffer)
     }
 }
@@ -27,7 +27,7 @@ impl RFCHRegister {
 
 impl Default for RFCHRegister {
     fn default() -> Self {
-        RFCHRegister { 0: [0x02] }
+        RFCHRegister([0x02])
     }
 }
 
@@ -43,6 +43,6 @@ impl Register for RFCHRegister {
     }
 
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self { 0: buffer }
+        Self(buffer)
     }
 }
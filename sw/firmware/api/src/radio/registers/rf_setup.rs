@@ -83,7 +83,7 @@ impl RFSetupRegister {
 
 impl Default for RFSetupRegister {
     fn default() -> Self {
-        RFSetupRegister { 0: [0x0E] }
+        RFSetupRegister([0x0E])
     }
 }
 
@@ -99,6 +99,6 @@ impl Register for RFSetupRegister {
     }
 
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self { 0: buffer }
+        Self(buffer)
     }
 }
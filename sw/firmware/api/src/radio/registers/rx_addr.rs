@@ -51,10 +51,7 @@ impl PipeAddress for Pipe5Register {
 pub struct RxPipeAddressRegister<TPipe: PipeAddress>([u8; 5], core::marker::PhantomData<TPipe>);
 impl<TPipe: PipeAddress> Default for RxPipeAddressRegister<TPipe> {
     fn default() -> Self {
-        RxPipeAddressRegister {
-            0: [u8::default(); MAX_REGISTER_VALUE_SIZE],
-            1: PhantomData,
-        }
+        RxPipeAddressRegister([u8::default(); MAX_REGISTER_VALUE_SIZE], PhantomData)
     }
 }
 
@@ -70,9 +67,6 @@ impl<TPipe: PipeAddress> Register for RxPipeAddressRegister<TPipe> {
     }
 
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self {
-            0: buffer,
-            1: PhantomData,
-        }
+        Self(buffer, PhantomData)
     }
 }
@@ -76,10 +76,7 @@ impl<TPipe: PipePayloadWidth> RxPipePayloadWidthRegister<TPipe> {
 
 impl<TPipe: PipePayloadWidth> Default for RxPipePayloadWidthRegister<TPipe> {
     fn default() -> Self {
-        RxPipePayloadWidthRegister {
-            0: [u8::default()],
-            1: PhantomData,
-        }
+        RxPipePayloadWidthRegister([u8::default()], PhantomData)
     }
 }
 
@@ -95,9 +92,6 @@ impl<TPipe: PipePayloadWidth> Register for RxPipePayloadWidthRegister<TPipe> {
     }
 
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self {
-            0: buffer,
-            1: PhantomData,
-        }
+        Self(buffer, PhantomData)
     }
 }
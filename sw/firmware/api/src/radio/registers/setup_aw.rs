@@ -11,11 +11,7 @@ pub enum SetupAWRegisterAW {
 
 impl SetupAWRegisterAW {
     pub fn is_illegal(&self) -> bool {
-        if let SetupAWRegisterAW::Illegal = self {
-            true
-        } else {
-            false
-        }
+        matches!(self, SetupAWRegisterAW::Illegal)
     }
 }
 
@@ -57,6 +53,6 @@ impl Register for SetupAWRegister {
     }
 
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self { 0: buffer }
+        Self(buffer)
     }
 }
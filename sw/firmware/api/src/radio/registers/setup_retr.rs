@@ -37,7 +37,7 @@ impl SetupRetrRegister {
 
 impl Default for SetupRetrRegister {
     fn default() -> Self {
-        SetupRetrRegister { 0: [0x03] }
+        SetupRetrRegister([0x03])
     }
 }
 
@@ -53,6 +53,6 @@ impl Register for SetupRetrRegister {
     }
 
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self { 0: buffer }
+        Self(buffer)
     }
 }
@@ -3,9 +3,7 @@ use super::{super::MAX_REGISTER_VALUE_SIZE, Register};
 pub struct TxAddrRegister([u8; MAX_REGISTER_VALUE_SIZE]);
 impl Default for TxAddrRegister {
     fn default() -> Self {
-        Self {
-            0: [0xE7, 0xE7, 0xE7, 0xE7, 0xE7],
-        }
+        Self([0xE7, 0xE7, 0xE7, 0xE7, 0xE7])
     }
 }
 
@@ -22,6 +20,6 @@ impl Register for TxAddrRegister {
 
     /// Set address field width.
     fn from_raw(buffer: Self::TRaw) -> Self {
-        Self { 0: buffer }
+        Self(buffer)
     }
 }
@@ -1,5 +1,8 @@
 use time::{BCDTime, Time};
 
+/// Number of seconds in a day, RTC time wraps around at midnight.
+const DAY_SECONDS: u32 = 24 * 60 * 60;
+
 /// Describes the RTC hardware management interface.
 pub trait RTCHardware {
     /// Initializes hardware if needed.
@@ -19,6 +22,9 @@ pub trait RTCHardware {
 
     /// Sets RTC Alarm in BCD format.
     fn set_alarm(&self, bcd_time: BCDTime);
+
+    /// Disables RTC Alarm and clears its pending flags, RTC clock keeps running.
+    fn disable_alarm(&self);
 }
 
 pub struct RTC<'a, T: RTCHardware> {
@@ -55,6 +61,25 @@ impl<'a, T: RTCHardware> RTC<'a, T> {
     pub fn set_time(&self, time: Time) {
         self.hw.set_time(time.into());
     }
+
+    /// Sets alarm to fire once specified amount of time elapses, current RTC time isn't changed.
+    pub fn set_alarm_after(&self, duration: Time) {
+        let mut alarm = self.time();
+        alarm.add_seconds(u32::from(duration.seconds));
+        alarm.add_minutes(u32::from(duration.minutes));
+        alarm.add_hours(u32::from(duration.hours));
+        self.set_alarm(alarm);
+    }
+
+    pub fn disable_alarm(&self) {
+        self.hw.disable_alarm()
+    }
+
+    /// Returns time left until alarm fires.
+    pub fn remaining(&self) -> Time {
+        let (time, alarm) = (self.time().as_seconds(), self.alarm().as_seconds());
+        Time::from_seconds((alarm + DAY_SECONDS - time) % DAY_SECONDS)
+    }
 }
 
 #[cfg(test)]
@@ -67,6 +92,7 @@ mod tests {
     enum Call {
         Setup,
         Teardown,
+        DisableAlarm,
     }
 
     #[derive(Default)]
@@ -117,6 +143,10 @@ mod tests {
         fn set_alarm(&self, bcd_time: BCDTime) {
             self.data.borrow_mut().data.alarm = bcd_time;
         }
+
+        fn disable_alarm(&self) {
+            self.data.borrow_mut().calls.log_call(Call::DisableAlarm);
+        }
     }
 
     #[test]
@@ -225,4 +255,66 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn set_alarm_after() {
+        let rtc_hw_mock = RTCHardwareMock {
+            data: RefCell::new(MockData::new(AssociatedData::default())),
+        };
+
+        RTC::new(&rtc_hw_mock).set_alarm_after(Time::from_minutes(10));
+        assert_eq!(
+            rtc_hw_mock.data.borrow().data.alarm,
+            BCDTime::from(Time {
+                hours: 13,
+                minutes: 44,
+                seconds: 51,
+            })
+        );
+
+        // Alarm rolls over after midnight.
+        RTC::new(&rtc_hw_mock).set_alarm_after(Time {
+            hours: 11,
+            minutes: 0,
+            seconds: 10,
+        });
+        assert_eq!(
+            rtc_hw_mock.data.borrow().data.alarm,
+            BCDTime::from(Time {
+                hours: 0,
+                minutes: 35,
+                seconds: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn remaining() {
+        let rtc_hw_mock = RTCHardwareMock {
+            data: RefCell::new(MockData::new(AssociatedData::default())),
+        };
+
+        assert_eq!(
+            RTC::new(&rtc_hw_mock).remaining(),
+            Time {
+                hours: 0,
+                minutes: 1,
+                seconds: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn disable_alarm() {
+        let rtc_hw_mock = RTCHardwareMock {
+            data: RefCell::new(MockData::new(AssociatedData::default())),
+        };
+
+        RTC::new(&rtc_hw_mock).disable_alarm();
+
+        assert_eq!(
+            rtc_hw_mock.data.borrow().calls.logs(),
+            [Some(Call::DisableAlarm)]
+        );
+    }
 }
@@ -0,0 +1,166 @@
+use array::Array;
+use buttons::ButtonPressType;
+use core::convert::TryFrom;
+use flash::{storage_key::StorageKey, storage_value::StorageValue, Flash, FlashHardware};
+use usb::commands::{KeyModifiers, MediaKey};
+
+/// Describes button press that can trigger a shortcut in Controller role or alarm that triggers
+/// it in hybrid Timer role (USB stays enumerated while countdown runs). There is a single `Alarm`
+/// shortcut that is sent for every alarm, no matter how alarm has been armed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ShortcutTrigger {
+    OneShort = 1,
+    OneLong = 2,
+    TenShort = 3,
+    TenLong = 4,
+    Alarm = 5,
+}
+
+impl ShortcutTrigger {
+    /// Returns trigger that corresponds to the press of one of the buttons, presses of both buttons
+    /// don't trigger shortcuts.
+    pub fn from_press((button_i, button_x): (ButtonPressType, ButtonPressType)) -> Option<Self> {
+        match (button_i, button_x) {
+            (ButtonPressType::Short, ButtonPressType::None) => Some(ShortcutTrigger::OneShort),
+            (ButtonPressType::Long, ButtonPressType::None) => Some(ShortcutTrigger::OneLong),
+            (ButtonPressType::None, ButtonPressType::Short) => Some(ShortcutTrigger::TenShort),
+            (ButtonPressType::None, ButtonPressType::Long) => Some(ShortcutTrigger::TenLong),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<u8> for ShortcutTrigger {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(ShortcutTrigger::OneShort),
+            2 => Ok(ShortcutTrigger::OneLong),
+            3 => Ok(ShortcutTrigger::TenShort),
+            4 => Ok(ShortcutTrigger::TenLong),
+            5 => Ok(ShortcutTrigger::Alarm),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Describes keyboard combo or media key that is sent to the host when shortcut is triggered.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shortcut {
+    Key(KeyModifiers, u8),
+    Media(MediaKey),
+}
+
+impl Shortcut {
+    /// Reads shortcut bound to the specified trigger from the flash, returns `None` if there is
+    /// no shortcut or it's been deleted.
+    pub fn read<T: FlashHardware>(flash: &Flash<T>, trigger: ShortcutTrigger) -> Option<Self> {
+        flash.read_value(StorageKey::Shortcut(trigger as u8).into())
+    }
+
+    /// Binds shortcut to the specified trigger and persists it in the flash.
+    #[allow(clippy::result_unit_err)]
+    pub fn write<T: FlashHardware>(
+        &self,
+        flash: &Flash<T>,
+        trigger: ShortcutTrigger,
+    ) -> Result<(), ()> {
+        flash.write_value(StorageKey::Shortcut(trigger as u8).into(), *self)
+    }
+
+    /// Removes shortcut bound to the specified trigger from the flash. Storage doesn't support
+    /// removal so shortcut is replaced with an empty value.
+    #[allow(clippy::result_unit_err)]
+    pub fn delete<T: FlashHardware>(flash: &Flash<T>, trigger: ShortcutTrigger) -> Result<(), ()> {
+        flash.write_bytes(StorageKey::Shortcut(trigger as u8).into(), &[])
+    }
+}
+
+/// Keyboard combo is serialized as `[1, modifiers, key code]` and media key as `[2, media key, 0]`.
+impl From<Shortcut> for Array<u8> {
+    fn from(shortcut: Shortcut) -> Self {
+        match shortcut {
+            Shortcut::Key(modifiers, key_code) => [1, modifiers.into(), key_code].as_ref().into(),
+            Shortcut::Media(media_key) => [2, media_key as u8, 0].as_ref().into(),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for Shortcut {
+    type Error = ();
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value {
+            [1, modifiers, key_code] if *key_code > 0 => {
+                Ok(Shortcut::Key(KeyModifiers::from(*modifiers), *key_code))
+            }
+            [2, media_key, 0] => MediaKey::try_from(*media_key).map(Shortcut::Media),
+            _ => Err(()),
+        }
+    }
+}
+
+impl StorageValue for Shortcut {
+    fn to_bytes(&self) -> Array<u8> {
+        Array::from(*self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from(bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correctly_serializes() {
+        let shortcut = Shortcut::Key(KeyModifiers::from(0b11), 0x10);
+        assert_eq!(Array::from(shortcut).as_ref(), [1, 0b11, 0x10]);
+        assert_eq!(Shortcut::try_from([1, 0b11, 0x10].as_ref()), Ok(shortcut));
+
+        let shortcut = Shortcut::Media(MediaKey::PlayPause);
+        assert_eq!(Array::from(shortcut).as_ref(), [2, 0x20, 0]);
+        assert_eq!(Shortcut::try_from([2, 0x20, 0].as_ref()), Ok(shortcut));
+
+        assert_eq!(shortcut.to_bytes().as_ref(), [2, 0x20, 0]);
+        assert_eq!(Shortcut::from_bytes(&[2, 0x20, 0]), Some(shortcut));
+        assert_eq!(Shortcut::from_bytes(&[]), None);
+    }
+
+    #[test]
+    fn fails_to_deserialize_invalid_shortcut() {
+        assert_eq!(Shortcut::try_from([0, 0, 0].as_ref()), Err(()));
+        assert_eq!(Shortcut::try_from([1, 0, 0].as_ref()), Err(()));
+        assert_eq!(Shortcut::try_from([2, 0x03, 0].as_ref()), Err(()));
+        assert_eq!(Shortcut::try_from([3, 0x01, 0].as_ref()), Err(()));
+        assert_eq!(Shortcut::try_from([1, 0, 0x10, 0].as_ref()), Err(()));
+    }
+
+    #[test]
+    fn detects_trigger() {
+        assert_eq!(
+            ShortcutTrigger::from_press((ButtonPressType::Short, ButtonPressType::None)),
+            Some(ShortcutTrigger::OneShort)
+        );
+        assert_eq!(
+            ShortcutTrigger::from_press((ButtonPressType::None, ButtonPressType::Long)),
+            Some(ShortcutTrigger::TenLong)
+        );
+        assert_eq!(
+            ShortcutTrigger::from_press((ButtonPressType::Short, ButtonPressType::Short)),
+            None
+        );
+        assert_eq!(
+            ShortcutTrigger::from_press((ButtonPressType::VeryLong, ButtonPressType::None)),
+            None
+        );
+
+        assert_eq!(ShortcutTrigger::try_from(2), Ok(ShortcutTrigger::OneLong));
+        assert_eq!(ShortcutTrigger::try_from(5), Ok(ShortcutTrigger::Alarm));
+        assert_eq!(ShortcutTrigger::try_from(0), Err(()));
+        assert_eq!(ShortcutTrigger::try_from(6), Err(()));
+    }
+}
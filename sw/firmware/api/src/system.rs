@@ -5,19 +5,25 @@ mod system_state;
 
 use adc::ADC;
 use bare_metal::CriticalSection;
-use beeper::PWMBeeper;
-use buttons::{Buttons, ButtonsPoll};
+use beeper::{stored_melody, volume::BeeperConfig, PWMBeeper};
+use buttons::{Buttons, ButtonsConfig, ButtonsPoll};
 use flash::{storage_slot::StorageSlot, Flash};
 use radio::Radio;
 use rtc::RTC;
 use systick::{SysTick, SysTickHardware};
+use time::Time;
 use timer::Timer;
 use usb::USB;
 
-pub use self::{system_hardware::SystemHardware, system_info::SystemInfo};
+pub use self::{
+    system_hardware::SystemHardware, system_info::SystemInfo, system_role::PomodoroConfig,
+};
 use self::{
-    system_role::{ControllerSystemRoleHandler, SystemRole, TimerSystemRoleHandler},
-    system_state::SystemState,
+    system_role::{
+        ControllerSystemRoleHandler, PomodoroSystemRoleHandler, SystemRole, TimerRoleMode,
+        TimerSystemRoleHandler,
+    },
+    system_state::{RoleState, SystemState},
 };
 
 pub struct System<T: SystemHardware, S: SysTickHardware> {
@@ -34,41 +40,65 @@ impl<T: SystemHardware, S: SysTickHardware> System<T, S> {
             systick,
         };
 
-        system.switch_to_role(
-            system
-                .flash()
-                .read(StorageSlot::Configuration)
-                .map_or_else(SystemRole::default, SystemRole::from),
-        );
+        // RTC keeps running all the time to track wall-clock time, roles only manage its alarm.
+        system.rtc().setup();
+
+        // Storage is recovered and upgraded only once, so that regular reads never write to flash.
+        // Device can still operate with the default configuration if storage can't be set up.
+        system.flash().setup().ok();
+
+        system.state.peripherals_states.buttons.config = ButtonsConfig::read(&system.flash());
+        system.state.peripherals_states.beeper.config = BeeperConfig::read(&system.flash());
+        system.state.peripherals_states.beeper.stored_melody = stored_melody::read(&system.flash());
+
+        let boot_role = system.boot_role();
+        system.switch_to_role(boot_role);
 
         system
     }
 
     pub fn handle_alarm(&mut self) {
-        if let SystemRole::Timer = self.state.role {
-            TimerSystemRoleHandler::on_alarm(self);
+        // Alarm fires only once, role handlers re-arm it if needed.
+        self.rtc().disable_alarm();
+
+        match self.state.role {
+            SystemRole::Timer => TimerSystemRoleHandler::on_alarm(self),
+            SystemRole::Pomodoro => PomodoroSystemRoleHandler::on_alarm(self),
+            SystemRole::Controller => {}
         }
     }
 
     pub fn handle_button_press(&mut self) {
         // If buttons weren't activated, don't do anything.
-        let buttons = self.buttons();
+        let mut buttons = self.buttons();
         if !buttons.triggered() {
             return;
         }
 
-        // If buttons are in the middle of the polling, reactivate them and let current polling complete.
+        // If buttons are in the middle of the polling, remember which of them were triggered (e.g.
+        // to detect double click), reactivate them and let current polling complete.
         if buttons.is_polling() {
+            buttons.track_triggers();
             buttons.reactivate();
             return;
         }
 
+        // New poll waits only for the gestures that are bound in the current role and mode.
+        self.state.peripherals_states.buttons.gestures = match self.state.role {
+            SystemRole::Timer => TimerSystemRoleHandler::gestures(self),
+            SystemRole::Controller => ControllerSystemRoleHandler::gestures(),
+            SystemRole::Pomodoro => PomodoroSystemRoleHandler::gestures(self),
+        };
+
         self.poll_buttons();
     }
 
     pub fn handle_usb_packet(&mut self, cs: &CriticalSection) {
-        if let SystemRole::Controller = self.state.role {
-            ControllerSystemRoleHandler::on_usb_packet(self, cs);
+        match (self.state.role, self.state.is_usb_hybrid) {
+            (SystemRole::Controller, _) | (_, true) => {
+                ControllerSystemRoleHandler::on_usb_packet(self, cs)
+            }
+            _ => {}
         }
     }
 
@@ -98,13 +128,33 @@ impl<T: SystemHardware, S: SysTickHardware> System<T, S> {
     pub fn sleep(&mut self) {
         match (
             self.state.role,
-            self.beeper().is_playing() || self.buttons().is_polling(),
+            self.state.is_usb_hybrid || self.beeper().is_playing() || self.buttons().is_polling(),
         ) {
             (SystemRole::Controller, _) | (_, true) => self.hw.exit_deep_sleep(),
             _ => self.hw.enter_deep_sleep(),
         }
     }
 
+    /// Returns the time of the day alarm is set to if device is in Timer role and alarm is pending,
+    /// no matter whether it has been armed with the buttons or via USB. Snoozed and recurring
+    /// alarms are pending as well.
+    pub fn pending_alarm(&self) -> Option<Time> {
+        match self.state.role_state {
+            Some(RoleState::Timer(TimerRoleMode::Alarm(_, _)))
+            | Some(RoleState::Timer(TimerRoleMode::AlarmAt(_)))
+            | Some(RoleState::Timer(TimerRoleMode::Recurring(_)))
+            | Some(RoleState::Timer(TimerRoleMode::Snooze(_))) => Some(self.rtc().alarm()),
+            _ => None,
+        }
+    }
+
+    /// Returns role system boots into, it's configured via `Configuration` flash slot.
+    fn boot_role(&self) -> SystemRole {
+        self.flash()
+            .read(StorageSlot::Configuration)
+            .map_or_else(SystemRole::default, SystemRole::from)
+    }
+
     /// Performs system software reset.
     fn reset(&mut self) {
         self.hw.reset();
@@ -120,6 +170,9 @@ impl<T: SystemHardware, S: SysTickHardware> System<T, S> {
                     SystemRole::Controller => {
                         ControllerSystemRoleHandler::on_buttons_press(self, (button_i, button_x))
                     }
+                    SystemRole::Pomodoro => {
+                        PomodoroSystemRoleHandler::on_buttons_press(self, (button_i, button_x))
+                    }
                 };
             }
             ButtonsPoll::Pending(pending_time) => self.timer().start(pending_time),
@@ -130,36 +183,67 @@ impl<T: SystemHardware, S: SysTickHardware> System<T, S> {
 
     /// Switches system to a new role.
     fn switch_to_role(&mut self, role: SystemRole) {
+        let is_usb_hybrid = self.state.is_usb_hybrid;
+
         self.state.role_state = None;
         self.state.role = role;
+        self.state.is_usb_hybrid = false;
 
         match self.state.role {
-            SystemRole::Timer => self.usb().teardown(),
+            SystemRole::Timer | SystemRole::Pomodoro => self.usb().teardown(),
+            // USB is still enumerated if we're coming from hybrid Timer role.
+            SystemRole::Controller if is_usb_hybrid => {}
             SystemRole::Controller => self.usb().setup(),
         };
     }
 
+    /// Switches system from Controller to Timer role keeping USB enumerated. Device can't enter deep
+    /// sleep in this mode, so it lasts only until alarm fires.
+    fn switch_to_hybrid_timer_role(&mut self) {
+        self.state.role_state = None;
+        self.state.role = SystemRole::Timer;
+        self.state.is_usb_hybrid = true;
+    }
+
+    /// Connects to the host while device stays in Timer role (hybrid mode), so that alarm action
+    /// can be sent once alarm fires.
+    fn enter_usb_hybrid_mode(&mut self) {
+        if !self.state.is_usb_hybrid {
+            self.usb().setup();
+            self.state.is_usb_hybrid = true;
+        }
+    }
+
+    /// Disconnects from the host if device is in hybrid Timer role, so that it can enter deep sleep
+    /// again. Role and its state are left intact.
+    fn leave_usb_hybrid_mode(&mut self) {
+        if self.state.is_usb_hybrid {
+            self.usb().teardown();
+            self.state.is_usb_hybrid = false;
+        }
+    }
+
     /// Creates an instance of `ADC` controller.
-    fn adc(&self) -> ADC<T> {
+    fn adc(&self) -> ADC<'_, T> {
         ADC::new(&self.hw)
     }
 
     /// Creates an instance of `RTC` controller.
-    fn rtc(&self) -> RTC<T> {
+    fn rtc(&self) -> RTC<'_, T> {
         RTC::new(&self.hw)
     }
 
     /// Creates an instance of `ADC` controller.
-    fn radio(&mut self) -> Radio<T, S> {
+    fn radio(&mut self) -> Radio<'_, T, S> {
         Radio::new(&mut self.hw, &mut self.systick)
     }
 
-    fn timer(&self) -> Timer<T> {
+    fn timer(&self) -> Timer<'_, T> {
         Timer::new(&self.hw)
     }
 
     /// Creates an instance of `Beeper` controller.
-    fn beeper(&mut self) -> PWMBeeper<T, S> {
+    fn beeper(&mut self) -> PWMBeeper<'_, T, S> {
         PWMBeeper::new(
             &self.hw,
             &mut self.systick,
@@ -168,17 +252,17 @@ impl<T: SystemHardware, S: SysTickHardware> System<T, S> {
     }
 
     /// Creates an instance of `Buttons` controller.
-    fn buttons(&mut self) -> Buttons<T> {
+    fn buttons(&mut self) -> Buttons<'_, T> {
         Buttons::new(&self.hw, &mut self.state.peripherals_states.buttons)
     }
 
     /// Creates an instance of `Flash` controller.
-    fn flash(&self) -> Flash<T> {
+    fn flash(&self) -> Flash<'_, T> {
         Flash::new(&self.hw)
     }
 
     /// Creates an instance of `USB` controller.
-    fn usb(&mut self) -> USB<T> {
+    fn usb(&mut self) -> USB<'_, T> {
         USB::new(&self.hw, &mut self.state.peripherals_states.usb)
     }
 }
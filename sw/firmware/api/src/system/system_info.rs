@@ -11,11 +11,11 @@ pub struct SystemInfo {
     pub flash_size_kb: u16,
 }
 
-impl Into<Array<u8>> for SystemInfo {
-    fn into(self) -> Array<u8> {
-        let mut array = Array::from(&self.id);
-        array.push((self.flash_size_kb & 0x00ff) as u8);
-        array.push(((self.flash_size_kb & 0xff00) >> 8) as u8);
+impl From<SystemInfo> for Array<u8> {
+    fn from(info: SystemInfo) -> Self {
+        let mut array = Array::from(&info.id);
+        array.push((info.flash_size_kb & 0x00ff) as u8);
+        array.push(((info.flash_size_kb & 0xff00) >> 8) as u8);
         array
     }
 }
@@ -1,27 +1,26 @@
 mod controller_system_role_handler;
+mod pomodoro_system_role_handler;
 mod timer_system_role_handler;
 
 pub use self::{
     controller_system_role_handler::ControllerSystemRoleHandler,
+    pomodoro_system_role_handler::{PomodoroConfig, PomodoroRoleMode, PomodoroSystemRoleHandler},
     timer_system_role_handler::{TimerRoleMode, TimerSystemRoleHandler},
 };
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub enum SystemRole {
+    #[default]
     Timer = 0x0,
     Controller = 0x1,
-}
-
-impl Default for SystemRole {
-    fn default() -> Self {
-        SystemRole::Timer
-    }
+    Pomodoro = 0x2,
 }
 
 impl From<u8> for SystemRole {
     fn from(value: u8) -> Self {
         match value {
             0x1 => SystemRole::Controller,
+            0x2 => SystemRole::Pomodoro,
             _ => SystemRole::default(),
         }
     }
@@ -1,14 +1,24 @@
-use super::super::{system_role::SystemRole, System, SystemHardware, SystemInfo};
+use super::super::{
+    system_role::{PomodoroConfig, SystemRole, TimerRoleMode, TimerSystemRoleHandler},
+    System, SystemHardware, SystemInfo,
+};
+use alarm::{EscalationConfig, SnoozeConfig};
 use array::Array;
 use bare_metal::CriticalSection;
-use beeper::melody::Melody;
-use buttons::ButtonPressType;
+use beeper::{
+    melody::Melody,
+    morse,
+    stored_melody::{self, StoredMelodyConfig},
+};
+use buttons::{ButtonPressType, ButtonsGestures};
+use preset::Preset;
+use shortcut::{Shortcut, ShortcutTrigger};
 use systick::SysTickHardware;
 use usb::{
     command_packet::CommandPacket,
     commands::{
-        ADCCommand, AlarmCommand, BeeperCommand, FlashCommand, KeyboardCommand, RadioCommand,
-        SystemCommand,
+        ADCCommand, AlarmCommand, BeeperCommand, FlashCommand, KeyModifiers, KeyboardCommand,
+        MediaKey, RadioCommand, SystemCommand, MELODY_QUEUE_FULL,
     },
     endpoint::DeviceEndpoint,
 };
@@ -21,21 +31,108 @@ impl ControllerSystemRoleHandler {
     ) {
         system.usb().interrupt();
 
-        match system.state.peripherals_states.usb.command {
-            Some(CommandPacket::Beeper(command)) => {
-                match command {
-                    BeeperCommand::Beep(n_beeps) => {
-                        system
-                            .beeper()
-                            .play_and_repeat(Melody::Beep, n_beeps as usize);
-                    }
-                    BeeperCommand::Melody(tones) => {
-                        system.beeper().play(Melody::Custom(tones));
-                    }
-                };
-
-                system.usb().send(DeviceEndpoint::System, &[0x00]);
+        // In hybrid Timer role device stays connected to the host only to let it control pending
+        // alarm, the rest of the commands are available in Controller role only.
+        if system.state.is_usb_hybrid {
+            match system.state.peripherals_states.usb.command {
+                Some(CommandPacket::Alarm(command)) if command.is_alarm_control() => {}
+                Some(_) => {
+                    system.usb().send(DeviceEndpoint::System, &[0xFF]);
+                    system.state.peripherals_states.usb.command = None;
+                    return;
+                }
+                None => {}
             }
+        }
+
+        match system.state.peripherals_states.usb.command {
+            Some(CommandPacket::Beeper(command)) => match command {
+                BeeperCommand::Beep(n_beeps) => {
+                    system
+                        .beeper()
+                        .play_and_repeat(Melody::Beep, n_beeps as usize);
+                    system.usb().send(DeviceEndpoint::System, &[0x00]);
+                }
+                BeeperCommand::Melody(tones) => {
+                    system.beeper().play(Melody::Custom(tones));
+                    system.usb().send(DeviceEndpoint::System, &[0x00]);
+                }
+                BeeperCommand::ClearStoredMelody => {
+                    stored_melody::clear(&system.flash());
+                    system.state.peripherals_states.beeper.stored_melody = Array::new();
+                    system.usb().send(DeviceEndpoint::System, &[0x00]);
+                }
+                BeeperCommand::AppendStoredMelody(tones) => {
+                    let status = match stored_melody::append(&system.flash(), tones.as_ref()) {
+                        Ok(melody) => {
+                            system.state.peripherals_states.beeper.stored_melody = melody;
+                            0x00
+                        }
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                }
+                BeeperCommand::GetStoredMelodyConfig => {
+                    let config = StoredMelodyConfig::read(&system.flash());
+                    let n_tones = system.state.peripherals_states.beeper.stored_melody.len();
+                    system.usb().send(
+                        DeviceEndpoint::System,
+                        &[0x00, config.into(), n_tones as u8],
+                    );
+                }
+                BeeperCommand::SetStoredMelodyConfig(config) => {
+                    let status = match config.write(&system.flash()) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                }
+                BeeperCommand::BeginMelody => {
+                    system.beeper().stop();
+                    system.usb().send(DeviceEndpoint::System, &[0x00]);
+                }
+                BeeperCommand::AppendMelody(tones) => {
+                    let mut beeper = system.beeper();
+                    let status = match beeper.enqueue(tones.as_ref()) {
+                        Ok(_) => 0x00,
+                        Err(_) => {
+                            // Start playing what's buffered to free up room for the re-sent chunk.
+                            beeper.play_queued();
+                            MELODY_QUEUE_FULL
+                        }
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                }
+                BeeperCommand::CommitMelody => {
+                    system.beeper().play_queued();
+                    system.usb().send(DeviceEndpoint::System, &[0x00]);
+                }
+                BeeperCommand::GetConfig => {
+                    let mut array = Array::from(system.state.peripherals_states.beeper.config);
+                    array.unshift(0x00);
+                    system.usb().send(DeviceEndpoint::System, array.as_ref());
+                }
+                BeeperCommand::Morse(wpm, pitch, text) => {
+                    let status = match morse::encode(text.as_ref(), wpm, pitch) {
+                        Ok(tones) => {
+                            system.beeper().play(tones);
+                            0x00
+                        }
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                }
+                BeeperCommand::SetConfig(config) => {
+                    let status = match config.write(&system.flash()) {
+                        Ok(_) => {
+                            system.state.peripherals_states.beeper.config = config;
+                            0x00
+                        }
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                }
+            },
             Some(CommandPacket::Alarm(command)) => {
                 if let AlarmCommand::Get = command {
                     let alarm = system.rtc().alarm();
@@ -43,11 +140,95 @@ impl ControllerSystemRoleHandler {
                         DeviceEndpoint::System,
                         &[0x00, alarm.hours, alarm.minutes, alarm.seconds],
                     );
-                } else if let AlarmCommand::Set(_time) = command {
-                    // We should send OK response before we enter Alarm mode and USB will be disabled.
-                    system.usb().send(DeviceEndpoint::System, &[0x00]);
-                    system.systick.delay(100);
-                    system.usb().send(DeviceEndpoint::System, &[0xFF]);
+                } else if let AlarmCommand::GetRemaining = command {
+                    // RTC alarm may be stale if there is no pending alarm.
+                    if system.pending_alarm().is_some() {
+                        let remaining = system.rtc().remaining();
+                        system.usb().send(
+                            DeviceEndpoint::System,
+                            &[0x00, remaining.hours, remaining.minutes, remaining.seconds],
+                        );
+                    } else {
+                        system.usb().send(DeviceEndpoint::System, &[0xFF]);
+                    }
+                } else if let AlarmCommand::GetPending = command {
+                    if let Some(alarm) = system.pending_alarm() {
+                        system.usb().send(
+                            DeviceEndpoint::System,
+                            &[0x00, alarm.hours, alarm.minutes, alarm.seconds],
+                        );
+                    } else {
+                        system.usb().send(DeviceEndpoint::System, &[0xFF]);
+                    }
+                } else if let AlarmCommand::Pause = command {
+                    let status = match TimerSystemRoleHandler::pause(system) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                } else if let AlarmCommand::Resume = command {
+                    let status = match TimerSystemRoleHandler::resume(system) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                } else if let AlarmCommand::Extend(minutes) = command {
+                    let status = match TimerSystemRoleHandler::extend(system, minutes) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                } else if let AlarmCommand::Cancel = command {
+                    let status = match TimerSystemRoleHandler::cancel(system) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                } else if let AlarmCommand::Set(time) = command {
+                    Self::arm_alarm(system, TimerRoleMode::Alarm(time, Melody::Alarm));
+                } else if let AlarmCommand::SetRecurring(time) = command {
+                    Self::arm_alarm(system, TimerRoleMode::Recurring(time));
+                } else if let AlarmCommand::SetAt(time) = command {
+                    Self::arm_alarm(system, TimerRoleMode::AlarmAt(time));
+                } else if let AlarmCommand::GetSnooze = command {
+                    let mut array = Array::from(SnoozeConfig::read(&system.flash()));
+                    array.unshift(0x00);
+                    system.usb().send(DeviceEndpoint::System, array.as_ref());
+                } else if let AlarmCommand::SetSnooze(config) = command {
+                    let status = match config.write(&system.flash()) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                } else if let AlarmCommand::GetEscalation = command {
+                    let mut array = Array::from(EscalationConfig::read(&system.flash()));
+                    array.unshift(0x00);
+                    system.usb().send(DeviceEndpoint::System, array.as_ref());
+                } else if let AlarmCommand::SetEscalation(config) = command {
+                    let status = match config.write(&system.flash()) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                } else if let AlarmCommand::GetPreset(index) = command {
+                    // Missing preset is reported with zero duration and melody.
+                    let mut array = Preset::read(&system.flash(), index)
+                        .map(Array::from)
+                        .unwrap_or_else(|| [0, 0, 0, 0].as_ref().into());
+                    array.unshift(0x00);
+                    system.usb().send(DeviceEndpoint::System, array.as_ref());
+                } else if let AlarmCommand::SetPreset(index, preset) = command {
+                    let status = match preset.write(&system.flash(), index) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                } else if let AlarmCommand::DeletePreset(index) = command {
+                    let status = match Preset::delete(&system.flash(), index) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
                 } else {
                     system.usb().send(DeviceEndpoint::System, &[0xFF]);
                 }
@@ -71,6 +252,38 @@ impl ControllerSystemRoleHandler {
                     .into();
                     array.unshift(0x00);
                     system.usb().send(DeviceEndpoint::System, array.as_ref());
+                } else if let SystemCommand::GetTime = command {
+                    let time = system.rtc().time();
+                    system.usb().send(
+                        DeviceEndpoint::System,
+                        &[0x00, time.hours, time.minutes, time.seconds],
+                    );
+                } else if let SystemCommand::SetTime(time) = command {
+                    system.rtc().set_time(time);
+                    system.usb().send(DeviceEndpoint::System, &[0x00]);
+                } else if let SystemCommand::GetButtonsConfig = command {
+                    let mut array = Array::from(system.state.peripherals_states.buttons.config);
+                    array.unshift(0x00);
+                    system.usb().send(DeviceEndpoint::System, array.as_ref());
+                } else if let SystemCommand::SetButtonsConfig(config) = command {
+                    let status = match config.write(&system.flash()) {
+                        Ok(_) => {
+                            system.state.peripherals_states.buttons.config = config;
+                            0x00
+                        }
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                } else if let SystemCommand::GetPomodoroConfig = command {
+                    let mut array = Array::from(PomodoroConfig::read(&system.flash()));
+                    array.unshift(0x00);
+                    system.usb().send(DeviceEndpoint::System, array.as_ref());
+                } else if let SystemCommand::SetPomodoroConfig(config) = command {
+                    let status = match config.write(&system.flash()) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
                 } else {
                     system.usb().send(DeviceEndpoint::System, &[0xFF]);
                 }
@@ -78,16 +291,14 @@ impl ControllerSystemRoleHandler {
             Some(CommandPacket::Flash(command)) => {
                 let response = match command {
                     FlashCommand::Read(storage_slot) => Ok(Array::from(
-                        [system.flash().read(storage_slot).unwrap_or_else(|| 0)].as_ref(),
+                        [system.flash().read(storage_slot).unwrap_or(0)].as_ref(),
                     )),
                     FlashCommand::Write(storage_slot, value) => system
                         .flash()
                         .write(storage_slot, value)
                         .map(|_| Array::new()),
-                    FlashCommand::EraseAll => {
-                        system.flash().erase_all();
-                        Ok(Array::new())
-                    }
+                    FlashCommand::EraseAll => system.flash().erase_all().map(|_| Array::new()),
+                    FlashCommand::Stats => system.flash().storage_stats().map(Array::from),
                 };
 
                 match response {
@@ -131,14 +342,7 @@ impl ControllerSystemRoleHandler {
                         system.systick.delay(delay as u32 * 1000);
                     }
 
-                    system.usb().send(
-                        DeviceEndpoint::Keyboard,
-                        &[0x01, modifiers.into(), 0, key_code, 0, 0, 0, 0, 0],
-                    );
-                    system.systick.delay(10);
-                    system
-                        .usb()
-                        .send(DeviceEndpoint::Keyboard, &[0x01, 0, 0, 0, 0, 0, 0, 0, 0]);
+                    Self::send_key(system, modifiers, key_code);
 
                     system.usb().send(DeviceEndpoint::System, &[0x00]);
                 }
@@ -147,14 +351,32 @@ impl ControllerSystemRoleHandler {
                         system.systick.delay(delay as u32 * 1000);
                     }
 
-                    system
-                        .usb()
-                        .send(DeviceEndpoint::Keyboard, &[0x02, key_code as u8]);
-                    system.systick.delay(10);
-                    system.usb().send(DeviceEndpoint::Keyboard, &[0x02, 0x0]);
+                    Self::send_media_key(system, key_code);
 
                     system.usb().send(DeviceEndpoint::System, &[0x00]);
                 }
+                KeyboardCommand::GetShortcut(trigger) => {
+                    // Missing shortcut is reported with zero type and codes.
+                    let mut array = Shortcut::read(&system.flash(), trigger)
+                        .map(Array::from)
+                        .unwrap_or_else(|| [0, 0, 0].as_ref().into());
+                    array.unshift(0x00);
+                    system.usb().send(DeviceEndpoint::System, array.as_ref());
+                }
+                KeyboardCommand::SetShortcut(trigger, shortcut) => {
+                    let status = match shortcut.write(&system.flash(), trigger) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                }
+                KeyboardCommand::DeleteShortcut(trigger) => {
+                    let status = match Shortcut::delete(&system.flash(), trigger) {
+                        Ok(_) => 0x00,
+                        Err(_) => 0xFF,
+                    };
+                    system.usb().send(DeviceEndpoint::System, &[status]);
+                }
             },
             _ => {}
         }
@@ -162,16 +384,93 @@ impl ControllerSystemRoleHandler {
         system.state.peripherals_states.usb.command = None;
     }
 
+    /// Returns gestures bound in Controller role: shortcuts don't use double click and very long
+    /// press on both buttons leaves the role.
+    pub fn gestures() -> ButtonsGestures {
+        ButtonsGestures {
+            double_click: false,
+            very_long_press: true,
+        }
+    }
+
     pub fn on_buttons_press<T: SystemHardware, S: SysTickHardware>(
         system: &mut System<T, S>,
         buttons_press_type: (ButtonPressType, ButtonPressType),
     ) {
-        if let (ButtonPressType::Long, ButtonPressType::Long) = buttons_press_type {
+        if let (ButtonPressType::VeryLong, ButtonPressType::VeryLong) = buttons_press_type {
             system.beeper().play(Melody::Reset);
 
             system.usb().teardown();
 
+            // Get back to the role device boots into, unless it's Controller role itself.
+            let role = match system.boot_role() {
+                SystemRole::Controller => SystemRole::Timer,
+                role => role,
+            };
+            system.switch_to_role(role);
+        } else if let Some(trigger) = ShortcutTrigger::from_press(buttons_press_type) {
+            if let Some(shortcut) = Shortcut::read(&system.flash(), trigger) {
+                Self::send_shortcut(system, shortcut);
+            }
+        }
+    }
+
+    /// Sends keyboard combo or media key to the host via Keyboard endpoint.
+    pub fn send_shortcut<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+        shortcut: Shortcut,
+    ) {
+        match shortcut {
+            Shortcut::Key(modifiers, key_code) => Self::send_key(system, modifiers, key_code),
+            Shortcut::Media(media_key) => Self::send_media_key(system, media_key),
+        }
+    }
+
+    /// Switches to Timer role and arms alarm. If alarm action is configured USB stays enumerated
+    /// (hybrid mode) until alarm fires, so that alarm can be controlled via USB and action can be
+    /// sent to the host. Disconnecting first would just make device enumerate again.
+    fn arm_alarm<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+        mode: TimerRoleMode,
+    ) {
+        // We should send OK response before we enter Alarm mode and USB may be disabled.
+        system.usb().send(DeviceEndpoint::System, &[0x00]);
+
+        if TimerSystemRoleHandler::has_alarm_action(system) {
+            system.switch_to_hybrid_timer_role();
+        } else {
+            system.systick.delay(100);
             system.switch_to_role(SystemRole::Timer);
         }
+
+        TimerSystemRoleHandler::set_mode(system, mode);
+    }
+
+    /// Sends key press report followed by the key release one.
+    fn send_key<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+        modifiers: KeyModifiers,
+        key_code: u8,
+    ) {
+        system.usb().send(
+            DeviceEndpoint::Keyboard,
+            &[0x01, modifiers.into(), 0, key_code, 0, 0, 0, 0, 0],
+        );
+        system.systick.delay(10);
+        system
+            .usb()
+            .send(DeviceEndpoint::Keyboard, &[0x01, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    /// Sends media key press report followed by the key release one.
+    fn send_media_key<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+        media_key: MediaKey,
+    ) {
+        system
+            .usb()
+            .send(DeviceEndpoint::Keyboard, &[0x02, media_key as u8]);
+        system.systick.delay(10);
+        system.usb().send(DeviceEndpoint::Keyboard, &[0x02, 0x0]);
     }
 }
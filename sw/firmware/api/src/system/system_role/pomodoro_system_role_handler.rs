@@ -0,0 +1,303 @@
+use super::super::{system_role::SystemRole, system_state::RoleState, System, SystemHardware};
+use array::Array;
+use beeper::melody::Melody;
+use buttons::{ButtonPressType, ButtonsGestures};
+use core::convert::TryFrom;
+use flash::{storage_key::StorageKey, storage_value::StorageValue, Flash, FlashHardware};
+use systick::SysTickHardware;
+use time::Time;
+
+/// Describes Pomodoro phase, nested value is the index of the current work cycle (starts from 1).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PomodoroRoleMode {
+    Idle,
+    Work(u8),
+    ShortBreak(u8),
+    LongBreak,
+}
+
+/// Pomodoro intervals (in minutes) and number of work cycles before the long break.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PomodoroConfig {
+    pub work: u8,
+    pub short_break: u8,
+    pub long_break: u8,
+    pub cycles: u8,
+}
+
+impl PomodoroConfig {
+    /// Reads config from the flash falling back to the default one if it's missing or invalid.
+    pub fn read<T: FlashHardware>(flash: &Flash<T>) -> Self {
+        flash
+            .read_value(StorageKey::PomodoroConfig.into())
+            .unwrap_or_default()
+    }
+
+    /// Persists config in the flash.
+    #[allow(clippy::result_unit_err)]
+    pub fn write<T: FlashHardware>(&self, flash: &Flash<T>) -> Result<(), ()> {
+        if !self.is_valid() {
+            return Err(());
+        }
+
+        flash.write_value(StorageKey::PomodoroConfig.into(), *self)
+    }
+
+    /// Returns mode that follows the specified one.
+    pub fn next_mode(&self, mode: PomodoroRoleMode) -> PomodoroRoleMode {
+        match mode {
+            PomodoroRoleMode::Idle | PomodoroRoleMode::LongBreak => PomodoroRoleMode::Work(1),
+            PomodoroRoleMode::Work(cycle) if cycle >= self.cycles => PomodoroRoleMode::LongBreak,
+            PomodoroRoleMode::Work(cycle) => PomodoroRoleMode::ShortBreak(cycle),
+            PomodoroRoleMode::ShortBreak(cycle) => PomodoroRoleMode::Work(cycle + 1),
+        }
+    }
+
+    /// Returns duration of the specified mode.
+    fn duration(&self, mode: PomodoroRoleMode) -> Option<Time> {
+        match mode {
+            PomodoroRoleMode::Idle => None,
+            PomodoroRoleMode::Work(_) => Some(Time::from_minutes(u32::from(self.work))),
+            PomodoroRoleMode::ShortBreak(_) => {
+                Some(Time::from_minutes(u32::from(self.short_break)))
+            }
+            PomodoroRoleMode::LongBreak => Some(Time::from_minutes(u32::from(self.long_break))),
+        }
+    }
+
+    /// Checks whether all intervals and number of cycles are positive.
+    fn is_valid(&self) -> bool {
+        self.work > 0 && self.short_break > 0 && self.long_break > 0 && self.cycles > 0
+    }
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        PomodoroConfig {
+            work: 25,
+            short_break: 5,
+            long_break: 15,
+            cycles: 4,
+        }
+    }
+}
+
+impl From<PomodoroConfig> for Array<u8> {
+    fn from(config: PomodoroConfig) -> Self {
+        [
+            config.work,
+            config.short_break,
+            config.long_break,
+            config.cycles,
+        ]
+        .as_ref()
+        .into()
+    }
+}
+
+impl TryFrom<&[u8]> for PomodoroConfig {
+    type Error = ();
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value {
+            [work, short_break, long_break, cycles] => {
+                let config = PomodoroConfig {
+                    work: *work,
+                    short_break: *short_break,
+                    long_break: *long_break,
+                    cycles: *cycles,
+                };
+
+                if config.is_valid() {
+                    Ok(config)
+                } else {
+                    Err(())
+                }
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl StorageValue for PomodoroConfig {
+    fn to_bytes(&self) -> Array<u8> {
+        Array::from(*self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from(bytes).ok()
+    }
+}
+
+pub struct PomodoroSystemRoleHandler;
+impl PomodoroSystemRoleHandler {
+    pub fn on_buttons_press<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+        (button_i, button_x): (ButtonPressType, ButtonPressType),
+    ) {
+        let current_mode = Self::current_mode(system);
+        match (current_mode, button_i, button_x) {
+            (PomodoroRoleMode::Idle, ButtonPressType::VeryLong, ButtonPressType::VeryLong) => {
+                system.switch_to_role(SystemRole::Controller)
+            }
+            (PomodoroRoleMode::Idle, ButtonPressType::Long, _)
+            | (PomodoroRoleMode::Idle, _, ButtonPressType::Long) => {
+                Self::set_mode(system, PomodoroRoleMode::Work(1));
+            }
+            (PomodoroRoleMode::Idle, _, _) => {}
+            // Any long or very long press stops Pomodoro cycle.
+            (_, ButtonPressType::Long, _)
+            | (_, _, ButtonPressType::Long)
+            | (_, ButtonPressType::VeryLong, _)
+            | (_, _, ButtonPressType::VeryLong) => {
+                Self::set_mode(system, PomodoroRoleMode::Idle);
+            }
+            // Double click restarts current phase.
+            (_, ButtonPressType::DoubleClick, _) | (_, _, ButtonPressType::DoubleClick) => {
+                Self::set_mode(system, current_mode);
+            }
+            // Short press skips current phase.
+            (PomodoroRoleMode::Work(_), _, _)
+            | (PomodoroRoleMode::ShortBreak(_), _, _)
+            | (PomodoroRoleMode::LongBreak, _, _) => {
+                let next_mode = PomodoroConfig::read(&system.flash()).next_mode(current_mode);
+                Self::set_mode(system, next_mode);
+            }
+        }
+    }
+
+    /// Returns gestures bound in the current mode: very long press is only used to leave the role
+    /// and double click only restarts running phase.
+    pub fn gestures<T: SystemHardware, S: SysTickHardware>(
+        system: &System<T, S>,
+    ) -> ButtonsGestures {
+        let is_idle = Self::current_mode(system) == PomodoroRoleMode::Idle;
+        ButtonsGestures {
+            double_click: !is_idle,
+            very_long_press: is_idle,
+        }
+    }
+
+    pub fn on_alarm<T: SystemHardware, S: SysTickHardware>(system: &mut System<T, S>) {
+        let current_mode = Self::current_mode(system);
+        if current_mode != PomodoroRoleMode::Idle {
+            let next_mode = PomodoroConfig::read(&system.flash()).next_mode(current_mode);
+            Self::set_mode(system, next_mode);
+        }
+    }
+
+    pub fn set_mode<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+        mode: PomodoroRoleMode,
+    ) {
+        let duration = PomodoroConfig::read(&system.flash()).duration(mode);
+
+        system.beeper().play(match mode {
+            PomodoroRoleMode::Idle => Melody::Reset,
+            PomodoroRoleMode::Work(_) => Melody::Work,
+            PomodoroRoleMode::ShortBreak(_) => Melody::ShortBreak,
+            PomodoroRoleMode::LongBreak => Melody::LongBreak,
+        });
+
+        let rtc = system.rtc();
+        if let Some(duration) = duration {
+            rtc.set_alarm_after(duration);
+        } else {
+            rtc.disable_alarm();
+        }
+
+        system.state.role_state = Some(RoleState::Pomodoro(mode));
+    }
+
+    fn current_mode<T: SystemHardware, S: SysTickHardware>(
+        system: &System<T, S>,
+    ) -> PomodoroRoleMode {
+        if let Some(RoleState::Pomodoro(mode)) = system.state.role_state {
+            mode
+        } else {
+            PomodoroRoleMode::Idle
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correctly_advances_modes() {
+        let config = PomodoroConfig {
+            work: 25,
+            short_break: 5,
+            long_break: 15,
+            cycles: 2,
+        };
+
+        assert_eq!(
+            config.next_mode(PomodoroRoleMode::Idle),
+            PomodoroRoleMode::Work(1)
+        );
+        assert_eq!(
+            config.next_mode(PomodoroRoleMode::Work(1)),
+            PomodoroRoleMode::ShortBreak(1)
+        );
+        assert_eq!(
+            config.next_mode(PomodoroRoleMode::ShortBreak(1)),
+            PomodoroRoleMode::Work(2)
+        );
+        assert_eq!(
+            config.next_mode(PomodoroRoleMode::Work(2)),
+            PomodoroRoleMode::LongBreak
+        );
+        assert_eq!(
+            config.next_mode(PomodoroRoleMode::LongBreak),
+            PomodoroRoleMode::Work(1)
+        );
+    }
+
+    #[test]
+    fn correctly_serializes_config() {
+        let config = PomodoroConfig {
+            work: 50,
+            short_break: 10,
+            long_break: 30,
+            cycles: 3,
+        };
+
+        assert_eq!(Array::from(config).as_ref(), [50, 10, 30, 3]);
+        assert_eq!(
+            PomodoroConfig::try_from([50, 10, 30, 3].as_ref()),
+            Ok(config)
+        );
+
+        assert_eq!(config.to_bytes().as_ref(), [50, 10, 30, 3]);
+        assert_eq!(PomodoroConfig::from_bytes(&[50, 10, 30, 3]), Some(config));
+    }
+
+    #[test]
+    fn fails_to_deserialize_invalid_config() {
+        assert_eq!(PomodoroConfig::try_from([0, 10, 30, 3].as_ref()), Err(()));
+        assert_eq!(PomodoroConfig::try_from([50, 10, 30, 0].as_ref()), Err(()));
+        assert_eq!(PomodoroConfig::try_from([50, 10, 30].as_ref()), Err(()));
+        assert_eq!(PomodoroConfig::from_bytes(&[50, 0, 30, 3]), None);
+    }
+
+    #[test]
+    fn correctly_calculates_durations() {
+        let config = PomodoroConfig::default();
+
+        assert_eq!(config.duration(PomodoroRoleMode::Idle), None);
+        assert_eq!(
+            config.duration(PomodoroRoleMode::Work(1)),
+            Some(Time::from_minutes(25))
+        );
+        assert_eq!(
+            config.duration(PomodoroRoleMode::ShortBreak(1)),
+            Some(Time::from_minutes(5))
+        );
+        assert_eq!(
+            config.duration(PomodoroRoleMode::LongBreak),
+            Some(Time::from_minutes(15))
+        );
+    }
+}
@@ -1,14 +1,48 @@
-use super::super::{system_role::SystemRole, system_state::RoleState, System, SystemHardware};
-use beeper::melody::Melody;
-use buttons::ButtonPressType;
+use super::super::{
+    system_role::{ControllerSystemRoleHandler, SystemRole},
+    system_state::RoleState,
+    System, SystemHardware,
+};
+use alarm::{EscalationConfig, SnoozeConfig};
+use array::Array;
+use beeper::{
+    melody::Melody,
+    note::{Note, NOTE_1_2_DURATION, NOTE_1_4_DURATION, NOTE_1_8_DURATION},
+    stored_melody::StoredMelodyConfig,
+    tone::Tone,
+};
+use buttons::{ButtonPressType, ButtonsGestures};
+use preset::Preset;
+use shortcut::{Shortcut, ShortcutTrigger};
 use systick::SysTickHardware;
 use time::Time;
 
+/// Tones used to announce remaining hours, tens of minutes and minutes respectively.
+const HOURS_TONE: Tone = Tone::new(Note::C5 as u8, NOTE_1_2_DURATION);
+const TENS_OF_MINUTES_TONE: Tone = Tone::new(Note::C6 as u8, NOTE_1_2_DURATION);
+const MINUTES_TONE: Tone = Tone::new(Note::C6 as u8, NOTE_1_8_DURATION);
+
+/// Number of minutes countdown alarm is extended by with the button gesture.
+const EXTENSION_MINUTES: u8 = 10;
+
+/// Maximum value of the Setup mode counter. RTC alarm wraps around at midnight, so countdown can't
+/// be a full day or longer.
+const MAX_SETUP_COUNTER: u32 = 24 * 60 - 1;
+
 #[derive(Debug, Copy, Clone)]
 pub enum TimerRoleMode {
     Idle,
     Setup(u32),
+    /// Countdown alarm, nested time is the duration of the countdown.
     Alarm(Time, Melody),
+    /// Paused countdown alarm, nested time is the duration left.
+    Paused(Time, Melody),
+    /// Alarm that fires at the specified time of the day.
+    AlarmAt(Time),
+    /// Nested value is the number of the current snooze (starts from 1).
+    Snooze(u8),
+    /// Alarm that is re-armed with the same interval every time it fires until it's cancelled.
+    Recurring(Time),
 }
 
 pub struct TimerSystemRoleHandler;
@@ -24,12 +58,18 @@ impl TimerSystemRoleHandler {
         };
 
         match (current_mode, button_i, button_x) {
-            (TimerRoleMode::Alarm(_, _), ButtonPressType::Long, ButtonPressType::Long) => {
-                Self::set_mode(system, TimerRoleMode::Idle);
-            }
-            (TimerRoleMode::Idle, ButtonPressType::Long, ButtonPressType::Long) => {
+            (TimerRoleMode::Idle, ButtonPressType::VeryLong, ButtonPressType::VeryLong) => {
                 system.switch_to_role(SystemRole::Controller)
             }
+            // Very long press on both buttons resets any mode.
+            (_, ButtonPressType::VeryLong, ButtonPressType::VeryLong)
+            | (TimerRoleMode::Alarm(_, _), ButtonPressType::Long, ButtonPressType::Long)
+            | (TimerRoleMode::Paused(_, _), ButtonPressType::Long, ButtonPressType::Long)
+            | (TimerRoleMode::AlarmAt(_), ButtonPressType::Long, ButtonPressType::Long)
+            | (TimerRoleMode::Snooze(_), ButtonPressType::Long, ButtonPressType::Long)
+            | (TimerRoleMode::Recurring(_), ButtonPressType::Long, ButtonPressType::Long) => {
+                Self::set_mode(system, TimerRoleMode::Idle);
+            }
             (TimerRoleMode::Setup(counter), ButtonPressType::Long, ButtonPressType::Long) => {
                 Self::set_mode(
                     system,
@@ -40,77 +80,433 @@ impl TimerSystemRoleHandler {
             | (TimerRoleMode::Idle, _, ButtonPressType::Long) => {
                 Self::set_mode(system, TimerRoleMode::Setup(0));
             }
+            // Short press on Ⅰ, Ⅹ or both recalls the 1st, 2nd or 3rd preset respectively.
+            (TimerRoleMode::Idle, ButtonPressType::Short, ButtonPressType::Short) => {
+                Self::recall_preset(system, 3).ok();
+            }
+            (TimerRoleMode::Idle, ButtonPressType::Short, _) => {
+                Self::recall_preset(system, 1).ok();
+            }
+            (TimerRoleMode::Idle, _, ButtonPressType::Short) => {
+                Self::recall_preset(system, 2).ok();
+            }
+            // Double click on Ⅰ pauses countdown alarm or resumes the paused one.
+            (TimerRoleMode::Alarm(_, _), ButtonPressType::DoubleClick, _) => {
+                Self::pause(system).ok();
+            }
+            (TimerRoleMode::Paused(_, _), ButtonPressType::DoubleClick, _) => {
+                Self::resume(system).ok();
+            }
             (TimerRoleMode::Alarm(_, _), ButtonPressType::Long, _)
-            | (TimerRoleMode::Alarm(_, _), _, ButtonPressType::Long) => {
+            | (TimerRoleMode::Alarm(_, _), _, ButtonPressType::Long)
+            | (TimerRoleMode::Paused(_, _), ButtonPressType::Long, _)
+            | (TimerRoleMode::Paused(_, _), _, ButtonPressType::Long)
+            | (TimerRoleMode::AlarmAt(_), ButtonPressType::Long, _)
+            | (TimerRoleMode::AlarmAt(_), _, ButtonPressType::Long)
+            | (TimerRoleMode::Snooze(_), ButtonPressType::Long, _)
+            | (TimerRoleMode::Snooze(_), _, ButtonPressType::Long)
+            | (TimerRoleMode::Recurring(_), ButtonPressType::Long, _)
+            | (TimerRoleMode::Recurring(_), _, ButtonPressType::Long) => {
                 Self::set_mode(system, TimerRoleMode::Idle);
             }
+            // Short press on both buttons turns pending alarm into a recurring one.
+            (TimerRoleMode::Alarm(time, _), ButtonPressType::Short, ButtonPressType::Short) => {
+                Self::set_mode(system, TimerRoleMode::Recurring(time));
+            }
+            // Double click on Ⅹ extends countdown alarm twice.
+            (TimerRoleMode::Alarm(_, _), _, ButtonPressType::DoubleClick)
+            | (TimerRoleMode::Paused(_, _), _, ButtonPressType::DoubleClick) => {
+                Self::extend(system, 2 * EXTENSION_MINUTES).ok();
+            }
+            // Short press on Ⅹ extends countdown alarm.
+            (TimerRoleMode::Alarm(_, _), _, ButtonPressType::Short)
+            | (TimerRoleMode::Paused(_, _), _, ButtonPressType::Short) => {
+                Self::extend(system, EXTENSION_MINUTES).ok();
+            }
+            (TimerRoleMode::Paused(remaining, _), ButtonPressType::Short, _) => {
+                system.beeper().play(remaining_time_tones(remaining));
+            }
+            // Short press on any other button announces the time left until alarm.
+            (TimerRoleMode::Alarm(_, _), ButtonPressType::Short, _)
+            | (TimerRoleMode::AlarmAt(_), ButtonPressType::Short, _)
+            | (TimerRoleMode::AlarmAt(_), _, ButtonPressType::Short)
+            | (TimerRoleMode::Recurring(_), ButtonPressType::Short, _)
+            | (TimerRoleMode::Recurring(_), _, ButtonPressType::Short) => {
+                let remaining = system.rtc().remaining();
+                system.beeper().play(remaining_time_tones(remaining));
+            }
             (TimerRoleMode::Setup(counter), ButtonPressType::Long, _)
             | (TimerRoleMode::Setup(counter), _, ButtonPressType::Long) => {
                 let time = match button_i {
-                    ButtonPressType::Long => Time::from_seconds(counter as u32),
-                    _ => Time::from_minutes(counter as u32),
+                    ButtonPressType::Long => Time::from_seconds(counter),
+                    _ => Time::from_minutes(counter),
                 };
 
                 Self::set_mode(system, TimerRoleMode::Alarm(time, Melody::Alarm));
             }
             (TimerRoleMode::Setup(counter), ButtonPressType::Short, _) => {
-                Self::set_mode(system, TimerRoleMode::Setup(counter + 1));
+                Self::set_mode(
+                    system,
+                    TimerRoleMode::Setup((counter + 1).min(MAX_SETUP_COUNTER)),
+                );
             }
             (TimerRoleMode::Setup(counter), _, ButtonPressType::Short) => {
-                Self::set_mode(system, TimerRoleMode::Setup(counter + 10));
+                Self::set_mode(
+                    system,
+                    TimerRoleMode::Setup((counter + 10).min(MAX_SETUP_COUNTER)),
+                );
+            }
+            // Double click counts as two short presses.
+            (TimerRoleMode::Setup(counter), ButtonPressType::DoubleClick, _) => {
+                Self::set_mode(
+                    system,
+                    TimerRoleMode::Setup((counter + 2).min(MAX_SETUP_COUNTER)),
+                );
+            }
+            (TimerRoleMode::Setup(counter), _, ButtonPressType::DoubleClick) => {
+                Self::set_mode(
+                    system,
+                    TimerRoleMode::Setup((counter + 20).min(MAX_SETUP_COUNTER)),
+                );
             }
             _ => {}
         }
     }
 
-    pub fn on_alarm<T: SystemHardware, S: SysTickHardware>(system: &mut System<T, S>) {
+    /// Returns gestures bound in the current mode, so that buttons poll doesn't wait for the ones
+    /// that aren't used.
+    pub fn gestures<T: SystemHardware, S: SysTickHardware>(
+        system: &System<T, S>,
+    ) -> ButtonsGestures {
+        match system.state.role_state {
+            Some(RoleState::Timer(TimerRoleMode::Setup(_))) => ButtonsGestures {
+                double_click: true,
+                very_long_press: true,
+            },
+            // Very long press would reset these modes just like long press on both buttons does.
+            Some(RoleState::Timer(TimerRoleMode::Alarm(_, _)))
+            | Some(RoleState::Timer(TimerRoleMode::Paused(_, _))) => ButtonsGestures {
+                double_click: true,
+                very_long_press: false,
+            },
+            Some(RoleState::Timer(TimerRoleMode::AlarmAt(_)))
+            | Some(RoleState::Timer(TimerRoleMode::Snooze(_)))
+            | Some(RoleState::Timer(TimerRoleMode::Recurring(_))) => ButtonsGestures::default(),
+            _ => ButtonsGestures {
+                double_click: false,
+                very_long_press: true,
+            },
+        }
+    }
+
+    /// Starts countdown alarm using preset with the specified index.
+    pub fn recall_preset<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+        index: u8,
+    ) -> Result<(), ()> {
+        let preset = Preset::read(&system.flash(), index).ok_or(())?;
+        Self::set_mode(
+            system,
+            TimerRoleMode::Alarm(preset.duration, preset.melody.unwrap_or(Melody::Alarm)),
+        );
+        Ok(())
+    }
+
+    /// Pauses countdown alarm remembering the time that is left.
+    pub fn pause<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+    ) -> Result<(), ()> {
         if let Some(RoleState::Timer(TimerRoleMode::Alarm(_, melody))) = system.state.role_state {
-            system.beeper().play_and_repeat(melody, 2);
+            let remaining = system.rtc().remaining();
+            Self::set_mode(system, TimerRoleMode::Paused(remaining, melody));
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Resumes paused countdown alarm with the time that was left.
+    pub fn resume<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+    ) -> Result<(), ()> {
+        if let Some(RoleState::Timer(TimerRoleMode::Paused(remaining, melody))) =
+            system.state.role_state
+        {
+            system.beeper().play(Melody::Resume);
+            system.rtc().set_alarm_after(remaining);
+            system.state.role_state =
+                Some(RoleState::Timer(TimerRoleMode::Alarm(remaining, melody)));
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Cancels pending, snoozed or paused alarm.
+    pub fn cancel<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+    ) -> Result<(), ()> {
+        match system.state.role_state {
+            Some(RoleState::Timer(TimerRoleMode::Paused(_, _))) => {}
+            _ if system.pending_alarm().is_some() => {}
+            _ => return Err(()),
+        }
+
+        Self::set_mode(system, TimerRoleMode::Idle);
+        Ok(())
+    }
+
+    /// Extends active or paused countdown alarm by the specified number of minutes.
+    pub fn extend<T: SystemHardware, S: SysTickHardware>(
+        system: &mut System<T, S>,
+        minutes: u8,
+    ) -> Result<(), ()> {
+        let mode = match system.state.role_state {
+            Some(RoleState::Timer(TimerRoleMode::Alarm(mut duration, melody))) => {
+                let rtc = system.rtc();
+                let mut alarm = rtc.alarm();
+                alarm.add_minutes(u32::from(minutes));
+                rtc.set_alarm(alarm);
+
+                duration.add_minutes(u32::from(minutes));
+                TimerRoleMode::Alarm(duration, melody)
+            }
+            Some(RoleState::Timer(TimerRoleMode::Paused(mut remaining, melody))) => {
+                remaining.add_minutes(u32::from(minutes));
+                TimerRoleMode::Paused(remaining, melody)
+            }
+            _ => return Err(()),
+        };
 
-            system.rtc().teardown();
+        system.beeper().play(Melody::Extend);
+        system.state.role_state = Some(RoleState::Timer(mode));
+        Ok(())
+    }
+
+    pub fn on_alarm<T: SystemHardware, S: SysTickHardware>(system: &mut System<T, S>) {
+        // Alarm action is sent to the host when alarm fires, snoozes don't repeat it.
+        match system.state.role_state {
+            Some(RoleState::Timer(TimerRoleMode::Alarm(_, _)))
+            | Some(RoleState::Timer(TimerRoleMode::AlarmAt(_)))
+            | Some(RoleState::Timer(TimerRoleMode::Recurring(_))) => {
+                Self::send_alarm_action(system)
+            }
+            _ => {}
+        }
+
+        // Recurring alarm doesn't snooze, it's just re-armed with the same interval.
+        if let Some(RoleState::Timer(TimerRoleMode::Recurring(time))) = system.state.role_state {
+            let melody = Self::resolve_melody(system, Melody::Alarm, |config| config.alarm);
+            let volume = system.state.peripherals_states.beeper.config.alarm_volume;
+            system.beeper().play_with_volume(melody, 1, volume);
+            system.rtc().set_alarm_after(time);
+            return;
+        }
+
+        let snooze_config = SnoozeConfig::read(&system.flash());
+        let (melody, snooze) = match system.state.role_state {
+            Some(RoleState::Timer(TimerRoleMode::Alarm(_, Melody::Alarm)))
+            | Some(RoleState::Timer(TimerRoleMode::AlarmAt(_))) => (
+                Self::resolve_melody(system, Melody::Alarm, |config| config.alarm),
+                1,
+            ),
+            Some(RoleState::Timer(TimerRoleMode::Alarm(_, melody))) => (melody, 1),
+            Some(RoleState::Timer(TimerRoleMode::Snooze(snooze))) => (
+                Self::resolve_melody(system, snooze_config.melody, |config| config.snooze),
+                snooze.saturating_add(1),
+            ),
+            _ => return,
+        };
+
+        // Every unacknowledged snooze makes alarm more intense up to the escalation limit.
+        let escalation_config = EscalationConfig::read(&system.flash());
+        let volume = escalation_config.volume(
+            snooze,
+            system.state.peripherals_states.beeper.config.alarm_volume,
+        );
+        system
+            .beeper()
+            .play_with_volume(melody, escalation_config.repeat(snooze), volume);
+
+        // Snooze alarm unless we've reached the snooze limit, in that case give up silently without
+        // interrupting the melody that is being played.
+        if snooze_config.limit == 0 || snooze <= snooze_config.limit {
+            Self::set_mode(system, TimerRoleMode::Snooze(snooze));
+        } else {
+            system.leave_usb_hybrid_mode();
+            system.state.role_state = Some(RoleState::Timer(TimerRoleMode::Idle));
+        }
+    }
 
-            // Snooze alarm for 10 seconds.
-            Self::set_mode(
-                system,
-                TimerRoleMode::Alarm(Time::from_seconds(10), Melody::Beep),
-            );
+    /// Replaces built-in `melody` with the one stored in flash if there is any and config says so.
+    fn resolve_melody<T: SystemHardware, S: SysTickHardware>(
+        system: &System<T, S>,
+        melody: Melody,
+        use_stored: fn(StoredMelodyConfig) -> bool,
+    ) -> Melody {
+        if !system
+            .state
+            .peripherals_states
+            .beeper
+            .stored_melody
+            .is_empty()
+            && use_stored(StoredMelodyConfig::read(&system.flash()))
+        {
+            Melody::Stored
+        } else {
+            melody
         }
     }
 
+    /// Checks whether alarm action is configured. The action is global: it's sent for every alarm
+    /// no matter whether it's been armed with the buttons, preset or via USB.
+    pub fn has_alarm_action<T: SystemHardware, S: SysTickHardware>(system: &System<T, S>) -> bool {
+        Shortcut::read(&system.flash(), ShortcutTrigger::Alarm).is_some()
+    }
+
+    /// Sends configured alarm action to the host if USB is enumerated (hybrid mode). Device stays
+    /// connected to the host only to send the action, so it disconnects right after that and
+    /// snoozes or recurring alarm don't keep it awake.
+    fn send_alarm_action<T: SystemHardware, S: SysTickHardware>(system: &mut System<T, S>) {
+        if !system.state.is_usb_hybrid {
+            return;
+        }
+
+        if let Some(shortcut) = Shortcut::read(&system.flash(), ShortcutTrigger::Alarm) {
+            ControllerSystemRoleHandler::send_shortcut(system, shortcut);
+        }
+
+        system.leave_usb_hybrid_mode();
+    }
+
     pub fn set_mode<T: SystemHardware, S: SysTickHardware>(
         system: &mut System<T, S>,
         mode: TimerRoleMode,
     ) {
-        let current_mode = if let Some(RoleState::Timer(mode)) = system.state.role_state {
-            mode
-        } else {
-            TimerRoleMode::Idle
-        };
-
         match &mode {
             TimerRoleMode::Idle => {
-                system.rtc().teardown();
+                system.rtc().disable_alarm();
                 system.beeper().play(Melody::Reset);
+                // Once alarm is over there is nothing to control via USB anymore.
+                system.leave_usb_hybrid_mode();
             }
             TimerRoleMode::Setup(c) => {
-                system
-                    .beeper()
-                    .play(if *c > 0 { Melody::Beep } else { Melody::Setup })
-            }
-            TimerRoleMode::Alarm(time, _) => {
-                // We don't need to additionally beep if we transition from one Alarm mode to
-                // another that means we're in a Snooze mode.
-                if !matches!(current_mode, TimerRoleMode::Alarm(_, _)) {
-                    system.beeper().play(Melody::Setup)
+                let melody = if *c > 0 {
+                    Melody::Beep
+                } else {
+                    Self::resolve_melody(system, Melody::Setup, |config| config.setup)
+                };
+                system.beeper().play(melody);
+            }
+            TimerRoleMode::Alarm(time, _) | TimerRoleMode::Recurring(time) => {
+                let melody = Self::resolve_melody(system, Melody::Setup, |config| config.setup);
+                system.beeper().play(melody);
+                system.rtc().set_alarm_after(*time);
+                // Stay connected to the host until alarm fires to send alarm action.
+                if Self::has_alarm_action(system) {
+                    system.enter_usb_hybrid_mode();
+                }
+            }
+            TimerRoleMode::AlarmAt(time) => {
+                let melody = Self::resolve_melody(system, Melody::Setup, |config| config.setup);
+                system.beeper().play(melody);
+                system.rtc().set_alarm(*time);
+                if Self::has_alarm_action(system) {
+                    system.enter_usb_hybrid_mode();
                 }
-
-                let rtc = system.rtc();
-                rtc.setup();
-                rtc.set_time(Time::default());
-                rtc.set_alarm(*time);
+            }
+            TimerRoleMode::Paused(_, _) => {
+                system.rtc().disable_alarm();
+                system.beeper().play(Melody::Pause);
+            }
+            // We don't need to additionally beep when alarm is snoozed since alarm melody is being
+            // played at this moment.
+            TimerRoleMode::Snooze(snooze) => {
+                let snooze_interval = EscalationConfig::read(&system.flash())
+                    .interval(SnoozeConfig::read(&system.flash()).interval, *snooze);
+                system
+                    .rtc()
+                    .set_alarm_after(Time::from_seconds(u32::from(snooze_interval)));
             }
         }
 
         system.state.role_state = Some(RoleState::Timer(mode));
     }
 }
+
+/// Builds tones that announce remaining time rounded up to minutes. If an hour or more is left,
+/// hours (low long tones) are followed by tens of minutes (long tones), otherwise tens of minutes
+/// are followed by minutes (short tones).
+fn remaining_time_tones(remaining: Time) -> Array<Tone> {
+    let minutes = remaining.as_seconds().div_ceil(60);
+    let groups = if minutes >= 60 {
+        [
+            (minutes / 60, HOURS_TONE),
+            (minutes % 60 / 10, TENS_OF_MINUTES_TONE),
+        ]
+    } else {
+        [
+            (minutes / 10, TENS_OF_MINUTES_TONE),
+            (minutes % 10, MINUTES_TONE),
+        ]
+    };
+
+    let mut tones = Array::new();
+    for (count, tone) in groups.iter().filter(|(count, _)| *count > 0) {
+        for _ in 0..*count {
+            tones.push(*tone);
+            tones.push(Tone::new(Note::Silence as u8, NOTE_1_4_DURATION));
+        }
+
+        // Make a longer pause between groups.
+        tones.push(Tone::new(Note::Silence as u8, NOTE_1_2_DURATION));
+    }
+
+    tones
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_tones(tones: &Array<Tone>, tone: Tone) -> usize {
+        tones.as_ref().iter().filter(|t| **t == tone).count()
+    }
+
+    #[test]
+    fn announces_minutes() {
+        let tones = remaining_time_tones(Time::from_seconds(24 * 60 + 10));
+        assert_eq!(count_tones(&tones, TENS_OF_MINUTES_TONE), 2);
+        assert_eq!(count_tones(&tones, MINUTES_TONE), 5);
+        assert_eq!(count_tones(&tones, HOURS_TONE), 0);
+
+        // Tens of minutes are announced first.
+        assert_eq!(tones[0], TENS_OF_MINUTES_TONE);
+        assert_eq!(tones[5], MINUTES_TONE);
+
+        let tones = remaining_time_tones(Time::from_seconds(30));
+        assert_eq!(count_tones(&tones, MINUTES_TONE), 1);
+        assert_eq!(count_tones(&tones, TENS_OF_MINUTES_TONE), 0);
+    }
+
+    #[test]
+    fn announces_hours() {
+        let tones = remaining_time_tones(Time {
+            hours: 2,
+            minutes: 35,
+            seconds: 0,
+        });
+        assert_eq!(count_tones(&tones, HOURS_TONE), 2);
+        assert_eq!(count_tones(&tones, TENS_OF_MINUTES_TONE), 3);
+        assert_eq!(count_tones(&tones, MINUTES_TONE), 0);
+
+        // The longest announcement fits into the tones array.
+        let tones = remaining_time_tones(Time {
+            hours: 23,
+            minutes: 59,
+            seconds: 0,
+        });
+        assert_eq!(count_tones(&tones, HOURS_TONE), 23);
+        assert_eq!(count_tones(&tones, TENS_OF_MINUTES_TONE), 5);
+    }
+}
@@ -1,4 +1,4 @@
-use super::system_role::{SystemRole, TimerRoleMode};
+use super::system_role::{PomodoroRoleMode, SystemRole, TimerRoleMode};
 use beeper::BeeperState;
 use buttons::ButtonsState;
 use usb::UsbState;
@@ -7,6 +7,9 @@ use usb::UsbState;
 pub struct SystemState {
     pub role: SystemRole,
     pub role_state: Option<RoleState>,
+    /// Indicates that USB stays enumerated in Timer role, so that device can act on the host when
+    /// alarm fires.
+    pub is_usb_hybrid: bool,
     pub peripherals_states: PeripheralsStates,
 }
 
@@ -15,6 +18,7 @@ impl Default for SystemState {
         SystemState {
             role: SystemRole::default(),
             role_state: None,
+            is_usb_hybrid: false,
             peripherals_states: PeripheralsStates {
                 usb: UsbState::default(),
                 beeper: BeeperState::default(),
@@ -34,4 +38,5 @@ pub struct PeripheralsStates {
 #[derive(Debug, Copy, Clone)]
 pub enum RoleState {
     Timer(TimerRoleMode),
+    Pomodoro(PomodoroRoleMode),
 }
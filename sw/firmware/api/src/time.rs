@@ -63,6 +63,16 @@ impl Time {
     pub fn from_hours(hours: u32) -> Self {
         Time::from_minutes(hours * 60)
     }
+
+    /// Checks whether hours, minutes and seconds represent a valid time of the day.
+    pub fn is_valid_time_of_day(hours: u8, minutes: u8, seconds: u8) -> bool {
+        hours < 24 && minutes < 60 && seconds < 60
+    }
+
+    /// Returns total number of seconds this time represents.
+    pub fn as_seconds(&self) -> u32 {
+        u32::from(self.hours) * 3600 + u32::from(self.minutes) * 60 + u32::from(self.seconds)
+    }
 }
 
 impl From<BCDTime> for Time {
@@ -240,6 +250,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn is_valid_time_of_day() {
+        assert!(Time::is_valid_time_of_day(0, 0, 0));
+        assert!(Time::is_valid_time_of_day(23, 59, 59));
+        assert!(!Time::is_valid_time_of_day(24, 0, 0));
+        assert!(!Time::is_valid_time_of_day(12, 60, 0));
+        assert!(!Time::is_valid_time_of_day(12, 0, 60));
+    }
+
+    #[test]
+    fn as_seconds() {
+        assert_eq!(Time::default().as_seconds(), 0);
+        assert_eq!(Time::from_seconds(6785).as_seconds(), 6785);
+        assert_eq!(
+            Time {
+                hours: 23,
+                minutes: 59,
+                seconds: 59,
+            }
+            .as_seconds(),
+            86399
+        );
+    }
+
     #[test]
     fn to_bcd() {
         assert_eq!(
@@ -8,7 +8,7 @@ mod system;
 
 pub use self::adc::ADCCommand;
 pub use self::alarm::AlarmCommand;
-pub use self::beeper::BeeperCommand;
+pub use self::beeper::{BeeperCommand, MAX_MELODY_TONES, MAX_MORSE_TEXT_LENGTH, MELODY_QUEUE_FULL};
 pub use self::flash::FlashCommand;
 pub use self::keyboard::{KeyModifiers, KeyboardCommand, MediaKey};
 pub use self::radio::RadioCommand;
@@ -1,5 +1,7 @@
+use alarm::{EscalationConfig, SnoozeConfig};
 use array::Array;
 use core::convert::TryFrom;
+use preset::Preset;
 use time::Time;
 use usb::usb_error::USBError;
 
@@ -7,6 +9,50 @@ use usb::usb_error::USBError;
 pub enum AlarmCommand {
     Get,
     Set(Time),
+    GetSnooze,
+    SetSnooze(SnoozeConfig),
+    /// Sets alarm that is re-armed every time it fires until it's cancelled.
+    SetRecurring(Time),
+    /// Sets alarm that fires at the specified time of the day.
+    SetAt(Time),
+    /// Gets time left until pending alarm fires, fails if there is no pending alarm.
+    GetRemaining,
+    /// Pauses active countdown alarm.
+    Pause,
+    /// Resumes paused countdown alarm.
+    Resume,
+    /// Extends active or paused countdown alarm by the specified number of minutes.
+    Extend(u8),
+    /// Gets preset with the specified index.
+    GetPreset(u8),
+    /// Sets preset with the specified index.
+    SetPreset(u8, Preset),
+    /// Deletes preset with the specified index.
+    DeletePreset(u8),
+    /// Gets alarm escalation policy.
+    GetEscalation,
+    /// Sets alarm escalation policy.
+    SetEscalation(EscalationConfig),
+    /// Gets time of the day pending alarm fires at, fails if there is no pending alarm.
+    GetPending,
+    /// Cancels pending, snoozed or paused alarm.
+    Cancel,
+}
+
+impl AlarmCommand {
+    /// Checks whether command only controls already armed alarm. These are the only commands device
+    /// accepts while it stays connected to the host in Timer role.
+    pub fn is_alarm_control(&self) -> bool {
+        matches!(
+            self,
+            AlarmCommand::GetPending
+                | AlarmCommand::GetRemaining
+                | AlarmCommand::Pause
+                | AlarmCommand::Resume
+                | AlarmCommand::Extend(_)
+                | AlarmCommand::Cancel
+        )
+    }
 }
 
 impl From<AlarmCommand> for Array<u8> {
@@ -14,6 +60,38 @@ impl From<AlarmCommand> for Array<u8> {
         match packet {
             AlarmCommand::Get => [1].as_ref().into(),
             AlarmCommand::Set(time) => [2, time.hours, time.minutes, time.seconds].as_ref().into(),
+            AlarmCommand::GetSnooze => [3].as_ref().into(),
+            AlarmCommand::SetSnooze(config) => {
+                let mut array = Array::from(config);
+                array.unshift(4);
+                array
+            }
+            AlarmCommand::SetRecurring(time) => {
+                [5, time.hours, time.minutes, time.seconds].as_ref().into()
+            }
+            AlarmCommand::SetAt(time) => {
+                [6, time.hours, time.minutes, time.seconds].as_ref().into()
+            }
+            AlarmCommand::GetRemaining => [7].as_ref().into(),
+            AlarmCommand::Pause => [8].as_ref().into(),
+            AlarmCommand::Resume => [9].as_ref().into(),
+            AlarmCommand::Extend(minutes) => [10, minutes].as_ref().into(),
+            AlarmCommand::GetPreset(index) => [11, index].as_ref().into(),
+            AlarmCommand::SetPreset(index, preset) => {
+                let mut array = Array::from(preset);
+                array.unshift(index);
+                array.unshift(12);
+                array
+            }
+            AlarmCommand::DeletePreset(index) => [13, index].as_ref().into(),
+            AlarmCommand::GetEscalation => [14].as_ref().into(),
+            AlarmCommand::SetEscalation(config) => {
+                let mut array = Array::from(config);
+                array.unshift(15);
+                array
+            }
+            AlarmCommand::GetPending => [16].as_ref().into(),
+            AlarmCommand::Cancel => [17].as_ref().into(),
         }
     }
 }
@@ -29,6 +107,43 @@ impl TryFrom<Array<u8>> for AlarmCommand {
                 minutes: value[1],
                 seconds: value[2],
             })),
+            (Some(0x3), 0) => Ok(AlarmCommand::GetSnooze),
+            (Some(0x4), 3) => SnoozeConfig::try_from(value.as_ref())
+                .map(AlarmCommand::SetSnooze)
+                .map_err(|_| USBError::InvalidCommand),
+            (Some(0x5), 3) => Ok(AlarmCommand::SetRecurring(Time {
+                hours: value[0],
+                minutes: value[1],
+                seconds: value[2],
+            })),
+            (Some(0x6), 3) if Time::is_valid_time_of_day(value[0], value[1], value[2]) => {
+                Ok(AlarmCommand::SetAt(Time {
+                    hours: value[0],
+                    minutes: value[1],
+                    seconds: value[2],
+                }))
+            }
+            (Some(0x7), 0) => Ok(AlarmCommand::GetRemaining),
+            (Some(0x8), 0) => Ok(AlarmCommand::Pause),
+            (Some(0x9), 0) => Ok(AlarmCommand::Resume),
+            (Some(0xA), 1) if value[0] > 0 => Ok(AlarmCommand::Extend(value[0])),
+            (Some(0xB), 1) if Preset::is_valid_index(value[0]) => {
+                Ok(AlarmCommand::GetPreset(value[0]))
+            }
+            (Some(0xC), 5) if Preset::is_valid_index(value[0]) => {
+                Preset::try_from(&value.as_ref()[1..])
+                    .map(|preset| AlarmCommand::SetPreset(value[0], preset))
+                    .map_err(|_| USBError::InvalidCommand)
+            }
+            (Some(0xD), 1) if Preset::is_valid_index(value[0]) => {
+                Ok(AlarmCommand::DeletePreset(value[0]))
+            }
+            (Some(0xE), 0) => Ok(AlarmCommand::GetEscalation),
+            (Some(0xF), 4) => EscalationConfig::try_from(value.as_ref())
+                .map(AlarmCommand::SetEscalation)
+                .map_err(|_| USBError::InvalidCommand),
+            (Some(0x10), 0) => Ok(AlarmCommand::GetPending),
+            (Some(0x11), 0) => Ok(AlarmCommand::Cancel),
             _ => Err(USBError::InvalidCommand),
         }
     }
@@ -45,6 +160,7 @@ impl TryFrom<&[u8]> for AlarmCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use beeper::melody::Melody;
 
     #[test]
     fn get_command() {
@@ -93,6 +209,207 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_snooze_command() {
+        assert_eq!(
+            AlarmCommand::try_from([3].as_ref()),
+            Ok(AlarmCommand::GetSnooze)
+        );
+
+        assert_eq!(Array::from(AlarmCommand::GetSnooze).as_ref(), [3]);
+    }
+
+    #[test]
+    fn set_snooze_command() {
+        let config = SnoozeConfig {
+            interval: 15,
+            limit: 3,
+            melody: Melody::Alarm,
+        };
+
+        assert_eq!(
+            AlarmCommand::try_from([4, 15, 3, 1].as_ref()),
+            Ok(AlarmCommand::SetSnooze(config))
+        );
+
+        assert_eq!(
+            Array::from(AlarmCommand::SetSnooze(config)).as_ref(),
+            [4, 15, 3, 1]
+        );
+    }
+
+    #[test]
+    fn escalation_commands() {
+        let config = EscalationConfig {
+            volume_step: 10,
+            repeat_step: 1,
+            interval_step: 2,
+            limit: 3,
+        };
+
+        assert_eq!(
+            AlarmCommand::try_from([14].as_ref()),
+            Ok(AlarmCommand::GetEscalation)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([15, 10, 1, 2, 3].as_ref()),
+            Ok(AlarmCommand::SetEscalation(config))
+        );
+        assert_eq!(
+            AlarmCommand::try_from([15, 10, 1, 2].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+
+        assert_eq!(Array::from(AlarmCommand::GetEscalation).as_ref(), [14]);
+        assert_eq!(
+            Array::from(AlarmCommand::SetEscalation(config)).as_ref(),
+            [15, 10, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn set_recurring_command() {
+        assert_eq!(
+            AlarmCommand::try_from([5, 0, 20, 0].as_ref()),
+            Ok(AlarmCommand::SetRecurring(Time {
+                hours: 0,
+                minutes: 20,
+                seconds: 0,
+            }))
+        );
+
+        assert_eq!(
+            Array::from(AlarmCommand::SetRecurring(Time {
+                hours: 1,
+                minutes: 30,
+                seconds: 15,
+            }))
+            .as_ref(),
+            [5, 1, 30, 15]
+        );
+    }
+
+    #[test]
+    fn set_at_command() {
+        let time = Time {
+            hours: 14,
+            minutes: 30,
+            seconds: 0,
+        };
+
+        assert_eq!(
+            AlarmCommand::try_from([6, 14, 30, 0].as_ref()),
+            Ok(AlarmCommand::SetAt(time))
+        );
+
+        assert_eq!(
+            Array::from(AlarmCommand::SetAt(time)).as_ref(),
+            [6, 14, 30, 0]
+        );
+    }
+
+    #[test]
+    fn get_remaining_command() {
+        assert_eq!(
+            AlarmCommand::try_from([7].as_ref()),
+            Ok(AlarmCommand::GetRemaining)
+        );
+
+        assert_eq!(Array::from(AlarmCommand::GetRemaining).as_ref(), [7]);
+    }
+
+    #[test]
+    fn get_pending_command() {
+        assert_eq!(
+            AlarmCommand::try_from([16].as_ref()),
+            Ok(AlarmCommand::GetPending)
+        );
+
+        assert_eq!(Array::from(AlarmCommand::GetPending).as_ref(), [16]);
+    }
+
+    #[test]
+    fn cancel_command() {
+        assert_eq!(
+            AlarmCommand::try_from([17].as_ref()),
+            Ok(AlarmCommand::Cancel)
+        );
+
+        assert_eq!(Array::from(AlarmCommand::Cancel).as_ref(), [17]);
+    }
+
+    #[test]
+    fn alarm_control_commands() {
+        assert!(AlarmCommand::GetPending.is_alarm_control());
+        assert!(AlarmCommand::GetRemaining.is_alarm_control());
+        assert!(AlarmCommand::Pause.is_alarm_control());
+        assert!(AlarmCommand::Resume.is_alarm_control());
+        assert!(AlarmCommand::Extend(5).is_alarm_control());
+        assert!(AlarmCommand::Cancel.is_alarm_control());
+
+        assert!(!AlarmCommand::Get.is_alarm_control());
+        assert!(!AlarmCommand::Set(Time::from_minutes(5)).is_alarm_control());
+        assert!(!AlarmCommand::GetSnooze.is_alarm_control());
+        assert!(!AlarmCommand::DeletePreset(1).is_alarm_control());
+    }
+
+    #[test]
+    fn pause_resume_command() {
+        assert_eq!(
+            AlarmCommand::try_from([8].as_ref()),
+            Ok(AlarmCommand::Pause)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([9].as_ref()),
+            Ok(AlarmCommand::Resume)
+        );
+
+        assert_eq!(Array::from(AlarmCommand::Pause).as_ref(), [8]);
+        assert_eq!(Array::from(AlarmCommand::Resume).as_ref(), [9]);
+    }
+
+    #[test]
+    fn extend_command() {
+        assert_eq!(
+            AlarmCommand::try_from([10, 15].as_ref()),
+            Ok(AlarmCommand::Extend(15))
+        );
+
+        assert_eq!(Array::from(AlarmCommand::Extend(15)).as_ref(), [10, 15]);
+    }
+
+    #[test]
+    fn preset_commands() {
+        let preset = Preset {
+            duration: Time {
+                hours: 0,
+                minutes: 25,
+                seconds: 0,
+            },
+            melody: Some(Melody::Beep),
+        };
+
+        assert_eq!(
+            AlarmCommand::try_from([11, 2].as_ref()),
+            Ok(AlarmCommand::GetPreset(2))
+        );
+        assert_eq!(
+            AlarmCommand::try_from([12, 1, 0, 25, 0, 2].as_ref()),
+            Ok(AlarmCommand::SetPreset(1, preset))
+        );
+        assert_eq!(
+            AlarmCommand::try_from([13, 3].as_ref()),
+            Ok(AlarmCommand::DeletePreset(3))
+        );
+
+        assert_eq!(Array::from(AlarmCommand::GetPreset(2)).as_ref(), [11, 2]);
+        assert_eq!(
+            Array::from(AlarmCommand::SetPreset(1, preset)).as_ref(),
+            [12, 1, 0, 25, 0, 2]
+        );
+        assert_eq!(Array::from(AlarmCommand::DeletePreset(3)).as_ref(), [13, 3]);
+    }
+
     #[test]
     fn invalid_command() {
         assert_eq!(
@@ -100,12 +417,84 @@ mod tests {
             Err(USBError::InvalidCommand)
         );
         assert_eq!(
-            AlarmCommand::try_from([3].as_ref()),
+            AlarmCommand::try_from([3, 1].as_ref()),
             Err(USBError::InvalidCommand)
         );
         assert_eq!(
             AlarmCommand::try_from([4, 5, 6].as_ref()),
             Err(USBError::InvalidCommand)
         );
+        assert_eq!(
+            AlarmCommand::try_from([4, 0, 6, 1].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([5].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([6, 14].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([6, 24, 0, 0].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([6, 25, 99, 0].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([6, 14, 30, 60].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([7, 1].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([8, 1].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([10].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([10, 0].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([11].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([11, 0].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([12, 4, 0, 25, 0, 0].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([12, 1, 0, 0, 0, 0].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([13, 4].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([16, 1].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([17, 1].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            AlarmCommand::try_from([18].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
     }
 }
@@ -1,25 +1,93 @@
 use array::Array;
-use beeper::tone::Tone;
+use beeper::{
+    morse, note::Note, stored_melody::StoredMelodyConfig, tone::Tone, volume::BeeperConfig,
+};
 use core::convert::TryFrom;
-use usb::usb_error::USBError;
+use usb::{descriptors::MAX_PACKET_SIZE, usb_error::USBError};
+
+/// Maximum number of tones that fit into a single USB packet: packet and command types are
+/// followed by the note and duration pairs.
+pub const MAX_MELODY_TONES: usize = (MAX_PACKET_SIZE - 2) / 2;
+
+/// Response status device sends when it can't buffer appended melody chunk yet, chunk should be
+/// re-sent once device plays some of the queued tones.
+pub const MELODY_QUEUE_FULL: u8 = 0x01;
+
+/// Maximum length of the Morse code text that fits into a single USB packet: packet and command
+/// types, speed and pitch are followed by the text.
+pub const MAX_MORSE_TEXT_LENGTH: usize = MAX_PACKET_SIZE - 4;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum BeeperCommand {
     Beep(u8),
     Melody(Array<Tone>),
+    /// Erases melody stored in flash.
+    ClearStoredMelody,
+    /// Appends tones to the melody stored in flash, long melodies are uploaded in several packets.
+    AppendStoredMelody(Array<Tone>),
+    GetStoredMelodyConfig,
+    SetStoredMelodyConfig(StoredMelodyConfig),
+    /// Starts streaming of the melody that doesn't fit into a single packet, stops current one.
+    BeginMelody,
+    /// Queues chunk of the streamed melody, responds with `MELODY_QUEUE_FULL` and starts playing
+    /// queued chunks if device can't buffer it yet.
+    AppendMelody(Array<Tone>),
+    /// Starts playing queued chunks, the ones appended later are played in sequence.
+    CommitMelody,
+    GetConfig,
+    SetConfig(BeeperConfig),
+    /// Plays ASCII text as Morse code with the specified speed (words per minute) and pitch.
+    Morse(u8, Note, Array<u8>),
+}
+
+/// Serializes tones as note and duration pairs following the command type.
+fn tones_to_array(command_type: u8, tones: Array<Tone>) -> Array<u8> {
+    let mut array = Array::from(&[command_type]);
+    tones.as_ref().iter().for_each(|tone| {
+        array.push(tone.note);
+        array.push(tone.duration);
+    });
+    array
+}
+
+/// Every tone consists of note and duration, so number of bytes should be even.
+fn tones_from_array(value: Array<u8>) -> Result<Array<Tone>, USBError> {
+    if value.is_empty() || value.len() & 1 == 1 {
+        return Err(USBError::InvalidCommand);
+    }
+
+    let mut array = Array::new();
+    value
+        .as_ref()
+        .chunks(2)
+        .for_each(|pair| array.push(Tone::new(pair[0], pair[1])));
+    Ok(array)
 }
 
 impl From<BeeperCommand> for Array<u8> {
     fn from(packet: BeeperCommand) -> Self {
         match packet {
             BeeperCommand::Beep(n_beeps) => (&[1, n_beeps]).into(),
-            BeeperCommand::Melody(tones) => {
-                let mut array = Array::from(&[2]);
-                tones.as_ref().iter().for_each(|tone| {
-                    array.push(tone.note);
-                    array.push(tone.duration);
-                });
-                array.as_ref().into()
+            BeeperCommand::Melody(tones) => tones_to_array(2, tones),
+            BeeperCommand::ClearStoredMelody => (&[3]).into(),
+            BeeperCommand::AppendStoredMelody(tones) => tones_to_array(4, tones),
+            BeeperCommand::GetStoredMelodyConfig => (&[5]).into(),
+            BeeperCommand::SetStoredMelodyConfig(config) => (&[6, config.into()]).into(),
+            BeeperCommand::BeginMelody => (&[7]).into(),
+            BeeperCommand::AppendMelody(tones) => tones_to_array(8, tones),
+            BeeperCommand::CommitMelody => (&[9]).into(),
+            BeeperCommand::GetConfig => (&[10]).into(),
+            BeeperCommand::SetConfig(config) => {
+                let mut array = Array::from(config);
+                array.unshift(11);
+                array
+            }
+            BeeperCommand::Morse(wpm, pitch, text) => {
+                let mut array = text;
+                array.unshift(pitch as u8);
+                array.unshift(wpm);
+                array.unshift(12);
+                array
             }
         }
     }
@@ -31,14 +99,29 @@ impl TryFrom<Array<u8>> for BeeperCommand {
     fn try_from(mut value: Array<u8>) -> Result<Self, Self::Error> {
         match (value.shift(), value.len()) {
             (Some(0x1), 1) => Ok(BeeperCommand::Beep(value[0])),
-            // Every tone consists of frequency and duration, so number of bytes should be even.
-            (Some(0x2), n_tones) if n_tones > 1 && n_tones & 1 == 0 => {
-                let mut array = Array::new();
-                value
-                    .as_ref()
-                    .chunks(2)
-                    .for_each(|pair| array.push(Tone::new(pair[0], pair[1])));
-                Ok(BeeperCommand::Melody(array))
+            (Some(0x2), _) => tones_from_array(value).map(BeeperCommand::Melody),
+            (Some(0x3), 0) => Ok(BeeperCommand::ClearStoredMelody),
+            (Some(0x4), _) => tones_from_array(value).map(BeeperCommand::AppendStoredMelody),
+            (Some(0x5), 0) => Ok(BeeperCommand::GetStoredMelodyConfig),
+            (Some(0x6), 1) => StoredMelodyConfig::try_from(value[0])
+                .map(BeeperCommand::SetStoredMelodyConfig)
+                .map_err(|_| USBError::InvalidCommand),
+            (Some(0x7), 0) => Ok(BeeperCommand::BeginMelody),
+            (Some(0x8), _) => tones_from_array(value).map(BeeperCommand::AppendMelody),
+            (Some(0x9), 0) => Ok(BeeperCommand::CommitMelody),
+            (Some(0xA), 0) => Ok(BeeperCommand::GetConfig),
+            (Some(0xB), 2) => BeeperConfig::try_from(value.as_ref())
+                .map(BeeperCommand::SetConfig)
+                .map_err(|_| USBError::InvalidCommand),
+            (Some(0xC), len) if len > 2 && morse::is_valid_wpm(value[0]) => {
+                match Note::try_from(value[1]) {
+                    Ok(Note::Silence) | Err(_) => Err(USBError::InvalidCommand),
+                    Ok(pitch) => Ok(BeeperCommand::Morse(
+                        value[0],
+                        pitch,
+                        Array::from(&value.as_ref()[2..]),
+                    )),
+                }
             }
             _ => Err(USBError::InvalidCommand),
         }
@@ -56,7 +139,7 @@ impl TryFrom<&[u8]> for BeeperCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use beeper::note::Note;
+    use beeper::{note::Note, volume::Volume};
 
     #[test]
     fn beep_command() {
@@ -83,6 +166,131 @@ mod tests {
             Array::from(BeeperCommand::Melody(tones)).as_ref(),
             [2, 0xA5, 100, 0xC5, 50]
         );
+
+        assert_eq!(MAX_MELODY_TONES, 31);
+    }
+
+    #[test]
+    fn stored_melody_commands() {
+        let mut tones: Array<Tone> = Array::new();
+        tones.push(Tone::new(Note::A5 as u8, 100));
+
+        assert_eq!(
+            BeeperCommand::try_from([3].as_ref()),
+            Ok(BeeperCommand::ClearStoredMelody)
+        );
+        assert_eq!(Array::from(BeeperCommand::ClearStoredMelody).as_ref(), [3]);
+
+        assert_eq!(
+            BeeperCommand::try_from([4, 0xA5, 100].as_ref()),
+            Ok(BeeperCommand::AppendStoredMelody(tones))
+        );
+        assert_eq!(
+            Array::from(BeeperCommand::AppendStoredMelody(tones)).as_ref(),
+            [4, 0xA5, 100]
+        );
+
+        assert_eq!(
+            BeeperCommand::try_from([5].as_ref()),
+            Ok(BeeperCommand::GetStoredMelodyConfig)
+        );
+        assert_eq!(
+            Array::from(BeeperCommand::GetStoredMelodyConfig).as_ref(),
+            [5]
+        );
+
+        let config = StoredMelodyConfig {
+            alarm: true,
+            snooze: true,
+            setup: false,
+        };
+        assert_eq!(
+            BeeperCommand::try_from([6, 0b011].as_ref()),
+            Ok(BeeperCommand::SetStoredMelodyConfig(config))
+        );
+        assert_eq!(
+            Array::from(BeeperCommand::SetStoredMelodyConfig(config)).as_ref(),
+            [6, 0b011]
+        );
+    }
+
+    #[test]
+    fn streamed_melody_commands() {
+        let mut tones: Array<Tone> = Array::new();
+        tones.push(Tone::new(Note::A5 as u8, 100));
+
+        assert_eq!(
+            BeeperCommand::try_from([7].as_ref()),
+            Ok(BeeperCommand::BeginMelody)
+        );
+        assert_eq!(Array::from(BeeperCommand::BeginMelody).as_ref(), [7]);
+
+        assert_eq!(
+            BeeperCommand::try_from([8, 0xA5, 100].as_ref()),
+            Ok(BeeperCommand::AppendMelody(tones))
+        );
+        assert_eq!(
+            Array::from(BeeperCommand::AppendMelody(tones)).as_ref(),
+            [8, 0xA5, 100]
+        );
+
+        assert_eq!(
+            BeeperCommand::try_from([9].as_ref()),
+            Ok(BeeperCommand::CommitMelody)
+        );
+        assert_eq!(Array::from(BeeperCommand::CommitMelody).as_ref(), [9]);
+    }
+
+    #[test]
+    fn config_commands() {
+        assert_eq!(
+            BeeperCommand::try_from([10].as_ref()),
+            Ok(BeeperCommand::GetConfig)
+        );
+        assert_eq!(Array::from(BeeperCommand::GetConfig).as_ref(), [10]);
+
+        let config = BeeperConfig {
+            duty_cycle: 10,
+            alarm_volume: Volume::Crescendo,
+        };
+        assert_eq!(
+            BeeperCommand::try_from([11, 10, 3].as_ref()),
+            Ok(BeeperCommand::SetConfig(config))
+        );
+        assert_eq!(
+            Array::from(BeeperCommand::SetConfig(config)).as_ref(),
+            [11, 10, 3]
+        );
+    }
+
+    #[test]
+    fn morse_command() {
+        let text = Array::from(b"SOS".as_ref());
+        assert_eq!(
+            BeeperCommand::try_from([12, 20, Note::A5 as u8, b'S', b'O', b'S'].as_ref()),
+            Ok(BeeperCommand::Morse(20, Note::A5, text))
+        );
+        assert_eq!(
+            Array::from(BeeperCommand::Morse(20, Note::A5, text)).as_ref(),
+            [12, 20, Note::A5 as u8, b'S', b'O', b'S']
+        );
+
+        assert_eq!(
+            BeeperCommand::try_from([12, 20, Note::A5 as u8].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            BeeperCommand::try_from([12, 4, Note::A5 as u8, b'S'].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            BeeperCommand::try_from([12, 20, Note::Silence as u8, b'S'].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            BeeperCommand::try_from([12, 20, 0xD5, b'S'].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
     }
 
     #[test]
@@ -92,11 +300,27 @@ mod tests {
             Err(USBError::InvalidCommand)
         );
         assert_eq!(
-            BeeperCommand::try_from([3].as_ref()),
+            BeeperCommand::try_from([13].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            BeeperCommand::try_from([2, 5, 6, 7].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            BeeperCommand::try_from([4].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            BeeperCommand::try_from([6, 0b1000].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            BeeperCommand::try_from([9, 1].as_ref()),
             Err(USBError::InvalidCommand)
         );
         assert_eq!(
-            BeeperCommand::try_from([4, 5, 6].as_ref()),
+            BeeperCommand::try_from([11, 0, 1].as_ref()),
             Err(USBError::InvalidCommand)
         );
     }
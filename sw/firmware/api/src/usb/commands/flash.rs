@@ -8,6 +8,7 @@ pub enum FlashCommand {
     Read(StorageSlot),
     Write(StorageSlot, u8),
     EraseAll,
+    Stats,
 }
 
 impl From<FlashCommand> for Array<u8> {
@@ -18,6 +19,7 @@ impl From<FlashCommand> for Array<u8> {
                 [2, storage_slot.into(), value].as_ref().into()
             }
             FlashCommand::EraseAll => [3].as_ref().into(),
+            FlashCommand::Stats => [4].as_ref().into(),
         }
     }
 }
@@ -36,6 +38,7 @@ impl TryFrom<Array<u8>> for FlashCommand {
                 Err(_) => Err(USBError::InvalidCommand),
             },
             (Some(0x3), 0) => Ok(FlashCommand::EraseAll),
+            (Some(0x4), 0) => Ok(FlashCommand::Stats),
             _ => Err(USBError::InvalidCommand),
         }
     }
@@ -93,6 +96,16 @@ mod tests {
         assert_eq!(Array::from(FlashCommand::EraseAll).as_ref(), [3]);
     }
 
+    #[test]
+    fn stats_command() {
+        assert_eq!(
+            FlashCommand::try_from([4].as_ref()),
+            Ok(FlashCommand::Stats)
+        );
+
+        assert_eq!(Array::from(FlashCommand::Stats).as_ref(), [4]);
+    }
+
     #[test]
     fn invalid_command() {
         assert_eq!(
@@ -100,7 +113,11 @@ mod tests {
             Err(USBError::InvalidCommand),
         );
         assert_eq!(
-            FlashCommand::try_from([4].as_ref()),
+            FlashCommand::try_from([4, 1].as_ref()),
+            Err(USBError::InvalidCommand),
+        );
+        assert_eq!(
+            FlashCommand::try_from([5].as_ref()),
             Err(USBError::InvalidCommand),
         );
         assert_eq!(
@@ -2,6 +2,7 @@ use super::super::usb_error::USBError;
 use array::Array;
 use bit_field::BitField;
 use core::convert::TryFrom;
+use shortcut::{Shortcut, ShortcutTrigger};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct KeyModifiers {
@@ -30,17 +31,17 @@ impl From<u8> for KeyModifiers {
     }
 }
 
-impl Into<u8> for KeyModifiers {
-    fn into(self) -> u8 {
+impl From<KeyModifiers> for u8 {
+    fn from(modifiers: KeyModifiers) -> Self {
         let mut modifiers_bits = 0u8;
-        modifiers_bits.set_bit(0, self.left_ctrl);
-        modifiers_bits.set_bit(1, self.left_shift);
-        modifiers_bits.set_bit(2, self.left_alt);
-        modifiers_bits.set_bit(3, self.left_gui);
-        modifiers_bits.set_bit(4, self.right_ctrl);
-        modifiers_bits.set_bit(5, self.right_shift);
-        modifiers_bits.set_bit(6, self.right_alt);
-        modifiers_bits.set_bit(7, self.right_gui);
+        modifiers_bits.set_bit(0, modifiers.left_ctrl);
+        modifiers_bits.set_bit(1, modifiers.left_shift);
+        modifiers_bits.set_bit(2, modifiers.left_alt);
+        modifiers_bits.set_bit(3, modifiers.left_gui);
+        modifiers_bits.set_bit(4, modifiers.right_ctrl);
+        modifiers_bits.set_bit(5, modifiers.right_shift);
+        modifiers_bits.set_bit(6, modifiers.right_alt);
+        modifiers_bits.set_bit(7, modifiers.right_gui);
 
         modifiers_bits
     }
@@ -78,6 +79,9 @@ impl TryFrom<u8> for MediaKey {
 pub enum KeyboardCommand {
     Key(KeyModifiers, u8, u8),
     Media(MediaKey, u8),
+    GetShortcut(ShortcutTrigger),
+    SetShortcut(ShortcutTrigger, Shortcut),
+    DeleteShortcut(ShortcutTrigger),
 }
 
 impl TryFrom<Array<u8>> for KeyboardCommand {
@@ -97,6 +101,19 @@ impl TryFrom<Array<u8>> for KeyboardCommand {
                     Err(USBError::InvalidCommand)
                 }
             }
+            (Some(0x3), 1) => ShortcutTrigger::try_from(value[0])
+                .map(KeyboardCommand::GetShortcut)
+                .map_err(|_| USBError::InvalidCommand),
+            (Some(0x4), 4) => match (
+                ShortcutTrigger::try_from(value[0]),
+                Shortcut::try_from(&value.as_ref()[1..]),
+            ) {
+                (Ok(trigger), Ok(shortcut)) => Ok(KeyboardCommand::SetShortcut(trigger, shortcut)),
+                _ => Err(USBError::InvalidCommand),
+            },
+            (Some(0x5), 1) => ShortcutTrigger::try_from(value[0])
+                .map(KeyboardCommand::DeleteShortcut)
+                .map_err(|_| USBError::InvalidCommand),
             _ => Err(USBError::InvalidCommand),
         }
     }
@@ -117,6 +134,14 @@ impl From<KeyboardCommand> for Array<u8> {
                 [1, modifiers.into(), key_code, delay].as_ref().into()
             }
             KeyboardCommand::Media(media_key, delay) => [2, media_key as u8, delay].as_ref().into(),
+            KeyboardCommand::GetShortcut(trigger) => [3, trigger as u8].as_ref().into(),
+            KeyboardCommand::SetShortcut(trigger, shortcut) => {
+                let mut array = Array::from(shortcut);
+                array.unshift(trigger as u8);
+                array.unshift(4);
+                array
+            }
+            KeyboardCommand::DeleteShortcut(trigger) => [5, trigger as u8].as_ref().into(),
         }
     }
 }
@@ -396,6 +421,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn shortcut_commands() {
+        assert_eq!(
+            KeyboardCommand::try_from([3, 2].as_ref()),
+            Ok(KeyboardCommand::GetShortcut(ShortcutTrigger::OneLong))
+        );
+        assert_eq!(
+            Array::from(KeyboardCommand::GetShortcut(ShortcutTrigger::TenShort)).as_ref(),
+            [3, 3]
+        );
+
+        assert_eq!(
+            KeyboardCommand::try_from([4, 1, 1, 3, 0x10].as_ref()),
+            Ok(KeyboardCommand::SetShortcut(
+                ShortcutTrigger::OneShort,
+                Shortcut::Key(KeyModifiers::from(3), 0x10)
+            ))
+        );
+        assert_eq!(
+            Array::from(KeyboardCommand::SetShortcut(
+                ShortcutTrigger::TenLong,
+                Shortcut::Media(MediaKey::Mute)
+            ))
+            .as_ref(),
+            [4, 4, 2, 4, 0]
+        );
+
+        assert_eq!(
+            KeyboardCommand::try_from([5, 4].as_ref()),
+            Ok(KeyboardCommand::DeleteShortcut(ShortcutTrigger::TenLong))
+        );
+        assert_eq!(
+            Array::from(KeyboardCommand::DeleteShortcut(ShortcutTrigger::OneShort)).as_ref(),
+            [5, 1]
+        );
+    }
+
     #[test]
     fn invalid_command() {
         assert_eq!(
@@ -443,5 +505,34 @@ mod tests {
             KeyboardCommand::try_from([2, 10, 5].as_ref()),
             Err(USBError::InvalidCommand)
         );
+
+        assert_eq!(
+            KeyboardCommand::try_from([3, 0].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            KeyboardCommand::try_from([3, 6].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            KeyboardCommand::try_from([4, 1, 1, 3].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            KeyboardCommand::try_from([4, 1, 2, 3, 0].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            KeyboardCommand::try_from([4, 0, 1, 3, 0x10].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            KeyboardCommand::try_from([5].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            KeyboardCommand::try_from([5, 6].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
     }
 }
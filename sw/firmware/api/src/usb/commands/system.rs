@@ -1,5 +1,8 @@
 use array::Array;
+use buttons::ButtonsConfig;
 use core::convert::TryFrom;
+use system::PomodoroConfig;
+use time::Time;
 use usb::usb_error::USBError;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -7,6 +10,18 @@ pub enum SystemCommand {
     Reset,
     Echo(Array<u8>),
     GetInfo,
+    /// Gets current wall-clock time kept by RTC.
+    GetTime,
+    /// Sets current wall-clock time kept by RTC.
+    SetTime(Time),
+    /// Gets buttons timings.
+    GetButtonsConfig,
+    /// Sets and persists buttons timings.
+    SetButtonsConfig(ButtonsConfig),
+    /// Gets Pomodoro intervals and number of cycles.
+    GetPomodoroConfig,
+    /// Sets and persists Pomodoro intervals and number of cycles.
+    SetPomodoroConfig(PomodoroConfig),
 }
 
 impl From<SystemCommand> for Array<u8> {
@@ -18,6 +33,22 @@ impl From<SystemCommand> for Array<u8> {
                 echo_data
             }
             SystemCommand::GetInfo => [3].as_ref().into(),
+            SystemCommand::GetTime => [4].as_ref().into(),
+            SystemCommand::SetTime(time) => {
+                [5, time.hours, time.minutes, time.seconds].as_ref().into()
+            }
+            SystemCommand::GetButtonsConfig => [6].as_ref().into(),
+            SystemCommand::SetButtonsConfig(config) => {
+                let mut array = Array::from(config);
+                array.unshift(7);
+                array
+            }
+            SystemCommand::GetPomodoroConfig => [8].as_ref().into(),
+            SystemCommand::SetPomodoroConfig(config) => {
+                let mut array = Array::from(config);
+                array.unshift(9);
+                array
+            }
         }
     }
 }
@@ -30,6 +61,22 @@ impl TryFrom<Array<u8>> for SystemCommand {
             (Some(0x1), 0) => Ok(SystemCommand::Reset),
             (Some(0x2), n_echo_bytes) if n_echo_bytes > 0 => Ok(SystemCommand::Echo(value)),
             (Some(0x3), 0) => Ok(SystemCommand::GetInfo),
+            (Some(0x4), 0) => Ok(SystemCommand::GetTime),
+            (Some(0x5), 3) if Time::is_valid_time_of_day(value[0], value[1], value[2]) => {
+                Ok(SystemCommand::SetTime(Time {
+                    hours: value[0],
+                    minutes: value[1],
+                    seconds: value[2],
+                }))
+            }
+            (Some(0x6), 0) => Ok(SystemCommand::GetButtonsConfig),
+            (Some(0x7), 5) => ButtonsConfig::try_from(value.as_ref())
+                .map(SystemCommand::SetButtonsConfig)
+                .map_err(|_| USBError::InvalidCommand),
+            (Some(0x8), 0) => Ok(SystemCommand::GetPomodoroConfig),
+            (Some(0x9), 4) => PomodoroConfig::try_from(value.as_ref())
+                .map(SystemCommand::SetPomodoroConfig)
+                .map_err(|_| USBError::InvalidCommand),
             _ => Err(USBError::InvalidCommand),
         }
     }
@@ -81,6 +128,91 @@ mod tests {
         assert_eq!(Array::from(SystemCommand::GetInfo).as_ref(), [3]);
     }
 
+    #[test]
+    fn get_time_command() {
+        assert_eq!(
+            SystemCommand::try_from([4].as_ref()),
+            Ok(SystemCommand::GetTime)
+        );
+
+        assert_eq!(Array::from(SystemCommand::GetTime).as_ref(), [4]);
+    }
+
+    #[test]
+    fn set_time_command() {
+        let time = Time {
+            hours: 14,
+            minutes: 30,
+            seconds: 5,
+        };
+
+        assert_eq!(
+            SystemCommand::try_from([5, 14, 30, 5].as_ref()),
+            Ok(SystemCommand::SetTime(time))
+        );
+
+        assert_eq!(
+            Array::from(SystemCommand::SetTime(time)).as_ref(),
+            [5, 14, 30, 5]
+        );
+    }
+
+    #[test]
+    fn get_buttons_config_command() {
+        assert_eq!(
+            SystemCommand::try_from([6].as_ref()),
+            Ok(SystemCommand::GetButtonsConfig)
+        );
+
+        assert_eq!(Array::from(SystemCommand::GetButtonsConfig).as_ref(), [6]);
+    }
+
+    #[test]
+    fn set_buttons_config_command() {
+        let config = ButtonsConfig {
+            poll_interval: 500,
+            short_press: 1000,
+            long_press: 3000,
+            very_long_press: 10000,
+            double_click: 1000,
+        };
+
+        assert_eq!(
+            SystemCommand::try_from([7, 10, 20, 60, 200, 20].as_ref()),
+            Ok(SystemCommand::SetButtonsConfig(config))
+        );
+
+        assert_eq!(
+            Array::from(SystemCommand::SetButtonsConfig(config)).as_ref(),
+            [7, 10, 20, 60, 200, 20]
+        );
+    }
+
+    #[test]
+    fn pomodoro_config_commands() {
+        let config = PomodoroConfig {
+            work: 50,
+            short_break: 10,
+            long_break: 30,
+            cycles: 3,
+        };
+
+        assert_eq!(
+            SystemCommand::try_from([8].as_ref()),
+            Ok(SystemCommand::GetPomodoroConfig)
+        );
+        assert_eq!(
+            SystemCommand::try_from([9, 50, 10, 30, 3].as_ref()),
+            Ok(SystemCommand::SetPomodoroConfig(config))
+        );
+
+        assert_eq!(Array::from(SystemCommand::GetPomodoroConfig).as_ref(), [8]);
+        assert_eq!(
+            Array::from(SystemCommand::SetPomodoroConfig(config)).as_ref(),
+            [9, 50, 10, 30, 3]
+        );
+    }
+
     #[test]
     fn invalid_command() {
         assert_eq!(
@@ -88,12 +220,48 @@ mod tests {
             Err(USBError::InvalidCommand)
         );
         assert_eq!(
-            SystemCommand::try_from([4].as_ref()),
+            SystemCommand::try_from([4, 1].as_ref()),
             Err(USBError::InvalidCommand)
         );
         assert_eq!(
             SystemCommand::try_from([5, 6, 7].as_ref()),
             Err(USBError::InvalidCommand)
         );
+        assert_eq!(
+            SystemCommand::try_from([5, 24, 0, 0].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            SystemCommand::try_from([5, 25, 99, 0].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            SystemCommand::try_from([5, 14, 30, 60].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            SystemCommand::try_from([6, 1].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            SystemCommand::try_from([7, 5, 10, 25, 100].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            SystemCommand::try_from([7, 5, 25, 10, 100, 10].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            SystemCommand::try_from([8, 1].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            SystemCommand::try_from([9, 50, 0, 30, 3].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
+        assert_eq!(
+            SystemCommand::try_from([10].as_ref()),
+            Err(USBError::InvalidCommand)
+        );
     }
 }
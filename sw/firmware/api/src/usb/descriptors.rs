@@ -276,13 +276,12 @@ mod tests {
             assert_eq!(string[0], string.len() as u8);
             assert_eq!(string[1], 0x3);
             // Chars should be NULL terminated.
-            assert_eq!(
+            assert!(
                 // Skip first two control bytes, the rest is the string content itself.
-                string[2..]
+                !string[2..]
                     .iter()
                     .enumerate()
-                    .any(|(index, val)| { index % 2 != 0 && *val != 0 }),
-                false
+                    .any(|(index, val)| { index % 2 != 0 && *val != 0 })
             )
         }
     }
@@ -40,10 +40,10 @@ impl TryFrom<u8> for EndpointType {
     }
 }
 
-impl Into<u8> for EndpointType {
+impl From<EndpointType> for u8 {
     /// Converts EndpointType into endpoint ID used to work with USB registers.
-    fn into(self) -> u8 {
-        match self {
+    fn from(endpoint_type: EndpointType) -> Self {
+        match endpoint_type {
             EndpointType::Control => 0,
             EndpointType::Device(DeviceEndpoint::System) => 1,
             EndpointType::Device(DeviceEndpoint::Keyboard) => 2,
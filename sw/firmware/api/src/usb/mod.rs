@@ -186,7 +186,7 @@ impl<'a, T: USBHardware> USB<'a, T> {
 
     /// Sends report via specified Device endpoint.
     pub fn send(&mut self, endpoint: DeviceEndpoint, data: &[u8]) {
-        self.send_data(EndpointType::Device(endpoint), &data);
+        self.send_data(EndpointType::Device(endpoint), data);
     }
 
     fn correct_transfer(&mut self) {
@@ -418,6 +418,9 @@ impl<'a, T: USBHardware> USB<'a, T> {
         }
     }
 
+    // Moving `if`s into match guards would make requests that don't pass them fall through to the
+    // last arm and stall the control endpoint.
+    #[allow(clippy::collapsible_match)]
     fn handle_endpoint_request(&mut self, request_header: SetupPacket) {
         let endpoint_address = request_header.index as u8;
         let is_device_endpoint = endpoint_address & 0x7f != 0;
@@ -629,7 +632,7 @@ impl<'a, T: USBHardware> USB<'a, T> {
                 self.send_control_data(if report_length < data.len() {
                     &data[..report_length]
                 } else {
-                    &data
+                    data
                 });
             }
             Request::GetStatus => self.send_control_data(&[0x0, 0x0]),
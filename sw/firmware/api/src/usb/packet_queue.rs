@@ -82,7 +82,7 @@ mod tests {
         queue.enqueue(EndpointType::Control, &[1, 2, 3]);
 
         let packet = queue.dequeue(EndpointType::Control);
-        assert_eq!(packet.is_some(), true);
+        assert!(packet.is_some());
         assert_eq!(packet.unwrap().as_ref(), &[1, 2, 3]);
 
         assert_eq!(queue.dequeue(EndpointType::Control), None);
@@ -104,12 +104,12 @@ mod tests {
         queue.enqueue(EndpointType::Device(DeviceEndpoint::Keyboard), &[7, 8, 9]);
 
         let packet = queue.dequeue(EndpointType::Control);
-        assert_eq!(packet.is_some(), true);
+        assert!(packet.is_some());
         assert_eq!(packet.unwrap().as_ref(), &[1, 2, 3]);
         assert_eq!(queue.dequeue(EndpointType::Control), None);
 
         let packet = queue.dequeue(EndpointType::Device(DeviceEndpoint::System));
-        assert_eq!(packet.is_some(), true);
+        assert!(packet.is_some());
         assert_eq!(packet.unwrap().as_ref(), &[4, 5, 6]);
         assert_eq!(
             queue.dequeue(EndpointType::Device(DeviceEndpoint::System)),
@@ -117,7 +117,7 @@ mod tests {
         );
 
         let packet = queue.dequeue(EndpointType::Device(DeviceEndpoint::Keyboard));
-        assert_eq!(packet.is_some(), true);
+        assert!(packet.is_some());
         assert_eq!(packet.unwrap().as_ref(), &[7, 8, 9]);
         assert_eq!(
             queue.dequeue(EndpointType::Device(DeviceEndpoint::Keyboard)),
@@ -128,7 +128,7 @@ mod tests {
     #[test]
     fn queue_with_multiple_packets() {
         // Data represents 2 packets (0..64), (64..100).
-        let data: Vec<u8> = (0u8..100).into_iter().collect();
+        let data: Vec<u8> = (0u8..100).collect();
         let mut data_ranges = vec![(0..MAX_PACKET_SIZE), (MAX_PACKET_SIZE..data.len())];
 
         let mut queue = PacketQueue::new();
@@ -139,15 +139,15 @@ mod tests {
         for range in data_ranges.drain(..) {
             let data_range = &data[range];
             let packet = queue.dequeue(EndpointType::Control);
-            assert_eq!(packet.is_some(), true);
+            assert!(packet.is_some());
             assert_eq!(packet.unwrap().as_ref(), data_range);
 
             let packet = queue.dequeue(EndpointType::Device(DeviceEndpoint::System));
-            assert_eq!(packet.is_some(), true);
+            assert!(packet.is_some());
             assert_eq!(packet.unwrap().as_ref(), data_range);
 
             let packet = queue.dequeue(EndpointType::Device(DeviceEndpoint::Keyboard));
-            assert_eq!(packet.is_some(), true);
+            assert!(packet.is_some());
             assert_eq!(packet.unwrap().as_ref(), data_range);
         }
 
@@ -166,7 +166,7 @@ mod tests {
     fn queue_with_overflow() {
         // Data represents 4 packets, but queue will only process 2 packets: (0..64), (64..128),
         // (128..192), (192..256).
-        let data: Vec<u8> = (0u8..=u8::max_value()).into_iter().collect();
+        let data: Vec<u8> = (0u8..=u8::MAX).collect();
         let mut data_ranges = vec![(0..MAX_PACKET_SIZE), (MAX_PACKET_SIZE..MAX_PACKET_SIZE * 2)];
 
         let mut queue = PacketQueue::new();
@@ -177,15 +177,15 @@ mod tests {
         for range in data_ranges.drain(..) {
             let data_range = &data[range];
             let packet = queue.dequeue(EndpointType::Control);
-            assert_eq!(packet.is_some(), true);
+            assert!(packet.is_some());
             assert_eq!(packet.unwrap().as_ref(), data_range);
 
             let packet = queue.dequeue(EndpointType::Device(DeviceEndpoint::System));
-            assert_eq!(packet.is_some(), true);
+            assert!(packet.is_some());
             assert_eq!(packet.unwrap().as_ref(), data_range);
 
             let packet = queue.dequeue(EndpointType::Device(DeviceEndpoint::Keyboard));
-            assert_eq!(packet.is_some(), true);
+            assert!(packet.is_some());
             assert_eq!(packet.unwrap().as_ref(), data_range);
         }
 
@@ -231,7 +231,7 @@ mod tests {
     #[test]
     fn clear_non_empty_queue() {
         println!("Size: {:?}", core::mem::size_of::<PacketQueue>());
-        let data: Vec<u8> = (0u8..=u8::max_value()).into_iter().collect();
+        let data: Vec<u8> = (0u8..=u8::MAX).collect();
 
         let mut queue = PacketQueue::new();
         queue.enqueue(EndpointType::Control, &data);
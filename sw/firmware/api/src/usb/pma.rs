@@ -29,7 +29,7 @@ impl PacketMemoryAreaAccessor {
             // Set RX address.
             self.set_u16(
                 endpoint_index * 8 + 4,
-                (base_address + (2 * endpoint_index + 1) as usize * MAX_PACKET_SIZE) as u16,
+                (base_address + (2 * endpoint_index + 1) * MAX_PACKET_SIZE) as u16,
             );
             self.set_rx_count(*endpoint, 0);
         }
@@ -74,7 +74,7 @@ impl PacketMemoryAreaAccessor {
             let high = iter.next();
             self.set_u16(
                 (base_offset + offset) & !1,
-                (u16::from(*high.unwrap_or_else(|| &0)) << 8) | u16::from(*low),
+                (u16::from(*high.unwrap_or(&0)) << 8) | u16::from(*low),
             );
 
             offset += if high.is_none() { 1 } else { 2 };
@@ -220,20 +220,14 @@ mod tests {
 
         pma.init(&SUPPORTED_ENDPOINTS);
 
-        sandbox[(CONTROL_OUT_PMA_ADDRESS >> 1) as usize] = 1;
-        sandbox[(CONTROL_OUT_PMA_ADDRESS >> 1) as usize + 1] = 2;
-        sandbox[(CONTROL_OUT_PMA_ADDRESS >> 1) as usize + 2] = 3;
-        sandbox[(CONTROL_OUT_PMA_ADDRESS >> 1) as usize + 3] = 4;
+        let offset = (CONTROL_OUT_PMA_ADDRESS >> 1) as usize;
+        sandbox[offset..offset + 4].copy_from_slice(&[1, 2, 3, 4]);
 
-        sandbox[(DEVICE_SYSTEM_OUT_PMA_ADDRESS >> 1) as usize] = 5;
-        sandbox[(DEVICE_SYSTEM_OUT_PMA_ADDRESS >> 1) as usize + 1] = 6;
-        sandbox[(DEVICE_SYSTEM_OUT_PMA_ADDRESS >> 1) as usize + 2] = 7;
-        sandbox[(DEVICE_SYSTEM_OUT_PMA_ADDRESS >> 1) as usize + 3] = 8;
+        let offset = (DEVICE_SYSTEM_OUT_PMA_ADDRESS >> 1) as usize;
+        sandbox[offset..offset + 4].copy_from_slice(&[5, 6, 7, 8]);
 
-        sandbox[(DEVICE_KEYBOARD_OUT_PMA_ADDRESS >> 1) as usize] = 9;
-        sandbox[(DEVICE_KEYBOARD_OUT_PMA_ADDRESS >> 1) as usize + 1] = 10;
-        sandbox[(DEVICE_KEYBOARD_OUT_PMA_ADDRESS >> 1) as usize + 2] = 11;
-        sandbox[(DEVICE_KEYBOARD_OUT_PMA_ADDRESS >> 1) as usize + 3] = 12;
+        let offset = (DEVICE_KEYBOARD_OUT_PMA_ADDRESS >> 1) as usize;
+        sandbox[offset..offset + 4].copy_from_slice(&[9, 10, 11, 12]);
 
         assert_eq!(
             [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
@@ -1,10 +1,19 @@
 MEMORY
 {
   /* NOTE K = KiBi = 1024 bytes */
-  FLASH : ORIGIN = 0x08000000, LENGTH = 32K
+  /* The last 3 pages (29-31) are reserved for the flash storage, see `src/flash.rs`. */
+  FLASH : ORIGIN = 0x08000000, LENGTH = 29K
   RAM : ORIGIN = 0x20000000, LENGTH = 6K
 }
 
+/* Address of the first page reserved for the flash storage (blob page). */
+_storage_start = 0x08007400;
+
+/* Writing stored values erases whole pages, so firmware must never be linked into the storage
+   pages. Linker fails with "region `FLASH' overflowed" if firmware doesn't fit into FLASH, and this
+   check makes sure FLASH region itself never grows into the storage pages. */
+ASSERT(ORIGIN(FLASH) + LENGTH(FLASH) <= _storage_start, "FLASH region overlaps the storage pages");
+
 /* This is where the call stack will be allocated. */
 /* The stack is of the full descending type. */
 /* You may want to use this variable to locate the call stack and static
@@ -67,7 +67,7 @@ impl PWMBeeperHardware for SystemHardwareImpl {
         self.rcc.regs.apb2enr.modify(|_, w| w.tim1en().disabled());
     }
 
-    fn pulse(&self, note_frequency: u32) {
+    fn pulse(&self, note_frequency: u32, duty_cycle: u8) {
         let frequency = if note_frequency == 0 || note_frequency > config::CLOCK_SPEED {
             0
         } else {
@@ -75,5 +75,10 @@ impl PWMBeeperHardware for SystemHardwareImpl {
         };
 
         self.tim1.arr.write(|w| w.arr().bits(frequency));
+
+        // Duty cycle defines the volume: the longer output is active within a period, the louder
+        // the sound is.
+        let channel_one_pulse = (u32::from(frequency) * u32::from(duty_cycle) / 100) as u16;
+        self.tim1.ccr3.write(|w| w.ccr().bits(channel_one_pulse));
     }
 }
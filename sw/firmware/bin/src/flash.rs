@@ -5,6 +5,10 @@ use kroneum_api::flash::FlashHardware;
 /// Sector 7, page 30 and 31 of STM32F04x flash memory.
 const PAGE_ADDRESSES: [usize; 2] = [0x0800_7800, 0x0800_7C00];
 
+/// Sector 7, page 29 of STM32F04x flash memory. Pages starting from this one are excluded from the
+/// FLASH region in `memory.x`, so that firmware is never linked into them.
+const BLOB_PAGE_ADDRESS: usize = 0x0800_7400;
+
 /// Disables or enables Flash write protection.
 fn toggle_write_protection(flash: &FLASH, enable_write_protection: bool) {
     let is_protected = flash.cr.read().lock().bit_is_set();
@@ -26,6 +30,10 @@ impl FlashHardware for SystemHardwareImpl {
         PAGE_ADDRESSES
     }
 
+    fn blob_page_address(&self) -> usize {
+        BLOB_PAGE_ADDRESS
+    }
+
     fn erase_page(&self, page_address: usize) {
         busy_wait_until_ready(&self.flash);
         toggle_write_protection(&self.flash, false);
@@ -2,6 +2,11 @@ use crate::hal::stm32::RTC;
 use crate::system::SystemHardwareImpl;
 use kroneum_api::{rtc::RTCHardware, time::BCDTime};
 
+/// Synchronous and asynchronous prescaler values: 40kHz/128 (0x7F + 1) => 312 Hz,
+/// 312Hz/312 (0x137 + 1) => 1Hz.
+const PREDIV_S: u16 = 0x137;
+const PREDIV_A: u8 = 0x7F;
+
 /// Disables or enables write protection for RTC registers.
 fn toggle_write_protection(rtc: &RTC, enable_write_protection: bool) {
     let protection_keys: [u8; 2] = if enable_write_protection {
@@ -15,6 +20,17 @@ fn toggle_write_protection(rtc: &RTC, enable_write_protection: bool) {
     }
 }
 
+/// Enters or exits initialization mode, RTC calendar is stopped while in this mode.
+fn toggle_init_mode(rtc: &RTC, enable: bool) {
+    if enable {
+        // Enable init phase and wait until it is allowed to modify RTC register values.
+        rtc.isr.modify(|_, w| w.init().set_bit());
+        while rtc.isr.read().initf().bit_is_clear() {}
+    } else {
+        rtc.isr.modify(|_, w| w.init().clear_bit());
+    }
+}
+
 fn toggle_alarm(rtc: &RTC, enable: bool) {
     rtc.cr.modify(|_, w| {
         w.alraie().bit(enable);
@@ -40,6 +56,31 @@ impl RTCHardware for SystemHardwareImpl {
             .bdcr
             .modify(|_, w| w.rtcen().set_bit().rtcsel().bits(0b10));
 
+        // Calendar is stopped while RTC is in init mode, so RTC is only configured if it hasn't been
+        // yet (e.g. after power loss). Otherwise it keeps wall-clock time across the resets.
+        let prer = self.rtc.prer.read();
+        let is_configured = self.rtc.isr.read().inits().bit_is_set()
+            && prer.prediv_s().bits() == PREDIV_S
+            && prer.prediv_a().bits() == PREDIV_A;
+        if !is_configured {
+            toggle_write_protection(&self.rtc, false);
+            toggle_init_mode(&self.rtc, true);
+
+            self.rtc
+                .prer
+                .modify(|_, w| unsafe { w.prediv_s().bits(PREDIV_S) });
+
+            self.rtc
+                .prer
+                .modify(|_, w| unsafe { w.prediv_a().bits(PREDIV_A) });
+
+            // Date isn't used, but non-zero year marks calendar as initialized (INITS flag).
+            self.rtc.dr.modify(|_, w| unsafe { w.yu().bits(1) });
+
+            toggle_init_mode(&self.rtc, false);
+            toggle_write_protection(&self.rtc, true);
+        }
+
         // Disable PWR clock.
         self.rcc.regs.apb1enr.modify(|_, w| w.pwren().clear_bit());
 
@@ -91,18 +132,7 @@ impl RTCHardware for SystemHardwareImpl {
     fn set_time(&self, bcd_time: BCDTime) {
         toggle_write_protection(&self.rtc, false);
 
-        // Enable init phase and wait until it is allowed to modify RTC register values.
-        self.rtc.isr.modify(|_, w| w.init().set_bit());
-        while self.rtc.isr.read().initf().bit_is_clear() {}
-
-        // Set prescaler, 40kHz/128 (0x7F + 1) => 312 Hz, 312Hz/312 (0x137 + 1) => 1Hz.
-        self.rtc
-            .prer
-            .modify(|_, w| unsafe { w.prediv_s().bits(0x137) });
-
-        self.rtc
-            .prer
-            .modify(|_, w| unsafe { w.prediv_a().bits(0x7F) });
+        toggle_init_mode(&self.rtc, true);
 
         // Configure Time register.
         self.rtc.tr.modify(|_, w| unsafe {
@@ -120,8 +150,7 @@ impl RTCHardware for SystemHardwareImpl {
                 .bits(bcd_time.seconds)
         });
 
-        // Disable init phase.
-        self.rtc.isr.modify(|_, w| w.init().clear_bit());
+        toggle_init_mode(&self.rtc, false);
 
         toggle_write_protection(&self.rtc, true);
     }
@@ -167,4 +196,15 @@ impl RTCHardware for SystemHardwareImpl {
 
         toggle_write_protection(&self.rtc, true);
     }
+
+    fn disable_alarm(&self) {
+        toggle_write_protection(&self.rtc, false);
+        toggle_alarm(&self.rtc, false);
+        toggle_write_protection(&self.rtc, true);
+
+        // Clear Alarm A flag.
+        self.rtc.isr.modify(|_, w| w.alraf().clear_bit());
+        // Clear EXTI line 17 flag.
+        self.exti.pr.modify(|_, w| w.pif17().set_bit());
+    }
 }
@@ -0,0 +1,20 @@
+use crate::system::SystemHardwareImpl;
+use kroneum_api::adc::{ADCChannel, ADCHardware};
+
+/// Values returned by the simulated ADC, indexed by the channel number (ADC_IN0 - ADC_IN7).
+#[derive(Default)]
+pub(crate) struct ADCState {
+    pub values: [u16; 8],
+}
+
+impl ADCHardware for SystemHardwareImpl {
+    fn setup(&self) {}
+
+    fn calibrate(&self) {}
+
+    fn read(&self, channel: ADCChannel) -> u16 {
+        self.state.borrow().adc.values[u8::from(channel) as usize]
+    }
+
+    fn teardown(&self) {}
+}
@@ -0,0 +1,73 @@
+use crate::system::SystemHardwareImpl;
+use kroneum_api::beeper::PWMBeeperHardware;
+
+/// Describes a single tone produced by the simulated beeper.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BeeperTone {
+    /// Virtual time (in ms) when the tone started.
+    pub started_at: u64,
+    /// Frequency of the PWM pulse, `0` means silence.
+    pub frequency: u32,
+    /// Duty cycle of the PWM pulse in percent, defines the volume.
+    pub duty_cycle: u8,
+    /// Duration of the tone in ms.
+    pub duration: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct BeeperState {
+    pub is_pwm_enabled: bool,
+    /// Tone that is currently being played (start time, frequency and duty cycle).
+    pub current_tone: Option<(u64, u32, u8)>,
+    /// Log of all tones that have been played so far.
+    pub tones: Vec<BeeperTone>,
+}
+
+impl BeeperState {
+    /// Closes currently playing tone (if any) and moves it to the log.
+    pub fn complete_current_tone(&mut self, now: u64) {
+        if let Some((started_at, frequency, duty_cycle)) = self.current_tone.take() {
+            self.tones.push(BeeperTone {
+                started_at,
+                frequency,
+                duty_cycle,
+                duration: now - started_at,
+            });
+        }
+    }
+
+    /// Returns the log of all tones including the one that is currently playing.
+    pub fn log(&self, now: u64) -> Vec<BeeperTone> {
+        let mut tones = self.tones.clone();
+        if let Some((started_at, frequency, duty_cycle)) = self.current_tone {
+            tones.push(BeeperTone {
+                started_at,
+                frequency,
+                duty_cycle,
+                duration: now - started_at,
+            });
+        }
+
+        tones
+    }
+}
+
+impl PWMBeeperHardware for SystemHardwareImpl {
+    fn enable_pwm(&self) {
+        self.state.borrow_mut().beeper.is_pwm_enabled = true;
+    }
+
+    fn disable_pwm(&self) {
+        let mut state = self.state.borrow_mut();
+        let now = state.now;
+        state.beeper.complete_current_tone(now);
+        state.beeper.is_pwm_enabled = false;
+    }
+
+    fn pulse(&self, note_frequency: u32, duty_cycle: u8) {
+        let mut state = self.state.borrow_mut();
+        let now = state.now;
+        state.beeper.complete_current_tone(now);
+        state.beeper.current_tone = Some((now, note_frequency, duty_cycle));
+    }
+}
@@ -0,0 +1,70 @@
+use crate::system::SystemHardwareImpl;
+use kroneum_api::buttons::{ButtonType, ButtonsHardware};
+
+/// Scheduled change of the button state: virtual time (in ms), button and whether it's pressed.
+pub(crate) type ButtonEdge = (u64, ButtonType, bool);
+
+#[derive(Default)]
+pub(crate) struct ButtonsState {
+    pub pressed: [bool; 2],
+    pub triggered: [bool; 2],
+    /// Scheduled button edges sorted by time.
+    pub edges: Vec<ButtonEdge>,
+}
+
+impl ButtonsState {
+    pub fn index(button_type: ButtonType) -> usize {
+        match button_type {
+            ButtonType::One => 0,
+            ButtonType::Ten => 1,
+        }
+    }
+
+    /// Schedules button press that starts at `at` and lasts for `duration` ms.
+    pub fn schedule_press(&mut self, button_type: ButtonType, at: u64, duration: u64) {
+        self.edges.push((at, button_type, true));
+        self.edges.push((at + duration, button_type, false));
+        self.edges.sort_by_key(|(at, _, _)| *at);
+    }
+
+    /// Returns time of the next scheduled button edge.
+    pub fn next_edge(&self) -> Option<u64> {
+        self.edges.first().map(|(at, _, _)| *at)
+    }
+
+    /// Applies all edges scheduled for the specified time and returns `true` if any of the buttons
+    /// has been triggered (rising edge, like EXTI line on the real hardware).
+    pub fn apply_edges(&mut self, now: u64) -> bool {
+        let mut triggered = false;
+        while let Some((at, button_type, is_pressed)) = self.edges.first().cloned() {
+            if at > now {
+                break;
+            }
+
+            self.edges.remove(0);
+
+            let index = Self::index(button_type);
+            if is_pressed && !self.pressed[index] {
+                self.triggered[index] = true;
+                triggered = true;
+            }
+            self.pressed[index] = is_pressed;
+        }
+
+        triggered
+    }
+}
+
+impl ButtonsHardware for SystemHardwareImpl {
+    fn is_button_pressed(&self, button_type: ButtonType) -> bool {
+        self.state.borrow().buttons.pressed[ButtonsState::index(button_type)]
+    }
+
+    fn is_button_triggered(&self, button_type: ButtonType) -> bool {
+        self.state.borrow().buttons.triggered[ButtonsState::index(button_type)]
+    }
+
+    fn reactivate_button(&self, button_type: ButtonType) {
+        self.state.borrow_mut().buttons.triggered[ButtonsState::index(button_type)] = false;
+    }
+}
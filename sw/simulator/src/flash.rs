@@ -0,0 +1,119 @@
+use crate::system::SystemHardwareImpl;
+use kroneum_api::flash::FlashHardware;
+use std::{cell::UnsafeCell, fs, io, path::PathBuf};
+
+/// Size of the single flash page in bytes (the same as on STM32F04x).
+const PAGE_SIZE: usize = 1024;
+
+/// Number of half-words in both flash pages used by storage and the one used by blob storage.
+const MEMORY_SIZE: usize = 3 * PAGE_SIZE / 2;
+
+/// Simulated flash memory. Storage reads and writes it directly through raw pointers, the same way
+/// it does on the real hardware, so memory lives on the heap and never moves. If image path is
+/// specified, memory is loaded from and persisted to that file.
+pub(crate) struct FlashState {
+    memory: Box<UnsafeCell<[u16; MEMORY_SIZE]>>,
+    image_path: Option<PathBuf>,
+}
+
+impl Default for FlashState {
+    fn default() -> Self {
+        FlashState {
+            memory: Box::new(UnsafeCell::new([0xffff; MEMORY_SIZE])),
+            image_path: None,
+        }
+    }
+}
+
+impl FlashState {
+    /// Loads flash memory from the image file, if file doesn't exist memory is considered erased.
+    pub fn load(image_path: PathBuf) -> io::Result<Self> {
+        let mut flash = FlashState {
+            image_path: Some(image_path.clone()),
+            ..Default::default()
+        };
+
+        match fs::read(&image_path) {
+            Ok(image) => {
+                let memory = flash.memory.get_mut();
+                for (index, half_word) in image.chunks(2).take(MEMORY_SIZE).enumerate() {
+                    memory[index] =
+                        u16::from_le_bytes([half_word[0], *half_word.get(1).unwrap_or(&0xff)]);
+                }
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+
+        Ok(flash)
+    }
+
+    /// Writes flash memory to the image file (if specified).
+    pub fn persist(&self) -> io::Result<()> {
+        if let Some(ref image_path) = self.image_path {
+            let image = self
+                .memory()
+                .iter()
+                .flat_map(|half_word| half_word.to_le_bytes().to_vec())
+                .collect::<Vec<u8>>();
+            fs::write(image_path, image)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns a copy of the flash memory used by the storage and blob storage.
+    pub fn memory(&self) -> [u16; MEMORY_SIZE] {
+        unsafe { *self.memory.get() }
+    }
+
+    fn page_addresses(&self) -> [usize; 2] {
+        let base_address = self.memory.get() as usize;
+        [base_address, base_address + PAGE_SIZE]
+    }
+
+    fn blob_page_address(&self) -> usize {
+        self.memory.get() as usize + 2 * PAGE_SIZE
+    }
+
+    fn erase_page(&self, page_address: usize) {
+        let page_index = (page_address - self.page_addresses()[0]) / PAGE_SIZE;
+        let memory = unsafe { &mut *self.memory.get() };
+        let half_words_per_page = PAGE_SIZE / 2;
+        for half_word in memory
+            [page_index * half_words_per_page..(page_index + 1) * half_words_per_page]
+            .iter_mut()
+        {
+            *half_word = 0xffff;
+        }
+    }
+}
+
+impl FlashHardware for SystemHardwareImpl {
+    fn page_addresses(&self) -> [usize; 2] {
+        self.state.borrow().flash.page_addresses()
+    }
+
+    fn blob_page_address(&self) -> usize {
+        self.state.borrow().flash.blob_page_address()
+    }
+
+    fn erase_page(&self, page_address: usize) {
+        let state = self.state.borrow();
+        state.flash.erase_page(page_address);
+        state
+            .flash
+            .persist()
+            .expect("Failed to persist flash image.");
+    }
+
+    fn enable_write_mode(&self) {}
+
+    fn disable_write_mode(&self) {
+        self.state
+            .borrow()
+            .flash
+            .persist()
+            .expect("Failed to persist flash image.");
+    }
+}
@@ -0,0 +1,16 @@
+#![doc = "Host-side simulator for the Kroneum Firmware"]
+#![deny(warnings)]
+
+mod adc;
+mod beeper;
+mod buttons;
+mod flash;
+mod radio;
+mod rtc;
+mod simulator;
+mod system;
+mod systick;
+mod timer;
+mod usb;
+
+pub use crate::{beeper::BeeperTone, simulator::Simulator};
@@ -0,0 +1,27 @@
+use crate::system::SystemHardwareImpl;
+use bare_metal::CriticalSection;
+use kroneum_api::{array::Array, radio::RadioHardware};
+
+/// Simulated radio isn't connected to anything, every SPI transfer reads zeros back.
+impl RadioHardware for SystemHardwareImpl {
+    fn setup(&mut self, _cs: &CriticalSection) {}
+
+    fn transfer(&mut self, payload: Array<u8>) -> Result<Array<u8>, ()> {
+        let mut response = Array::new();
+        for _ in 0..payload.len() {
+            response.push(0);
+        }
+
+        Ok(response)
+    }
+
+    fn enable_chip(&mut self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn disable_chip(&mut self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn teardown(&mut self, _cs: &CriticalSection) {}
+}
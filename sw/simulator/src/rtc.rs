@@ -0,0 +1,138 @@
+use crate::system::SystemHardwareImpl;
+use kroneum_api::{
+    rtc::RTCHardware,
+    time::{BCDTime, Time},
+};
+
+/// Number of milliseconds in a day, RTC time wraps around at midnight.
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Simulated RTC keeps time of the day with millisecond precision, but exposes it only with the
+/// second precision, just like the real hardware does.
+#[derive(Default)]
+pub(crate) struct RTCState {
+    pub is_running: bool,
+    /// Time of the day (in ms) captured at `captured_at` virtual time.
+    time_ms: u64,
+    captured_at: u64,
+    /// Time of the day (in seconds) alarm is set to, `None` if alarm is disabled.
+    alarm: Option<u32>,
+}
+
+impl RTCState {
+    /// Returns time of the day in ms at the specified virtual time.
+    fn time_ms(&self, now: u64) -> u64 {
+        let elapsed = if self.is_running {
+            now - self.captured_at
+        } else {
+            0
+        };
+
+        (self.time_ms + elapsed) % DAY_MS
+    }
+
+    fn capture(&mut self, now: u64) {
+        self.time_ms = self.time_ms(now);
+        self.captured_at = now;
+    }
+
+    pub fn start(&mut self, now: u64) {
+        self.capture(now);
+        self.is_running = true;
+    }
+
+    pub fn stop(&mut self, now: u64) {
+        self.capture(now);
+        self.is_running = false;
+        self.alarm = None;
+    }
+
+    pub fn time(&self, now: u64) -> Time {
+        Time::from_seconds((self.time_ms(now) / 1000) as u32)
+    }
+
+    pub fn set_time(&mut self, now: u64, time: Time) {
+        self.time_ms = u64::from(seconds_of_day(time)) * 1000;
+        self.captured_at = now;
+    }
+
+    pub fn alarm(&self) -> Option<Time> {
+        self.alarm.map(Time::from_seconds)
+    }
+
+    pub fn set_alarm(&mut self, time: Time) {
+        self.alarm = Some(seconds_of_day(time));
+    }
+
+    pub fn disable_alarm(&mut self) {
+        self.alarm = None;
+    }
+
+    /// Returns time left until alarm fires (if enabled).
+    pub fn time_to_alarm(&self, now: u64) -> Option<Time> {
+        if !self.is_running {
+            return None;
+        }
+
+        let day_seconds = (DAY_MS / 1000) as u32;
+        let time = (self.time_ms(now) / 1000) as u32;
+        self.alarm
+            .map(|alarm| Time::from_seconds((alarm + day_seconds - time) % day_seconds))
+    }
+
+    /// Returns virtual time when alarm is supposed to fire next time (if enabled).
+    pub fn next_alarm(&self, now: u64) -> Option<u64> {
+        if !self.is_running {
+            return None;
+        }
+
+        self.alarm.map(|alarm| {
+            let alarm_ms = u64::from(alarm) * 1000;
+            match (alarm_ms + DAY_MS - self.time_ms(now)) % DAY_MS {
+                0 => now + DAY_MS,
+                delta => now + delta,
+            }
+        })
+    }
+}
+
+fn seconds_of_day(time: Time) -> u32 {
+    u32::from(time.hours) * 3600 + u32::from(time.minutes) * 60 + u32::from(time.seconds)
+}
+
+impl RTCHardware for SystemHardwareImpl {
+    fn setup(&self) {
+        let mut state = self.state.borrow_mut();
+        let now = state.now;
+        state.rtc.start(now);
+    }
+
+    fn teardown(&self) {
+        let mut state = self.state.borrow_mut();
+        let now = state.now;
+        state.rtc.stop(now);
+    }
+
+    fn get_time(&self) -> BCDTime {
+        let state = self.state.borrow();
+        state.rtc.time(state.now).into()
+    }
+
+    fn get_alarm(&self) -> BCDTime {
+        self.state.borrow().rtc.alarm().unwrap_or_default().into()
+    }
+
+    fn set_time(&self, bcd_time: BCDTime) {
+        let mut state = self.state.borrow_mut();
+        let now = state.now;
+        state.rtc.set_time(now, bcd_time.into());
+    }
+
+    fn set_alarm(&self, bcd_time: BCDTime) {
+        self.state.borrow_mut().rtc.set_alarm(bcd_time.into());
+    }
+
+    fn disable_alarm(&self) {
+        self.state.borrow_mut().rtc.disable_alarm();
+    }
+}
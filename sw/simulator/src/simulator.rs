@@ -0,0 +1,353 @@
+use crate::{
+    beeper::BeeperTone,
+    flash::FlashState,
+    system::{SimulatorState, SystemHardwareImpl},
+    systick::SystickHardwareImpl,
+};
+use bare_metal::CriticalSection;
+use kroneum_api::{
+    adc::ADCChannel,
+    array::Array,
+    buttons::ButtonType,
+    system::System,
+    systick::SysTick,
+    time::Time,
+    usb::{command_packet::CommandPacket, endpoint::DeviceEndpoint},
+};
+use std::{cell::RefCell, io, path::PathBuf, rc::Rc};
+
+/// Maximum number of USB interrupts processed for a single host command, it protects simulator
+/// from spinning forever if device keeps producing transactions.
+const MAX_USB_INTERRUPTS_PER_COMMAND: usize = 100;
+
+/// Maximum time (in ms) simulator waits for the buttons polling to complete.
+const MAX_BUTTONS_SETTLE_TIME: u64 = 10_000;
+
+/// Asynchronous events simulated hardware can generate, the order defines priority of the events
+/// that happen at the same moment of virtual time.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+enum Event {
+    Button,
+    Timer,
+    SysTick,
+    Alarm,
+}
+
+/// Host-side simulator that runs unmodified `System` on top of the simulated hardware: virtual
+/// clock, scriptable buttons, file-backed flash, beeper that records tones it plays and USB
+/// peripheral the host can send commands to.
+pub struct Simulator {
+    state: Rc<RefCell<SimulatorState>>,
+    system: System<SystemHardwareImpl, SystickHardwareImpl>,
+    resets: usize,
+}
+
+impl Simulator {
+    /// Creates simulator with the pristine in-memory flash.
+    pub fn new() -> Self {
+        Self::with_flash(FlashState::default())
+    }
+
+    /// Creates simulator with the flash backed by the specified image file. If file doesn't exist
+    /// it will be created as soon as device writes something to the flash.
+    pub fn with_flash_image<P: Into<PathBuf>>(image_path: P) -> io::Result<Self> {
+        Ok(Self::with_flash(FlashState::load(image_path.into())?))
+    }
+
+    fn with_flash(flash: FlashState) -> Self {
+        let state = Rc::new(RefCell::new(SimulatorState {
+            flash,
+            ..Default::default()
+        }));
+
+        let mut simulator = Simulator {
+            system: Self::boot(&state),
+            state,
+            resets: 0,
+        };
+        simulator.system.sleep();
+        simulator
+    }
+
+    fn boot(
+        state: &Rc<RefCell<SimulatorState>>,
+    ) -> System<SystemHardwareImpl, SystickHardwareImpl> {
+        System::run(
+            SystemHardwareImpl::new(state.clone()),
+            SysTick::new(SystickHardwareImpl::new(state.clone())),
+        )
+    }
+
+    /// Returns current virtual time in ms.
+    pub fn now(&self) -> u64 {
+        self.state.borrow().now
+    }
+
+    /// Moves virtual clock forward by the specified number of ms dispatching all hardware events
+    /// (buttons, timer, SysTick and RTC alarm) that happen in the meantime.
+    pub fn advance(&mut self, ms: u64) {
+        let until = self.now() + ms;
+        while let Some((at, event)) = self.next_event(until) {
+            self.dispatch(at, event);
+        }
+
+        let mut state = self.state.borrow_mut();
+        if state.now < until {
+            state.now = until;
+        }
+    }
+
+    /// Presses specified buttons simultaneously for `duration` ms, releases them and waits until
+    /// device completes buttons polling.
+    pub fn press(&mut self, buttons: &[ButtonType], duration: u64) {
+        let now = self.now();
+        for button_type in buttons {
+            self.schedule_press(*button_type, now, duration);
+        }
+
+        self.advance(duration);
+        self.settle_buttons();
+    }
+
+    /// Schedules press of the specified button at virtual time `at` that lasts for `duration` ms.
+    /// Scheduled presses are processed by `advance`.
+    pub fn schedule_press(&mut self, button_type: ButtonType, at: u64, duration: u64) {
+        self.state
+            .borrow_mut()
+            .buttons
+            .schedule_press(button_type, at, duration);
+    }
+
+    /// Sends command to the device via the System USB endpoint and returns response payload
+    /// without the status byte. Error is returned if device isn't in the Controller mode or it
+    /// responded with an error status.
+    pub fn send_command(&mut self, packet: CommandPacket) -> Result<Vec<u8>, String> {
+        if !self.state.borrow().usb.is_enabled {
+            return Err("Device isn't connected to the host.".to_string());
+        }
+
+        let first_report_index = self.state.borrow().usb.reports.len();
+        self.state
+            .borrow_mut()
+            .usb
+            .receive(DeviceEndpoint::System, Array::from(packet).as_ref());
+        self.process_usb_transactions();
+
+        let response = self.state.borrow().usb.reports[first_report_index..]
+            .iter()
+            .find(|(endpoint, _)| *endpoint == DeviceEndpoint::System)
+            .map(|(_, report)| report.clone());
+
+        self.system.sleep();
+        self.reset_if_requested();
+
+        match response {
+            Some(ref report) if !report.is_empty() && report[0] == 0x00 => Ok(report[1..].to_vec()),
+            Some(_) => Err("Device failed to execute command.".to_string()),
+            None => Err("Device didn't respond.".to_string()),
+        }
+    }
+
+    /// Returns all reports device has sent via Keyboard USB endpoint.
+    pub fn keyboard_reports(&self) -> Vec<Vec<u8>> {
+        self.state
+            .borrow()
+            .usb
+            .reports
+            .iter()
+            .filter(|(endpoint, _)| *endpoint == DeviceEndpoint::Keyboard)
+            .map(|(_, report)| report.clone())
+            .collect()
+    }
+
+    /// Returns all tones beeper has played so far.
+    pub fn beeper_log(&self) -> Vec<BeeperTone> {
+        let state = self.state.borrow();
+        state.beeper.log(state.now)
+    }
+
+    /// Clears the log of the tones beeper has played.
+    pub fn clear_beeper_log(&mut self) {
+        self.state.borrow_mut().beeper.tones.clear();
+    }
+
+    /// Checks whether beeper is currently playing.
+    pub fn is_beeper_playing(&self) -> bool {
+        self.state.borrow().beeper.is_pwm_enabled
+    }
+
+    /// Sets value simulated ADC returns for the specified channel.
+    pub fn set_adc_value(&mut self, channel: ADCChannel, value: u16) {
+        self.state.borrow_mut().adc.values[u8::from(channel) as usize] = value;
+    }
+
+    /// Returns current RTC time if RTC is running.
+    pub fn rtc_time(&self) -> Option<Time> {
+        let state = self.state.borrow();
+        if state.rtc.is_running {
+            Some(state.rtc.time(state.now))
+        } else {
+            None
+        }
+    }
+
+    /// Returns RTC alarm time of the day if RTC is running and alarm is set.
+    pub fn rtc_alarm(&self) -> Option<Time> {
+        let state = self.state.borrow();
+        if state.rtc.is_running {
+            state.rtc.alarm()
+        } else {
+            None
+        }
+    }
+
+    /// Returns time left until RTC alarm fires if RTC is running and alarm is set.
+    pub fn time_to_alarm(&self) -> Option<Time> {
+        let state = self.state.borrow();
+        state.rtc.time_to_alarm(state.now)
+    }
+
+    /// Returns the time of the day alarm is set to if device is in Timer role and alarm is pending.
+    pub fn pending_alarm(&self) -> Option<Time> {
+        self.system.pending_alarm()
+    }
+
+    /// Checks whether USB device is enabled (device is in Controller mode).
+    pub fn is_usb_enabled(&self) -> bool {
+        self.state.borrow().usb.is_enabled
+    }
+
+    /// Checks whether device requested to enter deep sleep mode.
+    pub fn is_in_deep_sleep(&self) -> bool {
+        self.state.borrow().is_in_deep_sleep
+    }
+
+    /// Returns number of software resets device has performed.
+    pub fn resets(&self) -> usize {
+        self.resets
+    }
+
+    /// Returns a copy of the flash memory used by the device storage.
+    pub fn flash_memory(&self) -> Vec<u16> {
+        self.state.borrow().flash.memory().to_vec()
+    }
+
+    /// Returns the earliest event scheduled to happen before or at `until`.
+    fn next_event(&self, until: u64) -> Option<(u64, Event)> {
+        let state = self.state.borrow();
+        let events = [
+            (state.buttons.next_edge(), Event::Button),
+            (state.timer.deadline, Event::Timer),
+            (state.systick.deadline(), Event::SysTick),
+            (state.rtc.next_alarm(state.now), Event::Alarm),
+        ];
+
+        events
+            .iter()
+            .filter_map(|(at, event)| at.map(|at| (at, *event)))
+            .filter(|(at, _)| *at <= until)
+            .min_by(|(at_a, event_a), (at_b, event_b)| at_a.cmp(at_b).then(event_a.cmp(event_b)))
+    }
+
+    fn dispatch(&mut self, at: u64, event: Event) {
+        {
+            let mut state = self.state.borrow_mut();
+            if state.now < at {
+                state.now = at;
+            }
+        }
+
+        match event {
+            Event::Button => {
+                let triggered = {
+                    let mut state = self.state.borrow_mut();
+                    let now = state.now;
+                    state.buttons.apply_edges(now)
+                };
+
+                if triggered {
+                    self.system.handle_button_press();
+                }
+            }
+            Event::Timer => {
+                {
+                    let mut state = self.state.borrow_mut();
+                    let now = state.now;
+                    state.timer.deadline = Some(now + state.timer.period);
+                }
+                self.system.handle_timer();
+            }
+            Event::SysTick => {
+                {
+                    let mut state = self.state.borrow_mut();
+                    state.systick.started_at = state.now;
+                }
+                self.system.handle_systick();
+            }
+            Event::Alarm => self.system.handle_alarm(),
+        }
+
+        self.system.sleep();
+        self.reset_if_requested();
+    }
+
+    fn process_usb_transactions(&mut self) {
+        let cs = unsafe { CriticalSection::new() };
+        for _ in 0..MAX_USB_INTERRUPTS_PER_COMMAND {
+            if !self.state.borrow().usb.has_pending_transactions() {
+                break;
+            }
+
+            self.system.handle_usb_packet(&cs);
+        }
+    }
+
+    /// Waits until device completes buttons polling (timer used for polling is stopped).
+    fn settle_buttons(&mut self) {
+        let deadline = self.now() + MAX_BUTTONS_SETTLE_TIME;
+        while self.state.borrow().timer.deadline.is_some() && self.now() < deadline {
+            match self.next_event(deadline) {
+                Some((at, event)) => self.dispatch(at, event),
+                None => break,
+            }
+        }
+    }
+
+    /// Re-creates `System` if device performed software reset. Flash content and RTC are preserved
+    /// while the rest of the peripherals are reset.
+    fn reset_if_requested(&mut self) {
+        if !self.state.borrow().is_reset_requested {
+            return;
+        }
+
+        {
+            let mut state = self.state.borrow_mut();
+            let now = state.now;
+            state.is_reset_requested = false;
+            state.beeper.complete_current_tone(now);
+            state.beeper.is_pwm_enabled = false;
+            state.buttons.pressed = [false; 2];
+            state.buttons.triggered = [false; 2];
+            state.systick = Default::default();
+            state.timer = Default::default();
+            state.usb.is_enabled = false;
+        }
+
+        self.resets += 1;
+        self.system = Self::boot(&self.state);
+        self.system.sleep();
+    }
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        // Don't panic while dropping, flash is persisted on every write anyway.
+        let _ = self.state.borrow().flash.persist();
+    }
+}
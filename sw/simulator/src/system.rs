@@ -0,0 +1,71 @@
+use crate::{
+    adc::ADCState, beeper::BeeperState, buttons::ButtonsState, flash::FlashState, rtc::RTCState,
+    systick::SysTickState, timer::TimerState, usb::USBState,
+};
+use kroneum_api::system::SystemHardware;
+use std::{cell::RefCell, rc::Rc};
+
+/// Fake 12-byte unique device ID reported by the simulated device.
+const DEVICE_ID: [u8; 12] = [
+    0x4b, 0x52, 0x4f, 0x4e, 0x45, 0x55, 0x4d, 0x2d, 0x53, 0x49, 0x4d, 0x00,
+];
+
+/// Hex-encoded version of the `DEVICE_ID`.
+const DEVICE_ID_HEX: &str = "4b524f4e45554d2d53494d00";
+
+/// Flash memory size of the STM32F042 in Kilobytes.
+const FLASH_SIZE_KB: u16 = 32;
+
+/// State of all simulated peripherals. It's shared between the hardware implementation owned by the
+/// `System` and the `Simulator` that drives virtual time and inspects the outcome.
+#[derive(Default)]
+pub(crate) struct SimulatorState {
+    /// Virtual clock in milliseconds since simulator start.
+    pub now: u64,
+    pub adc: ADCState,
+    pub beeper: BeeperState,
+    pub buttons: ButtonsState,
+    pub flash: FlashState,
+    pub rtc: RTCState,
+    pub systick: SysTickState,
+    pub timer: TimerState,
+    pub usb: USBState,
+    pub is_in_deep_sleep: bool,
+    pub is_reset_requested: bool,
+}
+
+pub struct SystemHardwareImpl {
+    pub(crate) state: Rc<RefCell<SimulatorState>>,
+}
+
+impl SystemHardwareImpl {
+    pub(crate) fn new(state: Rc<RefCell<SimulatorState>>) -> Self {
+        SystemHardwareImpl { state }
+    }
+}
+
+impl SystemHardware for SystemHardwareImpl {
+    fn enter_deep_sleep(&mut self) {
+        self.state.borrow_mut().is_in_deep_sleep = true;
+    }
+
+    fn exit_deep_sleep(&mut self) {
+        self.state.borrow_mut().is_in_deep_sleep = false;
+    }
+
+    fn reset(&mut self) {
+        self.state.borrow_mut().is_reset_requested = true;
+    }
+
+    fn device_id(&self) -> &'static [u8; 12] {
+        &DEVICE_ID
+    }
+
+    fn device_id_hex(&self) -> &'static str {
+        DEVICE_ID_HEX
+    }
+
+    fn flash_size_kb(&self) -> u16 {
+        FLASH_SIZE_KB
+    }
+}
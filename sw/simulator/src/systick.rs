@@ -0,0 +1,71 @@
+use crate::system::SimulatorState;
+use kroneum_api::{config::CLOCK_SPEED, systick::SysTickHardware};
+use std::{cell::RefCell, rc::Rc};
+
+#[derive(Default)]
+pub(crate) struct SysTickState {
+    /// Reload period in ms.
+    pub period: u64,
+    pub started_at: u64,
+    pub is_counter_enabled: bool,
+    pub is_interrupt_enabled: bool,
+}
+
+impl SysTickState {
+    /// Returns virtual time when SysTick interrupt fires next time (if enabled).
+    pub fn deadline(&self) -> Option<u64> {
+        if self.is_counter_enabled && self.is_interrupt_enabled {
+            Some(self.started_at + self.period)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct SystickHardwareImpl {
+    state: Rc<RefCell<SimulatorState>>,
+}
+
+impl SystickHardwareImpl {
+    pub(crate) fn new(state: Rc<RefCell<SimulatorState>>) -> Self {
+        Self { state }
+    }
+}
+
+impl SysTickHardware for SystickHardwareImpl {
+    fn configure(&mut self, reload_value: u32) {
+        let mut state = self.state.borrow_mut();
+        state.systick.period = u64::from(reload_value) / u64::from(CLOCK_SPEED / 1000);
+        state.systick.started_at = state.now;
+    }
+
+    fn enable_counter(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.systick.is_counter_enabled = true;
+        state.systick.started_at = state.now;
+    }
+
+    fn disable_counter(&mut self) {
+        self.state.borrow_mut().systick.is_counter_enabled = false;
+    }
+
+    /// Busy waiting is simulated by moving virtual clock forward to the moment counter wraps.
+    fn has_wrapped(&mut self) -> bool {
+        let mut state = self.state.borrow_mut();
+        let wraps_at = state.systick.started_at + state.systick.period;
+        if state.now < wraps_at {
+            state.now = wraps_at;
+        }
+        state.systick.started_at = state.now;
+
+        true
+    }
+
+    fn enable_interrupt(&mut self) {
+        self.state.borrow_mut().systick.is_interrupt_enabled = true;
+    }
+
+    fn disable_interrupt(&mut self) {
+        self.state.borrow_mut().systick.is_interrupt_enabled = false;
+    }
+}
@@ -0,0 +1,23 @@
+use crate::system::SystemHardwareImpl;
+use kroneum_api::timer::TimerHardware;
+
+/// Simulated general purpose timer (TIM2) that fires periodically until it's torn down.
+#[derive(Default)]
+pub(crate) struct TimerState {
+    pub period: u64,
+    /// Virtual time when timer fires next time, `None` if timer isn't running.
+    pub deadline: Option<u64>,
+}
+
+impl TimerHardware for SystemHardwareImpl {
+    fn setup(&self, frequency_hz: u32, reload_value: u32) {
+        let mut state = self.state.borrow_mut();
+        let period = u64::from(reload_value) * 1000 / u64::from(frequency_hz);
+        state.timer.period = period;
+        state.timer.deadline = Some(state.now + period);
+    }
+
+    fn teardown(&self) {
+        self.state.borrow_mut().timer.deadline = None;
+    }
+}
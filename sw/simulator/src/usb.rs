@@ -0,0 +1,156 @@
+use crate::system::SystemHardwareImpl;
+use kroneum_api::usb::{
+    endpoint::{DeviceEndpoint, EndpointDirection, EndpointStatus, EndpointType},
+    Transaction, TransactionFlags, USBHardware, UsbInterrupt,
+};
+use std::{cell::UnsafeCell, collections::VecDeque};
+
+/// Number of 16-bit cells in the Packet Memory Area.
+const PMA_SIZE: usize = 256;
+
+/// Simulated USB peripheral. The host side talks to the device through the Packet Memory Area the
+/// same way real USB peripheral does: it writes OUT packets to the endpoint RX buffer and reads IN
+/// packets from the endpoint TX buffer once device marks it as valid.
+pub(crate) struct USBState {
+    pub is_enabled: bool,
+    pma: Box<UnsafeCell<[u16; PMA_SIZE]>>,
+    /// Completed transactions that haven't been handled by the device yet.
+    transactions: VecDeque<(EndpointType, EndpointDirection)>,
+    /// Packets device has sent to the host via device endpoints.
+    pub reports: Vec<(DeviceEndpoint, Vec<u8>)>,
+}
+
+impl Default for USBState {
+    fn default() -> Self {
+        USBState {
+            is_enabled: false,
+            pma: Box::new(UnsafeCell::new([0; PMA_SIZE])),
+            transactions: VecDeque::new(),
+            reports: Vec::new(),
+        }
+    }
+}
+
+impl USBState {
+    fn pma(&mut self) -> &mut [u16; PMA_SIZE] {
+        self.pma.get_mut()
+    }
+
+    /// Returns `true` if there are transactions device should handle.
+    pub fn has_pending_transactions(&self) -> bool {
+        !self.transactions.is_empty()
+    }
+
+    /// Puts host packet into the RX buffer of the specified endpoint and signals device about it.
+    pub fn receive(&mut self, endpoint: DeviceEndpoint, packet: &[u8]) {
+        let endpoint_index = Into::<u8>::into(EndpointType::Device(endpoint)) as usize;
+        let pma = self.pma();
+
+        let rx_offset = pma[(endpoint_index * 8 + 4) >> 1] as usize;
+        for (index, chunk) in packet.chunks(2).enumerate() {
+            pma[(rx_offset >> 1) + index] =
+                (u16::from(*chunk.get(1).unwrap_or(&0)) << 8) | u16::from(chunk[0]);
+        }
+        pma[(endpoint_index * 8 + 6) >> 1] = 0x8400 | packet.len() as u16;
+
+        self.transactions
+            .push_back((EndpointType::Device(endpoint), EndpointDirection::Receive));
+    }
+
+    /// Reads packet device put into TX buffer of the specified endpoint.
+    fn transmit(&mut self, endpoint: DeviceEndpoint) {
+        let endpoint_type = EndpointType::Device(endpoint);
+        let endpoint_index = Into::<u8>::into(endpoint_type) as usize;
+        let pma = self.pma();
+
+        let tx_offset = pma[(endpoint_index * 8) >> 1] as usize;
+        let tx_count = pma[(endpoint_index * 8 + 2) >> 1] as usize;
+        let packet = (0..tx_count)
+            .map(|index| {
+                let half_word = pma[(tx_offset >> 1) + (index >> 1)];
+                if index & 1 == 0 {
+                    (half_word & 0xff) as u8
+                } else {
+                    (half_word >> 8) as u8
+                }
+            })
+            .collect();
+
+        self.reports.push((endpoint, packet));
+        self.transactions
+            .push_back((endpoint_type, EndpointDirection::Transmit));
+    }
+}
+
+impl USBHardware for SystemHardwareImpl {
+    fn setup(&self) {
+        self.state.borrow_mut().usb.is_enabled = true;
+    }
+
+    fn teardown(&self) {
+        let mut state = self.state.borrow_mut();
+        state.usb.is_enabled = false;
+        state.usb.transactions.clear();
+    }
+
+    fn enable(&self) {}
+
+    fn btable_address(&self) -> usize {
+        self.state.borrow().usb.pma.get() as usize
+    }
+
+    fn transaction(&self) -> Transaction {
+        let (endpoint, direction) = *self
+            .state
+            .borrow()
+            .usb
+            .transactions
+            .front()
+            .expect("There is no pending USB transaction.");
+
+        Transaction {
+            endpoint,
+            direction,
+            flags: TransactionFlags {
+                setup: false,
+                rx: matches!(direction, EndpointDirection::Receive),
+                tx: matches!(direction, EndpointDirection::Transmit),
+            },
+        }
+    }
+
+    fn set_endpoint_status(
+        &self,
+        endpoint: EndpointType,
+        direction: EndpointDirection,
+        status: EndpointStatus,
+    ) {
+        if let (
+            EndpointType::Device(device_endpoint),
+            EndpointDirection::Transmit,
+            EndpointStatus::Valid,
+        ) = (endpoint, direction, status)
+        {
+            self.state.borrow_mut().usb.transmit(device_endpoint);
+        }
+    }
+
+    fn set_address(&self, _address: u8) {}
+
+    fn open_endpoint(&self, _endpoint: EndpointType) {}
+
+    fn close_endpoint(&self, _endpoint: EndpointType) {}
+
+    fn is_interrupt_active(&self, interrupt: UsbInterrupt) -> bool {
+        match interrupt {
+            UsbInterrupt::CorrectTransfer => self.state.borrow().usb.has_pending_transactions(),
+            _ => false,
+        }
+    }
+
+    fn mark_interrupt_as_handled(&self, _interrupt: UsbInterrupt) {}
+
+    fn mark_transaction_as_handled(&self, _endpoint: EndpointType, _direction: EndpointDirection) {
+        self.state.borrow_mut().usb.transactions.pop_front();
+    }
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use common::*;
+use kroneum_api::{
+    alarm::{EscalationConfig, SnoozeConfig},
+    beeper::{
+        melody::Melody,
+        note::Note,
+        volume::{BeeperConfig, Volume},
+    },
+    buttons::ButtonType,
+    time::Time,
+    usb::{
+        command_packet::CommandPacket,
+        commands::{AlarmCommand, BeeperCommand, SystemCommand},
+    },
+};
+use kroneum_sim::Simulator;
+
+#[test]
+fn keeps_wall_clock_time() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::System(SystemCommand::SetTime(Time {
+            hours: 14,
+            minutes: 0,
+            seconds: 0,
+        })),
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::System(SystemCommand::GetTime)),
+        Ok(vec![14, 0, 0])
+    );
+
+    let alarm = Time {
+        hours: 14,
+        minutes: 30,
+        seconds: 0,
+    };
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::SetAt(alarm)),
+    );
+    assert_eq!(simulator.pending_alarm(), Some(alarm));
+
+    simulator.advance(1000);
+    simulator.clear_beeper_log();
+    simulator.advance(30 * 60 * 1000);
+    assert_eq!(simulator.beeper_log()[0].frequency, ALARM_FREQUENCY);
+    assert_eq!(simulator.rtc_alarm(), Some(after(alarm, 10)));
+
+    // Countdown alarms don't reset the clock.
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    simulator.press(&[ButtonType::Ten], SHORT_PRESS);
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    assert_time_to_alarm(&simulator, 10);
+    assert!(simulator.rtc_time().unwrap() > alarm);
+}
+
+#[test]
+fn gives_up_after_last_snooze() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    let config = SnoozeConfig {
+        interval: 20,
+        limit: 2,
+        melody: Melody::Setup,
+    };
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::SetSnooze(config)),
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::Alarm(AlarmCommand::GetSnooze)),
+        Ok(vec![20, 2, 4])
+    );
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(Time::from_seconds(5))),
+    );
+
+    let alarm = simulator.rtc_alarm().unwrap();
+    simulator.advance(5000);
+    assert_eq!(simulator.rtc_alarm(), Some(after(alarm, 20)));
+    assert_eq!(simulator.pending_alarm(), simulator.rtc_alarm());
+
+    // Every snooze plays configured melody instead of the alarm one.
+    for _ in 0..2 {
+        simulator.advance(19_000);
+        simulator.clear_beeper_log();
+        simulator.advance(1000);
+        assert!(simulator
+            .beeper_log()
+            .iter()
+            .any(|tone| tone.frequency == frequency(Note::DSharp5)));
+    }
+
+    assert_eq!(simulator.rtc_alarm(), None);
+    assert_eq!(simulator.pending_alarm(), None);
+
+    simulator.advance(60_000);
+    assert!(!simulator.is_beeper_playing());
+    assert!(simulator.is_in_deep_sleep());
+}
+
+#[test]
+fn escalates_alarm_on_every_snooze() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    let snooze_config = SnoozeConfig {
+        interval: 20,
+        limit: 0,
+        melody: Melody::Setup,
+    };
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::SetSnooze(snooze_config)),
+    );
+    send_ok(
+        &mut simulator,
+        CommandPacket::Beeper(BeeperCommand::SetConfig(BeeperConfig {
+            duty_cycle: 20,
+            alarm_volume: Volume::Normal,
+        })),
+    );
+
+    let escalation_config = EscalationConfig {
+        volume_step: 10,
+        repeat_step: 0,
+        interval_step: 5,
+        limit: 2,
+    };
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::SetEscalation(escalation_config)),
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::Alarm(AlarmCommand::GetEscalation)),
+        Ok(vec![10, 0, 5, 2])
+    );
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(Time::from_seconds(5))),
+    );
+
+    // Alarm itself doesn't escalate.
+    let mut alarm = simulator.rtc_alarm().unwrap();
+    simulator.advance(4000);
+    simulator.clear_beeper_log();
+    simulator.advance(1000);
+    assert!(simulator
+        .beeper_log()
+        .iter()
+        .filter(|tone| tone.frequency > 0)
+        .all(|tone| tone.duty_cycle == 20));
+    assert_eq!(simulator.rtc_alarm(), Some(after(alarm, 20)));
+
+    // Every snooze is louder and comes sooner until the escalation limit is reached.
+    for (interval, duty_cycle, next_interval) in [(20, 30, 15), (15, 40, 10), (10, 40, 10)].iter() {
+        alarm = after(alarm, *interval);
+        simulator.advance(u64::from(*interval) * 1000 - 1000);
+        simulator.clear_beeper_log();
+        simulator.advance(1000);
+        assert!(simulator
+            .beeper_log()
+            .iter()
+            .filter(|tone| tone.frequency > 0)
+            .all(|tone| tone.duty_cycle == *duty_cycle));
+        assert_eq!(simulator.rtc_alarm(), Some(after(alarm, *next_interval)));
+    }
+}
@@ -0,0 +1,193 @@
+mod common;
+
+use common::*;
+use kroneum_api::{
+    array::Array,
+    beeper::{
+        note::Note,
+        stored_melody::StoredMelodyConfig,
+        tone::Tone,
+        volume::{BeeperConfig, Volume},
+    },
+    time::Time,
+    usb::{
+        command_packet::CommandPacket,
+        commands::{AlarmCommand, BeeperCommand, MAX_MELODY_TONES},
+    },
+};
+use kroneum_sim::Simulator;
+
+#[test]
+fn plays_morse_code() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    simulator.clear_beeper_log();
+    send_ok(
+        &mut simulator,
+        CommandPacket::Beeper(BeeperCommand::Morse(
+            20,
+            Note::A5,
+            Array::from(b"SOS".as_ref()),
+        )),
+    );
+
+    simulator.advance(3000);
+    assert!(!simulator.is_beeper_playing());
+
+    let marks = simulator
+        .beeper_log()
+        .into_iter()
+        .filter(|tone| tone.frequency > 0)
+        .collect::<Vec<_>>();
+    assert!(marks
+        .iter()
+        .all(|tone| tone.frequency == frequency(Note::A5)));
+    assert_eq!(
+        marks.iter().map(|tone| tone.duration).collect::<Vec<_>>(),
+        [60, 60, 60, 180, 180, 180, 60, 60, 60]
+    );
+
+    // Unsupported characters are rejected.
+    send_failing(
+        &mut simulator,
+        CommandPacket::Beeper(BeeperCommand::Morse(
+            20,
+            Note::A5,
+            Array::from(b"#".as_ref()),
+        )),
+    );
+}
+
+#[test]
+fn streams_long_melody() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    let chunk = |note: Note| Array::from(&[Tone::new(note as u8, 50); MAX_MELODY_TONES]);
+    send_ok(
+        &mut simulator,
+        CommandPacket::Beeper(BeeperCommand::BeginMelody),
+    );
+    for note in [Note::C5, Note::E5].iter() {
+        send_ok(
+            &mut simulator,
+            CommandPacket::Beeper(BeeperCommand::AppendMelody(chunk(*note))),
+        );
+    }
+
+    assert!(!simulator.is_beeper_playing());
+
+    // Device can't buffer more tones, so it starts playing the queued ones to free up room.
+    simulator.clear_beeper_log();
+    send_failing(
+        &mut simulator,
+        CommandPacket::Beeper(BeeperCommand::AppendMelody(chunk(Note::G5))),
+    );
+    assert!(simulator.is_beeper_playing());
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Beeper(BeeperCommand::AppendMelody(chunk(Note::G5))),
+    );
+    send_ok(
+        &mut simulator,
+        CommandPacket::Beeper(BeeperCommand::CommitMelody),
+    );
+
+    simulator.advance(3 * MAX_MELODY_TONES as u64 * 50 + 1000);
+    assert!(!simulator.is_beeper_playing());
+
+    let frequencies = simulator
+        .beeper_log()
+        .iter()
+        .map(|tone| tone.frequency)
+        .collect::<Vec<_>>();
+    assert_eq!(frequencies.len(), 3 * MAX_MELODY_TONES);
+    assert_eq!(frequencies[0], frequency(Note::C5));
+    assert_eq!(frequencies[MAX_MELODY_TONES], frequency(Note::E5));
+    assert_eq!(frequencies[2 * MAX_MELODY_TONES], frequency(Note::G5));
+}
+
+#[test]
+fn applies_beeper_volume() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Beeper(BeeperCommand::SetConfig(BeeperConfig {
+            duty_cycle: 20,
+            alarm_volume: Volume::Crescendo,
+        })),
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::Beeper(BeeperCommand::GetConfig)),
+        Ok(vec![20, 3])
+    );
+
+    simulator.clear_beeper_log();
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(Time::from_seconds(5))),
+    );
+
+    // Setup melody is played with the configured volume.
+    simulator.advance(1000);
+    assert_eq!(simulator.beeper_log()[0].duty_cycle, 20);
+
+    // Alarm melody starts quietly and gets louder.
+    simulator.clear_beeper_log();
+    simulator.advance(4000);
+    let tones = simulator.beeper_log();
+    assert_eq!(tones[0].duty_cycle, 5);
+    assert!(tones.iter().any(|tone| tone.duty_cycle > 5));
+    assert!(tones.iter().all(|tone| tone.duty_cycle <= 20));
+}
+
+#[test]
+fn plays_stored_melody_when_alarm_fires() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    let tones = [
+        Tone::new(Note::C5 as u8, 100),
+        Tone::new(Note::E5 as u8, 100),
+    ];
+    for tone in tones.iter() {
+        send_ok(
+            &mut simulator,
+            CommandPacket::Beeper(BeeperCommand::AppendStoredMelody(Array::from(&[*tone]))),
+        );
+    }
+    send_ok(
+        &mut simulator,
+        CommandPacket::Beeper(BeeperCommand::SetStoredMelodyConfig(StoredMelodyConfig {
+            alarm: true,
+            snooze: false,
+            setup: false,
+        })),
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::Beeper(BeeperCommand::GetStoredMelodyConfig)),
+        Ok(vec![0b001, 2])
+    );
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(Time::from_seconds(5))),
+    );
+
+    simulator.advance(1000);
+    simulator.clear_beeper_log();
+    simulator.advance(4000);
+
+    let tones = simulator.beeper_log();
+    assert_eq!(tones[0].frequency, frequency(Note::C5));
+    assert_eq!(tones[1].frequency, frequency(Note::E5));
+
+    // Snooze still plays built-in beep melody.
+    simulator.clear_beeper_log();
+    simulator.advance(10 * 1000);
+    assert_eq!(simulator.beeper_log()[0].frequency, frequency(Note::G5));
+}
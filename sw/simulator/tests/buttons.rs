@@ -0,0 +1,74 @@
+mod common;
+
+use common::*;
+use kroneum_api::{
+    buttons::{ButtonType, ButtonsConfig},
+    usb::{command_packet::CommandPacket, commands::SystemCommand},
+};
+use kroneum_sim::Simulator;
+
+#[test]
+fn distinguishes_long_and_very_long_press() {
+    let mut simulator = Simulator::new();
+
+    // Long press on both buttons enters Setup mode instead of switching to Controller role,
+    // very long press resets it.
+    simulator.press(&[ButtonType::One, ButtonType::Ten], LONG_PRESS);
+    assert!(!simulator.is_usb_enabled());
+    simulator.press(&[ButtonType::One, ButtonType::Ten], VERY_LONG_PRESS);
+    assert!(!simulator.is_usb_enabled());
+
+    switch_to_controller(&mut simulator);
+
+    simulator.press(&[ButtonType::One, ButtonType::Ten], LONG_PRESS);
+    assert!(simulator.is_usb_enabled());
+
+    simulator.press(&[ButtonType::One, ButtonType::Ten], VERY_LONG_PRESS);
+    assert!(!simulator.is_usb_enabled());
+}
+
+#[test]
+fn applies_buttons_config() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    let config = ButtonsConfig {
+        long_press: 2500,
+        very_long_press: 6000,
+        ..Default::default()
+    };
+    send_ok(
+        &mut simulator,
+        CommandPacket::System(SystemCommand::SetButtonsConfig(config)),
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::System(SystemCommand::GetButtonsConfig)),
+        Ok(vec![5, 10, 50, 120, 10])
+    );
+
+    // Default very long press isn't long enough anymore.
+    simulator.press(&[ButtonType::One, ButtonType::Ten], VERY_LONG_PRESS);
+    assert!(simulator.is_usb_enabled());
+    simulator.press(&[ButtonType::One, ButtonType::Ten], 6500);
+    assert!(!simulator.is_usb_enabled());
+
+    // Default long press is treated as short one now.
+    simulator.advance(1000);
+    simulator.clear_beeper_log();
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    assert!(simulator.beeper_log().is_empty());
+
+    simulator.press(&[ButtonType::One], 3000);
+    assert!(!simulator.beeper_log().is_empty());
+}
+
+#[test]
+fn double_click_counts_as_two_presses() {
+    let mut simulator = Simulator::new();
+
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    double_click(&mut simulator, ButtonType::Ten);
+
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    assert_time_to_alarm(&simulator, 20);
+}
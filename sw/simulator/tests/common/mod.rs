@@ -0,0 +1,105 @@
+//! Helpers shared by the simulator tests, every test crate uses only a subset of them.
+#![allow(dead_code)]
+
+use kroneum_api::{
+    beeper::{note::Note, tone::Tone},
+    buttons::ButtonType,
+    flash::storage_slot::StorageSlot,
+    shortcut::{Shortcut, ShortcutTrigger},
+    time::Time,
+    usb::{
+        command_packet::CommandPacket,
+        commands::{FlashCommand, KeyboardCommand, MediaKey, SystemCommand},
+    },
+};
+use kroneum_sim::Simulator;
+
+pub const SHORT_PRESS: u64 = 100;
+pub const LONG_PRESS: u64 = 1500;
+pub const VERY_LONG_PRESS: u64 = 5500;
+
+/// Error simulator reports when device responds with a failure to the host command.
+pub const COMMAND_FAILED: &str = "Device failed to execute command.";
+
+/// First tone of the built-in alarm melody (B7).
+pub const ALARM_FREQUENCY: u32 = 3951;
+
+pub fn frequency(note: Note) -> u32 {
+    Tone::new(note as u8, 0).frequency()
+}
+
+/// Sends command to the device and makes sure it succeeds without any response data.
+pub fn send_ok(simulator: &mut Simulator, packet: CommandPacket) {
+    assert_eq!(simulator.send_command(packet), Ok(vec![]));
+}
+
+/// Sends command to the device and makes sure device fails to execute it.
+pub fn send_failing(simulator: &mut Simulator, packet: CommandPacket) {
+    assert_eq!(
+        simulator.send_command(packet),
+        Err(COMMAND_FAILED.to_string())
+    );
+}
+
+pub fn write_flash(simulator: &mut Simulator, slot: StorageSlot, value: u8) {
+    send_ok(
+        simulator,
+        CommandPacket::Flash(FlashCommand::Write(slot, value)),
+    );
+}
+
+/// Returns time of the day that is the specified number of seconds after the given one.
+pub fn after(time: Time, seconds: u32) -> Time {
+    let mut time = time;
+    time.add_seconds(seconds);
+    time
+}
+
+/// Alarm is armed while the button is still pressed, so by the time press completes up to a
+/// second may have already elapsed.
+pub fn assert_time_to_alarm(simulator: &Simulator, seconds: u32) {
+    let time_to_alarm = simulator.time_to_alarm().unwrap();
+    assert!(time_to_alarm <= Time::from_seconds(seconds));
+    assert!(time_to_alarm >= Time::from_seconds(seconds - 1));
+}
+
+/// Enters Timer Setup mode, adds the specified number of minutes with Ⅹ and Ⅰ buttons and starts
+/// the timer.
+pub fn start_timer(simulator: &mut Simulator, minutes: u32) {
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    for _ in 0..minutes / 10 {
+        simulator.press(&[ButtonType::Ten], SHORT_PRESS);
+    }
+    for _ in 0..minutes % 10 {
+        simulator.press(&[ButtonType::One], SHORT_PRESS);
+    }
+    simulator.press(&[ButtonType::Ten], LONG_PRESS);
+}
+
+/// Double clicks the specified button.
+pub fn double_click(simulator: &mut Simulator, button_type: ButtonType) {
+    let now = simulator.now();
+    simulator.schedule_press(button_type, now + 350, SHORT_PRESS);
+    simulator.press(&[button_type], SHORT_PRESS);
+}
+
+pub fn switch_to_controller(simulator: &mut Simulator) {
+    simulator.press(&[ButtonType::One, ButtonType::Ten], VERY_LONG_PRESS);
+    assert!(simulator.is_usb_enabled());
+}
+
+/// Resets the device so that it picks up configuration written to the flash.
+pub fn reset(simulator: &mut Simulator) {
+    send_ok(simulator, CommandPacket::System(SystemCommand::Reset));
+}
+
+/// Configures alarm action, so that device stays connected to the host until alarm fires.
+pub fn set_alarm_action(simulator: &mut Simulator) {
+    send_ok(
+        simulator,
+        CommandPacket::Keyboard(KeyboardCommand::SetShortcut(
+            ShortcutTrigger::Alarm,
+            Shortcut::Media(MediaKey::PlayPause),
+        )),
+    );
+}
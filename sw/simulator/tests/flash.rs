@@ -0,0 +1,77 @@
+mod common;
+
+use common::*;
+use kroneum_api::{
+    array::Array,
+    flash::{storage_slot::StorageSlot, storage_stats::StorageStats},
+    usb::{command_packet::CommandPacket, commands::FlashCommand},
+};
+use kroneum_sim::Simulator;
+use std::{convert::TryFrom, env, fs};
+
+#[test]
+fn reports_flash_stats() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    let stats = |simulator: &mut Simulator| {
+        let response = simulator
+            .send_command(CommandPacket::Flash(FlashCommand::Stats))
+            .unwrap();
+        StorageStats::try_from(Array::from(&response)).unwrap()
+    };
+
+    write_flash(&mut simulator, StorageSlot::Custom(1), 1);
+    assert_eq!(
+        stats(&mut simulator),
+        StorageStats {
+            erase_counts: [0, 0],
+            rollover_count: 0,
+            active_page: 0,
+            used_size: 14,
+            page_size: 1024,
+        }
+    );
+
+    // Enough writes to fill the first page.
+    for value in 0..200 {
+        write_flash(&mut simulator, StorageSlot::Custom(1), value);
+    }
+
+    let stats = stats(&mut simulator);
+    assert_eq!(stats.erase_counts, [0, 1]);
+    assert_eq!(stats.rollover_count, 1);
+    assert_eq!(stats.active_page, 1);
+    assert_eq!(
+        simulator.send_command(CommandPacket::Flash(FlashCommand::Read(
+            StorageSlot::Custom(1)
+        ))),
+        Ok(vec![199])
+    );
+}
+
+#[test]
+fn persists_flash_image() {
+    let image_path = env::temp_dir().join(format!("kroneum-sim-{}.bin", std::process::id()));
+    let _ = fs::remove_file(&image_path);
+
+    {
+        let mut simulator = Simulator::with_flash_image(&image_path).unwrap();
+        switch_to_controller(&mut simulator);
+
+        write_flash(&mut simulator, StorageSlot::Custom(1), 42);
+        // Make device boot into Controller mode next time.
+        write_flash(&mut simulator, StorageSlot::Configuration, 1);
+    }
+
+    let mut simulator = Simulator::with_flash_image(&image_path).unwrap();
+    assert!(simulator.is_usb_enabled());
+    assert_eq!(
+        simulator.send_command(CommandPacket::Flash(FlashCommand::Read(
+            StorageSlot::Custom(1)
+        ))),
+        Ok(vec![42])
+    );
+
+    fs::remove_file(&image_path).unwrap();
+}
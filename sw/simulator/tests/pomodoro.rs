@@ -0,0 +1,85 @@
+mod common;
+
+use common::*;
+use kroneum_api::{
+    beeper::note::Note,
+    buttons::ButtonType,
+    flash::storage_slot::StorageSlot,
+    system::PomodoroConfig,
+    usb::{command_packet::CommandPacket, commands::SystemCommand},
+};
+use kroneum_sim::Simulator;
+
+#[test]
+fn runs_pomodoro_cycles() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    let config = PomodoroConfig {
+        work: 2,
+        short_break: 1,
+        long_break: 3,
+        cycles: 2,
+    };
+    send_ok(
+        &mut simulator,
+        CommandPacket::System(SystemCommand::SetPomodoroConfig(config)),
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::System(SystemCommand::GetPomodoroConfig)),
+        Ok(vec![2, 1, 3, 2])
+    );
+    write_flash(&mut simulator, StorageSlot::Configuration, 2);
+    reset(&mut simulator);
+    assert!(!simulator.is_usb_enabled());
+
+    // Start the first work interval.
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    assert_time_to_alarm(&simulator, 120);
+
+    // Every phase is followed by the next one: first note of its melody and its duration.
+    let phases: [(Note, u32, u32); 4] = [
+        (Note::G6, 60, 2 * 60),
+        (Note::C6, 2 * 60, 60),
+        (Note::C7, 3 * 60, 2 * 60),
+        (Note::C6, 2 * 60, 3 * 60),
+    ];
+    for (first_note, next_duration, duration) in phases.iter() {
+        let alarm = simulator.rtc_alarm().unwrap();
+
+        simulator.advance(1000);
+        simulator.clear_beeper_log();
+        simulator.advance(u64::from(*duration) * 1000 - 1000);
+
+        assert_eq!(simulator.beeper_log()[0].frequency, frequency(*first_note));
+        assert_eq!(simulator.rtc_alarm(), Some(after(alarm, *next_duration)));
+    }
+
+    // Long press stops the cycle.
+    simulator.press(&[ButtonType::Ten], LONG_PRESS);
+    assert_eq!(simulator.rtc_alarm(), None);
+}
+
+#[test]
+fn returns_to_boot_role_after_controller() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::System(SystemCommand::SetPomodoroConfig(PomodoroConfig {
+            work: 2,
+            ..PomodoroConfig::default()
+        })),
+    );
+    write_flash(&mut simulator, StorageSlot::Configuration, 2);
+    reset(&mut simulator);
+
+    switch_to_controller(&mut simulator);
+    simulator.press(&[ButtonType::One, ButtonType::Ten], VERY_LONG_PRESS);
+    assert!(!simulator.is_usb_enabled());
+
+    // Long press starts work interval in Pomodoro role instead of entering Timer Setup mode.
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    assert_time_to_alarm(&simulator, 120);
+}
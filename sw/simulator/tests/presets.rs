@@ -0,0 +1,49 @@
+mod common;
+
+use common::*;
+use kroneum_api::{
+    buttons::ButtonType,
+    preset::Preset,
+    time::Time,
+    usb::{command_packet::CommandPacket, commands::AlarmCommand},
+};
+use kroneum_sim::Simulator;
+
+#[test]
+fn recalls_presets() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    let preset = Preset {
+        duration: Time::from_minutes(2),
+        melody: None,
+    };
+    for index in 1..=2 {
+        send_ok(
+            &mut simulator,
+            CommandPacket::Alarm(AlarmCommand::SetPreset(index, preset)),
+        );
+    }
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::DeletePreset(2)),
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::Alarm(AlarmCommand::GetPreset(1))),
+        Ok(vec![0, 2, 0, 0])
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::Alarm(AlarmCommand::GetPreset(2))),
+        Ok(vec![0, 0, 0, 0])
+    );
+
+    simulator.press(&[ButtonType::One, ButtonType::Ten], VERY_LONG_PRESS);
+    assert!(!simulator.is_usb_enabled());
+
+    // Deleted preset can't be recalled.
+    simulator.press(&[ButtonType::Ten], SHORT_PRESS);
+    assert_eq!(simulator.rtc_alarm(), None);
+
+    simulator.press(&[ButtonType::One], SHORT_PRESS);
+    assert_time_to_alarm(&simulator, 120);
+}
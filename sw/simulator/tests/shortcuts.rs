@@ -0,0 +1,177 @@
+mod common;
+
+use common::*;
+use kroneum_api::{
+    buttons::ButtonType,
+    shortcut::{Shortcut, ShortcutTrigger},
+    time::Time,
+    usb::{
+        command_packet::CommandPacket,
+        commands::{AlarmCommand, KeyModifiers, KeyboardCommand, MediaKey},
+    },
+};
+use kroneum_sim::Simulator;
+
+#[test]
+fn sends_shortcuts() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    // Nothing is sent until shortcut is configured.
+    simulator.press(&[ButtonType::One], SHORT_PRESS);
+    assert!(simulator.keyboard_reports().is_empty());
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Keyboard(KeyboardCommand::SetShortcut(
+            ShortcutTrigger::OneShort,
+            Shortcut::Key(KeyModifiers::from(0b11), 0x10),
+        )),
+    );
+    send_ok(
+        &mut simulator,
+        CommandPacket::Keyboard(KeyboardCommand::SetShortcut(
+            ShortcutTrigger::TenLong,
+            Shortcut::Media(MediaKey::Mute),
+        )),
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::Keyboard(KeyboardCommand::GetShortcut(
+            ShortcutTrigger::TenLong
+        ))),
+        Ok(vec![2, 0x04, 0])
+    );
+
+    simulator.press(&[ButtonType::One], SHORT_PRESS);
+    simulator.press(&[ButtonType::Ten], LONG_PRESS);
+    assert_eq!(
+        simulator.keyboard_reports(),
+        vec![
+            vec![0x01, 0b11, 0, 0x10, 0, 0, 0, 0, 0],
+            vec![0x01, 0, 0, 0, 0, 0, 0, 0, 0],
+            vec![0x02, 0x04],
+            vec![0x02, 0x00],
+        ]
+    );
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Keyboard(KeyboardCommand::DeleteShortcut(ShortcutTrigger::TenLong)),
+    );
+    assert_eq!(
+        simulator.send_command(CommandPacket::Keyboard(KeyboardCommand::GetShortcut(
+            ShortcutTrigger::TenLong
+        ))),
+        Ok(vec![0, 0, 0])
+    );
+
+    simulator.press(&[ButtonType::Ten], LONG_PRESS);
+    assert_eq!(simulator.keyboard_reports().len(), 4);
+}
+
+#[test]
+fn sends_alarm_action_in_hybrid_mode() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Keyboard(KeyboardCommand::SetShortcut(
+            ShortcutTrigger::Alarm,
+            Shortcut::Media(MediaKey::PlayPause),
+        )),
+    );
+
+    let time = Time::from_seconds(60);
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(time)),
+    );
+
+    // Device should switch to Timer role, but stay connected to the host.
+    assert!(simulator.is_usb_enabled());
+    assert!(!simulator.is_in_deep_sleep());
+    assert_eq!(simulator.pending_alarm(), simulator.rtc_alarm());
+    assert_eq!(
+        simulator.send_command(CommandPacket::Alarm(AlarmCommand::GetRemaining)),
+        Ok(vec![0, 1, 0])
+    );
+
+    simulator.advance(59 * 1000);
+    assert!(simulator.keyboard_reports().is_empty());
+
+    simulator.advance(1000);
+    assert_eq!(
+        simulator.keyboard_reports(),
+        vec![vec![0x02, 0x20], vec![0x02, 0x00]]
+    );
+
+    // Device disconnects from the host as soon as action is sent.
+    assert!(!simulator.is_usb_enabled());
+    assert_eq!(simulator.pending_alarm(), simulator.rtc_alarm());
+
+    // Snoozes don't repeat the action.
+    simulator.advance(20 * 1000);
+    assert_eq!(simulator.keyboard_reports().len(), 2);
+
+    simulator.press(&[ButtonType::One, ButtonType::Ten], LONG_PRESS);
+    assert_eq!(simulator.pending_alarm(), None);
+    simulator.advance(3000);
+    assert!(simulator.is_in_deep_sleep());
+
+    simulator.press(&[ButtonType::One, ButtonType::Ten], VERY_LONG_PRESS);
+    assert!(simulator.is_usb_enabled());
+
+    simulator.press(&[ButtonType::One, ButtonType::Ten], VERY_LONG_PRESS);
+    assert!(!simulator.is_usb_enabled());
+}
+
+#[test]
+fn sends_alarm_action_only_once_for_recurring_alarm() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+    set_alarm_action(&mut simulator);
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::SetRecurring(Time::from_minutes(1))),
+    );
+    assert!(simulator.is_usb_enabled());
+
+    simulator.advance(60 * 1000);
+    assert_eq!(simulator.keyboard_reports().len(), 2);
+    assert!(!simulator.is_usb_enabled());
+
+    // Recurring alarm doesn't keep device awake.
+    simulator.advance(30 * 1000);
+    assert!(simulator.is_in_deep_sleep());
+
+    simulator.advance(30 * 1000);
+    assert_eq!(simulator.keyboard_reports().len(), 2);
+    assert_eq!(simulator.pending_alarm(), simulator.rtc_alarm());
+}
+
+#[test]
+fn sends_alarm_action_for_alarm_set_with_buttons() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+    set_alarm_action(&mut simulator);
+
+    // Leave Controller role, alarm action is global and applies to alarms set with the buttons too.
+    simulator.press(&[ButtonType::One, ButtonType::Ten], VERY_LONG_PRESS);
+    assert!(!simulator.is_usb_enabled());
+
+    start_timer(&mut simulator, 1);
+    assert!(simulator.is_usb_enabled());
+    assert_eq!(
+        simulator.send_command(CommandPacket::Alarm(AlarmCommand::GetRemaining)),
+        Ok(vec![0, 1, 0])
+    );
+
+    simulator.advance(60 * 1000);
+    assert_eq!(
+        simulator.keyboard_reports(),
+        vec![vec![0x02, 0x20], vec![0x02, 0x00]]
+    );
+    assert!(!simulator.is_usb_enabled());
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use common::*;
+use kroneum_api::{beeper::note::Note, buttons::ButtonType};
+use kroneum_sim::Simulator;
+
+#[test]
+fn boots_into_timer_mode() {
+    let simulator = Simulator::new();
+
+    assert_eq!(simulator.now(), 0);
+    assert!(!simulator.is_usb_enabled());
+    assert!(simulator.is_in_deep_sleep());
+    assert_eq!(simulator.rtc_alarm(), None);
+}
+
+#[test]
+fn plays_alarm_when_timer_expires() {
+    let mut simulator = Simulator::new();
+
+    start_timer(&mut simulator, 3);
+
+    let alarm = simulator.rtc_alarm().unwrap();
+    assert_time_to_alarm(&simulator, 180);
+
+    simulator.advance(5000);
+    assert!(simulator.is_in_deep_sleep());
+    assert_time_to_alarm(&simulator, 175);
+
+    simulator.clear_beeper_log();
+    simulator.advance(3 * 60 * 1000);
+
+    let tones = simulator.beeper_log();
+    assert!(!tones.is_empty());
+    assert_eq!(tones[0].frequency, ALARM_FREQUENCY);
+
+    // Device snoozes alarm for 10 seconds.
+    assert_eq!(simulator.rtc_alarm(), Some(after(alarm, 10)));
+}
+
+#[test]
+fn announces_remaining_time() {
+    let mut simulator = Simulator::new();
+
+    start_timer(&mut simulator, 25);
+
+    simulator.advance(5000);
+    simulator.clear_beeper_log();
+    simulator.press(&[ButtonType::One], SHORT_PRESS);
+    simulator.advance(5000);
+
+    // Two long tones for tens of minutes and five short tones for minutes.
+    let tones = simulator.beeper_log();
+    let count_tones = |duration| {
+        tones
+            .iter()
+            .filter(|tone| tone.frequency == frequency(Note::C6) && tone.duration == duration)
+            .count()
+    };
+    assert_eq!(count_tones(200), 2);
+    assert_eq!(count_tones(50), 5);
+
+    // Timer keeps running.
+    assert_time_to_alarm(&simulator, 25 * 60 - 10);
+}
+
+#[test]
+fn pauses_resumes_and_extends_timer() {
+    let mut simulator = Simulator::new();
+
+    start_timer(&mut simulator, 5);
+    simulator.advance(60_000);
+
+    // Double click on Ⅰ pauses the timer.
+    simulator.clear_beeper_log();
+    double_click(&mut simulator, ButtonType::One);
+    assert_eq!(simulator.beeper_log()[0].frequency, frequency(Note::G6));
+    assert_eq!(simulator.time_to_alarm(), None);
+    assert_eq!(simulator.pending_alarm(), None);
+
+    // Paused timer doesn't fire.
+    simulator.advance(10 * 60 * 1000);
+    assert!(simulator.is_in_deep_sleep());
+
+    // Another double click on Ⅰ resumes it.
+    simulator.clear_beeper_log();
+    double_click(&mut simulator, ButtonType::One);
+    assert_eq!(simulator.beeper_log()[0].frequency, frequency(Note::C6));
+    assert_time_to_alarm(&simulator, 4 * 60 - 1);
+
+    simulator.advance(1000);
+    simulator.clear_beeper_log();
+    simulator.press(&[ButtonType::Ten], SHORT_PRESS);
+    simulator.advance(1000);
+    let tones = simulator.beeper_log();
+    assert_eq!(tones[0].frequency, frequency(Note::C6));
+    assert_eq!(tones[2].frequency, frequency(Note::E6));
+    assert_time_to_alarm(&simulator, 14 * 60 - 3);
+
+    // Long press on Ⅰ still cancels the timer.
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    assert_eq!(simulator.time_to_alarm(), None);
+    assert_eq!(simulator.pending_alarm(), None);
+}
+
+#[test]
+fn repeats_recurring_alarm() {
+    let mut simulator = Simulator::new();
+
+    // Start 2 minutes timer and turn it into a recurring one.
+    start_timer(&mut simulator, 2);
+    simulator.advance(30_000);
+    simulator.press(&[ButtonType::One, ButtonType::Ten], SHORT_PRESS);
+    assert_time_to_alarm(&simulator, 120);
+
+    for _ in 0..3 {
+        let alarm = simulator.rtc_alarm().unwrap();
+        assert_eq!(simulator.pending_alarm(), Some(alarm));
+
+        simulator.advance(30_000);
+        assert!(simulator.is_in_deep_sleep());
+
+        simulator.clear_beeper_log();
+        simulator.advance(90_000);
+        assert_eq!(simulator.beeper_log()[0].frequency, ALARM_FREQUENCY);
+
+        // Alarm is re-armed with the same interval instead of being snoozed.
+        assert_eq!(simulator.rtc_alarm(), Some(after(alarm, 120)));
+    }
+
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    assert_eq!(simulator.rtc_alarm(), None);
+    assert_eq!(simulator.pending_alarm(), None);
+}
+
+#[test]
+fn cancels_timer() {
+    let mut simulator = Simulator::new();
+
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    simulator.press(&[ButtonType::Ten], SHORT_PRESS);
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    assert_time_to_alarm(&simulator, 10);
+
+    simulator.press(&[ButtonType::One, ButtonType::Ten], LONG_PRESS);
+    assert_eq!(simulator.rtc_alarm(), None);
+    assert!(!simulator.is_usb_enabled());
+}
+
+#[test]
+fn limits_timer_to_less_than_a_day() {
+    let mut simulator = Simulator::new();
+
+    // 25 hours is capped at 23 hours 59 minutes instead of wrapping around to 1 hour.
+    start_timer(&mut simulator, 25 * 60);
+    assert_time_to_alarm(&simulator, (24 * 60 - 1) * 60);
+}
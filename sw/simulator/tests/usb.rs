@@ -0,0 +1,251 @@
+mod common;
+
+use common::*;
+use kroneum_api::{
+    adc::ADCChannel,
+    array::Array,
+    buttons::ButtonType,
+    time::Time,
+    usb::{
+        command_packet::CommandPacket,
+        commands::{ADCCommand, AlarmCommand, SystemCommand},
+    },
+};
+use kroneum_sim::Simulator;
+
+#[test]
+fn handles_usb_commands() {
+    let mut simulator = Simulator::new();
+    assert_eq!(
+        simulator.send_command(CommandPacket::System(SystemCommand::GetInfo)),
+        Err("Device isn't connected to the host.".to_string())
+    );
+
+    switch_to_controller(&mut simulator);
+    assert!(!simulator.is_in_deep_sleep());
+
+    assert_eq!(
+        simulator.send_command(CommandPacket::System(SystemCommand::Echo(Array::from(&[
+            1, 2, 3
+        ])))),
+        Ok(vec![1, 2, 3])
+    );
+
+    let info = simulator
+        .send_command(CommandPacket::System(SystemCommand::GetInfo))
+        .unwrap();
+    assert_eq!(&info[..12], b"KRONEUM-SIM\0");
+
+    simulator.set_adc_value(ADCChannel::Channel3, 0x1234);
+    assert_eq!(
+        simulator.send_command(CommandPacket::ADC(ADCCommand::Read(ADCChannel::Channel3))),
+        Ok(vec![0x34, 0x12])
+    );
+}
+
+#[test]
+fn arms_alarm_via_usb() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+    assert_eq!(simulator.pending_alarm(), None);
+
+    let time = Time::from_seconds(315);
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(time)),
+    );
+
+    // Device should switch to Timer role and disconnect from the host.
+    assert!(!simulator.is_usb_enabled());
+    assert_eq!(simulator.time_to_alarm(), Some(time));
+    assert_eq!(simulator.pending_alarm(), simulator.rtc_alarm());
+
+    // Let device finish playing setup melody.
+    simulator.advance(1000);
+    simulator.clear_beeper_log();
+    simulator.advance(314 * 1000);
+
+    let tones = simulator.beeper_log();
+    assert!(!tones.is_empty());
+    assert_eq!(tones[0].frequency, ALARM_FREQUENCY);
+}
+
+#[test]
+fn arms_recurring_alarm_via_usb() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::SetRecurring(Time::from_minutes(20))),
+    );
+    assert!(!simulator.is_usb_enabled());
+
+    let alarm = simulator.rtc_alarm().unwrap();
+    assert_eq!(simulator.time_to_alarm(), Some(Time::from_minutes(20)));
+
+    simulator.advance(20 * 60 * 1000);
+    assert_eq!(simulator.rtc_alarm(), Some(after(alarm, 20 * 60)));
+    assert_eq!(simulator.pending_alarm(), simulator.rtc_alarm());
+}
+
+#[test]
+fn reports_remaining_time_via_usb() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+    set_alarm_action(&mut simulator);
+
+    // There is no pending alarm yet.
+    send_failing(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::GetRemaining),
+    );
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(Time::from_minutes(5))),
+    );
+    simulator.advance(60_000);
+    assert_eq!(
+        simulator.send_command(CommandPacket::Alarm(AlarmCommand::GetRemaining)),
+        Ok(vec![0, 4, 0])
+    );
+
+    // Paused alarm isn't pending.
+    send_ok(&mut simulator, CommandPacket::Alarm(AlarmCommand::Pause));
+    send_failing(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::GetRemaining),
+    );
+}
+
+#[test]
+fn reports_pending_alarm_via_usb() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+    set_alarm_action(&mut simulator);
+
+    send_failing(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::GetPending),
+    );
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(Time::from_minutes(5))),
+    );
+    let alarm = simulator.pending_alarm().unwrap();
+    assert_eq!(
+        simulator.send_command(CommandPacket::Alarm(AlarmCommand::GetPending)),
+        Ok(vec![alarm.hours, alarm.minutes, alarm.seconds])
+    );
+}
+
+#[test]
+fn controls_timer_via_usb() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+    set_alarm_action(&mut simulator);
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(Time::from_minutes(5))),
+    );
+    simulator.advance(60_000);
+
+    send_ok(&mut simulator, CommandPacket::Alarm(AlarmCommand::Pause));
+    assert_eq!(simulator.time_to_alarm(), None);
+    assert_eq!(simulator.pending_alarm(), None);
+    send_failing(&mut simulator, CommandPacket::Alarm(AlarmCommand::Pause));
+
+    // Paused timer doesn't fire.
+    simulator.advance(10 * 60 * 1000);
+    assert!(!simulator.is_beeper_playing());
+
+    send_ok(&mut simulator, CommandPacket::Alarm(AlarmCommand::Resume));
+    assert_eq!(simulator.time_to_alarm(), Some(Time::from_minutes(4)));
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Extend(10)),
+    );
+    assert_eq!(simulator.time_to_alarm(), Some(Time::from_minutes(14)));
+    assert_eq!(simulator.pending_alarm(), simulator.rtc_alarm());
+
+    // Device disconnects from the host once timer is cancelled.
+    simulator.press(&[ButtonType::One], LONG_PRESS);
+    assert_eq!(simulator.pending_alarm(), None);
+    assert!(!simulator.is_usb_enabled());
+
+    simulator.advance(3000);
+    assert!(simulator.is_in_deep_sleep());
+}
+
+#[test]
+fn accepts_only_alarm_commands_in_hybrid_mode() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+    set_alarm_action(&mut simulator);
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(Time::from_minutes(5))),
+    );
+    assert!(simulator.is_usb_enabled());
+
+    send_failing(
+        &mut simulator,
+        CommandPacket::System(SystemCommand::GetInfo),
+    );
+    send_failing(
+        &mut simulator,
+        CommandPacket::ADC(ADCCommand::Read(ADCChannel::Channel3)),
+    );
+    send_failing(&mut simulator, CommandPacket::Alarm(AlarmCommand::Get));
+    send_failing(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(Time::from_minutes(1))),
+    );
+    assert_eq!(simulator.time_to_alarm(), Some(Time::from_minutes(5)));
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Extend(1)),
+    );
+    assert_eq!(simulator.time_to_alarm(), Some(Time::from_minutes(6)));
+}
+
+#[test]
+fn cancels_alarm_via_usb() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+    set_alarm_action(&mut simulator);
+
+    // There is nothing to cancel in Controller role.
+    send_failing(&mut simulator, CommandPacket::Alarm(AlarmCommand::Cancel));
+
+    send_ok(
+        &mut simulator,
+        CommandPacket::Alarm(AlarmCommand::Set(Time::from_minutes(5))),
+    );
+    send_ok(&mut simulator, CommandPacket::Alarm(AlarmCommand::Pause));
+    send_ok(&mut simulator, CommandPacket::Alarm(AlarmCommand::Cancel));
+    assert_eq!(simulator.pending_alarm(), None);
+    assert_eq!(simulator.rtc_alarm(), None);
+
+    // Device disconnects from the host once alarm is cancelled.
+    assert!(!simulator.is_usb_enabled());
+    simulator.advance(3000);
+    assert!(simulator.is_in_deep_sleep());
+    assert!(simulator.keyboard_reports().is_empty());
+}
+
+#[test]
+fn resets_device() {
+    let mut simulator = Simulator::new();
+    switch_to_controller(&mut simulator);
+
+    reset(&mut simulator);
+    assert_eq!(simulator.resets(), 1);
+    assert!(!simulator.is_usb_enabled());
+}